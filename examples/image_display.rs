@@ -47,12 +47,11 @@ fn main() -> std::io::Result<()> {
     // --- Main Game Loop ---
     loop {
         // --- Input Handling ---
-        if poll(Duration::from_millis(0))? {
-            if let Ok(Event::Key(key_event)) = read() {
-                if key_event.code == KeyCode::Char('q') {
-                    break; // Exit loop on 'q'
-                }
-            }
+        if poll(Duration::from_millis(0))?
+            && let Ok(Event::Key(key_event)) = read()
+            && key_event.code == KeyCode::Char('q')
+        {
+            break; // Exit loop on 'q'
         }
 
         // --- Update and Draw Scene ---
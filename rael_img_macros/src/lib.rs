@@ -0,0 +1,158 @@
+//! Compile-time image embedding for `rael_img`.
+//!
+//! This crate provides the [`include_image!`] macro. Use it through `rael_img` with the
+//! `macros` feature, which re-exports it as `rael_img::include_image`; the expanded code
+//! refers to types of `rael_img`.
+
+use image::imageops::FilterType;
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use std::path::PathBuf;
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, LitInt, LitStr, Token};
+
+/// Decodes and resizes an image at compile time and embeds its pixels in the binary.
+///
+/// The first argument is the path of the image, relative to the directory of the
+/// `Cargo.toml` of the crate being compiled. It can be followed by these settings:
+///
+/// * `width = N`, `height = N` - The size in pixels. With only one of them the other keeps
+///   the aspect ratio; with both the image is stretched to exactly that size. Without
+///   either it keeps its size.
+/// * `alpha_threshold = N` - Leave out pixels with an alpha below `N`, from 0 to 255.
+/// * `filter = name` - The resize filter: `nearest`, `triangle` (the default),
+///   `catmull_rom`, `gaussian` or `lanczos3`.
+///
+/// The macro expands to a constant `rael_img::embed::EmbeddedImage`, so it can initialize
+/// a `static` or `const`. The crate is rebuilt when the image changes.
+///
+/// # Examples
+///
+/// ```ignore
+/// use rael_img::embed::EmbeddedImage;
+/// use rael_img::include_image;
+///
+/// static LOGO: EmbeddedImage = include_image!("assets/logo.png", width = 40);
+/// ```
+#[proc_macro]
+pub fn include_image(input: TokenStream) -> TokenStream {
+    let arguments = syn::parse_macro_input!(input as Arguments);
+    match expand(&arguments) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+struct Arguments {
+    path: LitStr,
+    width: Option<u32>,
+    height: Option<u32>,
+    alpha_threshold: u8,
+    filter: FilterType,
+}
+
+impl Parse for Arguments {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut arguments = Arguments {
+            path: input.parse()?,
+            width: None,
+            height: None,
+            alpha_threshold: 0,
+            filter: FilterType::Triangle,
+        };
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let name: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            match name.to_string().as_str() {
+                "width" => arguments.width = Some(positive(&input.parse()?)?),
+                "height" => arguments.height = Some(positive(&input.parse()?)?),
+                "alpha_threshold" => {
+                    arguments.alpha_threshold = input.parse::<LitInt>()?.base10_parse()?;
+                }
+                "filter" => arguments.filter = filter(&input.parse()?)?,
+                _ => {
+                    return Err(syn::Error::new(
+                        name.span(),
+                        "expected `width`, `height`, `alpha_threshold` or `filter`",
+                    ));
+                }
+            }
+        }
+        Ok(arguments)
+    }
+}
+
+fn positive(literal: &LitInt) -> syn::Result<u32> {
+    match literal.base10_parse()? {
+        0 => Err(syn::Error::new(
+            literal.span(),
+            "the size must be at least 1",
+        )),
+        value => Ok(value),
+    }
+}
+
+fn filter(name: &Ident) -> syn::Result<FilterType> {
+    Ok(match name.to_string().as_str() {
+        "nearest" => FilterType::Nearest,
+        "triangle" => FilterType::Triangle,
+        "catmull_rom" => FilterType::CatmullRom,
+        "gaussian" => FilterType::Gaussian,
+        "lanczos3" => FilterType::Lanczos3,
+        _ => {
+            return Err(syn::Error::new(
+                name.span(),
+                "expected `nearest`, `triangle`, `catmull_rom`, `gaussian` or `lanczos3`",
+            ));
+        }
+    })
+}
+
+fn expand(arguments: &Arguments) -> syn::Result<proc_macro2::TokenStream> {
+    let span = arguments.path.span();
+    let root = std::env::var_os("CARGO_MANIFEST_DIR").map_or_else(PathBuf::new, PathBuf::from);
+    let path = root.join(arguments.path.value());
+    let error = |message: String| syn::Error::new(span, message);
+
+    let image = image::open(&path).map_err(|e| error(format!("{}: {e}", path.display())))?;
+    let (source_width, source_height) = (image.width(), image.height());
+    let scaled = |length: u32, from: u32, to: u32| {
+        ((f64::from(length) * f64::from(to) / f64::from(from)).round() as u32).max(1)
+    };
+    let (width, height) = match (arguments.width, arguments.height) {
+        (Some(width), Some(height)) => (width, height),
+        (Some(width), None) => (width, scaled(source_height, source_width, width)),
+        (None, Some(height)) => (scaled(source_width, source_height, height), height),
+        (None, None) => (source_width, source_height),
+    };
+    let image = if (width, height) == (source_width, source_height) {
+        image.to_rgba8()
+    } else {
+        image::imageops::resize(&image, width, height, arguments.filter)
+    };
+
+    let pixels = image
+        .enumerate_pixels()
+        .filter(|(_, _, pixel)| pixel[3] >= arguments.alpha_threshold)
+        .map(|(x, y, pixel)| {
+            let [r, g, b, _] = pixel.0;
+            quote! { (#x, #y, ::rael_img::embed::__Color { r: #r, g: #g, b: #b }) }
+        });
+    let file = LitStr::new(&path.to_string_lossy(), Span::call_site());
+    Ok(quote! {
+        {
+            // Rebuild when the image changes.
+            const _: &[u8] = include_bytes!(#file);
+            ::rael_img::embed::EmbeddedImage {
+                width: #width,
+                height: #height,
+                pixels: &[#(#pixels),*],
+            }
+        }
+    })
+}
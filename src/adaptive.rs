@@ -0,0 +1,364 @@
+//! Rendering that adapts to the capabilities of the current terminal.
+//!
+//! [`AdaptiveRenderer`] picks the best [`OutputMode`] for a [`TermCaps`] and loads the image
+//! at the resolution that mode needs, so the same call shows a pixel-perfect image on kitty
+//! and a glyph mosaic on a plain UTF-8 terminal.
+
+use crate::console::{
+    ConsoleCell, ConsoleGlyphs, ConsoleProfile, console_cells_to_ansi, render_console,
+};
+use crate::decode::DecodePolicy;
+use crate::density::{DensityOptions, render_density};
+use crate::error::RaelImgError;
+use crate::grid::PixelGrid;
+use crate::mosaic::{
+    GlyphCell, GlyphSet, MosaicOptions, cells_to_ansi, cells_to_ansi_256, render_mosaic,
+};
+use crate::protocol::iterm2::{Iterm2Options, to_iterm2};
+use crate::protocol::kitty::{KittyOptions, to_kitty};
+#[cfg(feature = "sixel")]
+use crate::protocol::sixel::{SixelOptions, to_sixel};
+use crate::protocol::{EncodedImage, cursor_to};
+use crate::quantize::ANSI_16;
+use crate::terminal::{CellSize, ColorDepth, TermCaps, UnicodeLevel};
+use crate::width::AmbiguousWidth;
+use crate::{LoadOptions, load_image_with};
+use rael::Color;
+
+/// The ways an image can be shown on a terminal, from best to most compatible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputMode {
+    /// The kitty graphics protocol.
+    Kitty,
+    /// The iTerm2 inline image protocol.
+    Iterm2,
+    /// Sixel graphics. Needs the `sixel` feature.
+    #[cfg(feature = "sixel")]
+    Sixel,
+    /// Colored glyph cells chosen by the mosaic renderer.
+    Mosaic(GlyphSet),
+    /// Glyph cells like [`Mosaic`](OutputMode::Mosaic), with colors from the xterm
+    /// 256-color palette for terminals without truecolor.
+    Palette256(GlyphSet),
+    /// Colored spaces, one averaged color per cell. Works without Unicode.
+    Spaces,
+    /// Glyphs and colors chosen for a 16-color console.
+    Console(ConsoleProfile),
+    /// Plain characters by density, without any color.
+    Density,
+}
+
+impl OutputMode {
+    /// Returns the best mode the given capabilities support. Sixel is only picked with the
+    /// `sixel` feature.
+    pub fn for_caps(caps: &TermCaps) -> Self {
+        if caps.kitty {
+            return OutputMode::Kitty;
+        }
+        if caps.iterm2 {
+            return OutputMode::Iterm2;
+        }
+        #[cfg(feature = "sixel")]
+        if caps.sixel {
+            return OutputMode::Sixel;
+        }
+        if caps.color == ColorDepth::Monochrome {
+            OutputMode::Density
+        } else if caps.color == ColorDepth::Ansi256 && caps.unicode == UnicodeLevel::Unicode {
+            OutputMode::Palette256(GlyphSet::QUADRANTS)
+        } else if caps.color <= ColorDepth::Ansi256 {
+            let glyphs = match (caps.unicode, caps.ambiguous_width) {
+                (UnicodeLevel::Unicode, AmbiguousWidth::Narrow) => ConsoleGlyphs::Shades,
+                _ => ConsoleGlyphs::Ascii,
+            };
+            OutputMode::Console(ConsoleProfile {
+                palette: ANSI_16,
+                glyphs,
+            })
+        } else if caps.unicode == UnicodeLevel::Unicode {
+            OutputMode::Mosaic(GlyphSet::QUADRANTS)
+        } else {
+            OutputMode::Spaces
+        }
+    }
+}
+
+/// The output of an [`AdaptiveRenderer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rendered {
+    /// A graphics protocol escape sequence.
+    Escape(EncodedImage),
+    /// Character cells with colors.
+    Cells(Vec<GlyphCell>),
+    /// Character cells to be written with xterm 256-color escapes.
+    Cells256(Vec<GlyphCell>),
+    /// Character cells with 16-color palette indices.
+    Console(Vec<ConsoleCell>),
+    /// Lines of plain text starting at a `(column, row)` position.
+    Text {
+        /// The `(column, row)` of the first character.
+        position: (u32, u32),
+        /// The lines, top to bottom.
+        lines: Vec<String>,
+    },
+}
+
+impl Rendered {
+    /// Returns the string to write to the terminal, whatever the mode.
+    pub fn to_ansi(&self) -> String {
+        match self {
+            Rendered::Escape(image) => image.escape.clone(),
+            Rendered::Cells(cells) => cells_to_ansi(cells),
+            Rendered::Cells256(cells) => cells_to_ansi_256(cells),
+            Rendered::Console(cells) => console_cells_to_ansi(cells),
+            Rendered::Text { position, lines } => {
+                let mut output = String::new();
+                for (row, line) in (position.1..).zip(lines) {
+                    output.push_str(&format!("\u{1b}[{};{}H{line}", row + 1, position.0 + 1));
+                }
+                output
+            }
+        }
+    }
+}
+
+/// Loads and renders images in the best mode of a terminal.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::adaptive::AdaptiveRenderer;
+/// use rael_img::terminal::TermCaps;
+///
+/// let renderer = AdaptiveRenderer::new(TermCaps::detect());
+/// let rendered = renderer.render("./assets/cover.png", (40, 20), (2, 1)).unwrap();
+/// print!("{}", rendered.to_ansi());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveRenderer {
+    mode: OutputMode,
+    cell_size: CellSize,
+    ambiguous_width: AmbiguousWidth,
+    /// The processing options; size and position come from each render call.
+    options: LoadOptions,
+}
+
+impl AdaptiveRenderer {
+    /// Creates a renderer using the best mode for `caps`.
+    pub fn new(caps: TermCaps) -> Self {
+        Self::with_mode(OutputMode::for_caps(&caps)).ambiguous_width(caps.ambiguous_width)
+    }
+
+    /// Creates a renderer that always uses `mode`.
+    pub fn with_mode(mode: OutputMode) -> Self {
+        Self {
+            mode,
+            cell_size: CellSize::TYPICAL,
+            ambiguous_width: AmbiguousWidth::Narrow,
+            options: LoadOptions::default(),
+        }
+    }
+
+    /// Sets the cell size used to pick the pixel resolution for graphics protocols.
+    pub fn cell_size(mut self, cell_size: CellSize) -> Self {
+        self.cell_size = cell_size;
+        self
+    }
+
+    /// Sets how the terminal draws ambiguous-width glyphs, so character modes only use
+    /// glyphs that cover one cell.
+    pub fn ambiguous_width(mut self, ambiguous_width: AmbiguousWidth) -> Self {
+        self.ambiguous_width = ambiguous_width;
+        self
+    }
+
+    /// If `true`, images are stretched to fill the whole area instead of keeping their
+    /// aspect ratio.
+    pub fn stretch(mut self, stretch: bool) -> Self {
+        self.options.stretch = stretch;
+        self
+    }
+
+    /// Sets how damaged image files are treated.
+    pub fn policy(mut self, policy: DecodePolicy) -> Self {
+        self.options.policy = policy;
+        self
+    }
+
+    /// Sets how images are processed: resampling, finishing steps, stretching and the
+    /// decoding policy.
+    ///
+    /// The size, position and scale in `options` are ignored; every render fits the image
+    /// into its own area.
+    pub fn load_options(mut self, options: LoadOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Returns the selected output mode.
+    pub fn mode(&self) -> OutputMode {
+        self.mode
+    }
+
+    /// Loads the image at `path` and renders it to fit within `area`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file path to the image to be loaded.
+    /// * `area` - The `(columns, rows)` the image may cover. The aspect ratio is preserved
+    ///            unless the renderer stretches.
+    /// * `position` - The `(column, row)` of the top-left cell.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is:
+    /// - `Ok(Rendered)`: The rendered image in the selected mode.
+    /// - `Err(RaelImgError)`: If there was an error loading or encoding the image.
+    pub fn render(
+        &self,
+        path: &str,
+        area: (u32, u32),
+        position: (u32, u32),
+    ) -> Result<Rendered, RaelImgError> {
+        let (columns, rows) = (area.0.max(1), area.1.max(1));
+        match self.mode {
+            OutputMode::Kitty | OutputMode::Iterm2 => {
+                self.render_escape(path, columns, rows, position)
+            }
+            #[cfg(feature = "sixel")]
+            OutputMode::Sixel => self.render_escape(path, columns, rows, position),
+            OutputMode::Mosaic(glyphs) | OutputMode::Palette256(glyphs) => {
+                let options = MosaicOptions {
+                    glyphs,
+                    cell_pixels: (2, 4),
+                    position,
+                    ambiguous_width: self.ambiguous_width,
+                };
+                let pixels = self.load(path, Some(columns * 2), Some(rows * 4), (0, 0))?;
+                let cells = render_mosaic(&pixels, &options);
+                Ok(match self.mode {
+                    OutputMode::Palette256(_) => Rendered::Cells256(cells),
+                    _ => Rendered::Cells(cells),
+                })
+            }
+            OutputMode::Spaces => {
+                let pixels = self.load(path, Some(columns), Some(rows * 2), (0, 0))?;
+                Ok(Rendered::Cells(spaces(&pixels, position)))
+            }
+            OutputMode::Console(profile) => {
+                let pixels = self.load(
+                    path,
+                    Some(columns),
+                    Some(rows * 2),
+                    (position.0, position.1 * 2),
+                )?;
+                Ok(Rendered::Console(render_console(&pixels, &profile)))
+            }
+            OutputMode::Density => {
+                let pixels = self.load(path, Some(columns), Some(rows * 2), (0, 0))?;
+                Ok(Rendered::Text {
+                    position,
+                    lines: render_density(
+                        &pixels,
+                        &DensityOptions {
+                            ambiguous_width: self.ambiguous_width,
+                            ..DensityOptions::default()
+                        },
+                    ),
+                })
+            }
+        }
+    }
+
+    /// Renders the image as the escape sequence of a graphics protocol mode.
+    fn render_escape(
+        &self,
+        path: &str,
+        columns: u32,
+        rows: u32,
+        position: (u32, u32),
+    ) -> Result<Rendered, RaelImgError> {
+        let width = columns * self.cell_size.width.max(1);
+        let height = rows * self.cell_size.height.max(1);
+        let pixels = self.load(path, Some(width), Some(height), (0, 0))?;
+        let mut image = match self.mode {
+            OutputMode::Kitty => to_kitty(
+                &pixels,
+                &KittyOptions {
+                    move_cursor: false,
+                    cell_size: self.cell_size,
+                    ..KittyOptions::default()
+                },
+            ),
+            #[cfg(feature = "sixel")]
+            OutputMode::Sixel => to_sixel(
+                &pixels,
+                &SixelOptions {
+                    move_cursor: false,
+                    cell_size: self.cell_size,
+                    ..SixelOptions::default()
+                },
+            ),
+            _ => to_iterm2(
+                &pixels,
+                &Iterm2Options {
+                    move_cursor: false,
+                    cell_size: self.cell_size,
+                    ..Iterm2Options::default()
+                },
+            )?,
+        };
+        image.placement.column = position.0;
+        image.placement.row = position.1;
+        image.escape.insert_str(0, &cursor_to(&image.placement));
+        Ok(Rendered::Escape(image))
+    }
+
+    /// Loads the image at the resolution a mode needs.
+    fn load(
+        &self,
+        path: &str,
+        width: Option<u32>,
+        height: Option<u32>,
+        position: (u32, u32),
+    ) -> Result<Vec<(u32, u32, Color)>, RaelImgError> {
+        let signed = |value: u32| i32::try_from(value).unwrap_or(i32::MAX);
+        let options = LoadOptions {
+            width,
+            height,
+            position: (signed(position.0), signed(position.1)),
+            scale: 1.0,
+            ..self.options
+        };
+        load_image_with(path, &options)
+    }
+}
+
+/// Renders every pair of vertically adjacent pixels as a space with their average color.
+fn spaces(pixels: &[(u32, u32, Color)], position: (u32, u32)) -> Vec<GlyphCell> {
+    let grid = PixelGrid::from_pixels(pixels);
+    let mut cells = Vec::new();
+    for row in 0..grid.height.div_ceil(2) {
+        for column in 0..grid.width {
+            let top = grid.get(column, row * 2);
+            let bottom = grid.get(column, row * 2 + 1);
+            let color = match (top, bottom) {
+                (Some(a), Some(b)) => Color {
+                    r: ((u16::from(a.r) + u16::from(b.r)) / 2) as u8,
+                    g: ((u16::from(a.g) + u16::from(b.g)) / 2) as u8,
+                    b: ((u16::from(a.b) + u16::from(b.b)) / 2) as u8,
+                },
+                (Some(color), None) | (None, Some(color)) => color,
+                (None, None) => continue,
+            };
+            cells.push(GlyphCell {
+                column: column + position.0,
+                row: row + position.1,
+                glyph: ' ',
+                fg: color,
+                bg: color,
+            });
+        }
+    }
+    cells
+}
@@ -0,0 +1,1118 @@
+//! Animated images.
+//!
+//! Frames are processed like [`load_image_with`](crate::load_image_with) processes a still
+//! image: every frame is resized to the same target size and offset by the same position.
+//! Animated GIF, WebP and PNG (APNG) files are supported; still PNG and WebP files load as
+//! a single frame.
+//!
+//! [`play_animation`] streams frames to a callback for simple players. [`Animation`] keeps
+//! all frames in memory and answers which one to show at any point in time, which suits
+//! game loops with a variable time step. [`AnimationPlayer`] also keeps the playback time
+//! and can be paused, so a game loop only hands it the time each frame took.
+
+use crate::LoadOptions;
+use crate::error::RaelImgError;
+use crate::grid::PixelGrid;
+use crate::pipeline::process_image;
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::{AnimationDecoder, DynamicImage, Frame, Frames, ImageFormat};
+use rael::Color;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Delays below this are treated as [`DEFAULT_DELAY`], like browsers do, because many GIFs
+/// store a delay of 0 expecting it.
+const MIN_DELAY: Duration = Duration::from_millis(20);
+
+/// The delay used for frames that do not specify a usable one.
+pub const DEFAULT_DELAY: Duration = Duration::from_millis(100);
+
+/// How often an [`Animation`] plays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Repeat {
+    /// Loops forever.
+    #[default]
+    Infinite,
+    /// Plays the given number of times, then holds the last frame.
+    Times(u32),
+}
+
+/// The frames of an animation with their delays.
+///
+/// With the `serde` feature this type can be serialized, with its frames, delays, playback
+/// order and repeat count.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::animation::Animation;
+/// use rael_img::LoadOptions;
+/// use std::time::Instant;
+///
+/// let animation = Animation::load("./assets/coin.gif", &LoadOptions::default()).unwrap();
+/// let start = Instant::now();
+/// loop {
+///     // However long the last frame of the game took, this is the right frame now.
+///     let pixels = animation.frame_at(start.elapsed());
+///     // for &(x, y, color) in pixels {
+///     //     canvas.set_pixel(x as usize, y as usize, 1, color);
+///     // }
+/// #   break;
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(into = "AnimationData", try_from = "AnimationData")
+)]
+pub struct Animation {
+    /// Shared so that several playback orders of one sheet do not copy its pixels.
+    frames: Arc<Vec<Vec<(u32, u32, Color)>>>,
+    delays: Vec<Duration>,
+    /// The frame indices in playback order.
+    sequence: Vec<usize>,
+    /// The time at which each step of `sequence` ends, from the start of one loop.
+    ends: Vec<Duration>,
+    repeat: Repeat,
+}
+
+/// The serialized form of an [`Animation`], checked like [`Animation::new`] and
+/// [`Animation::with_order`] when read.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct AnimationData {
+    frames: Vec<crate::serde_color::Pixels>,
+    delays: Vec<Duration>,
+    sequence: Vec<usize>,
+    repeat: Repeat,
+}
+
+#[cfg(feature = "serde")]
+impl From<Animation> for AnimationData {
+    fn from(animation: Animation) -> Self {
+        let frames = Arc::unwrap_or_clone(animation.frames);
+        Self {
+            frames: frames.into_iter().map(crate::serde_color::Pixels).collect(),
+            delays: animation.delays,
+            sequence: animation.sequence,
+            repeat: animation.repeat,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<AnimationData> for Animation {
+    type Error = String;
+
+    fn try_from(data: AnimationData) -> Result<Self, Self::Error> {
+        let frames = data.frames.into_iter().map(|frame| frame.0).collect();
+        Animation::new(frames, data.delays)
+            .ok_or("an animation needs one delay per frame")?
+            .with_order(data.sequence)
+            .map(|animation| animation.with_repeat(data.repeat))
+            .ok_or_else(|| "the playback order refers to a missing frame".to_string())
+    }
+}
+
+impl Animation {
+    /// Creates a looping animation from frames and the delay of each.
+    ///
+    /// Returns `None` if the number of frames and delays differ.
+    pub fn new(frames: Vec<Vec<(u32, u32, Color)>>, delays: Vec<Duration>) -> Option<Self> {
+        if frames.len() != delays.len() {
+            return None;
+        }
+        let sequence = (0..frames.len()).collect();
+        let mut animation = Self {
+            frames: Arc::new(frames),
+            delays,
+            sequence,
+            ends: Vec::new(),
+            repeat: Repeat::Infinite,
+        };
+        animation.update_ends();
+        Some(animation)
+    }
+
+    /// Decodes every frame of an animation file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of an animated GIF, WebP or PNG file.
+    /// * `options` - How every frame is resized and positioned.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is:
+    /// - `Ok(Animation)`: The looping animation.
+    /// - `Err(RaelImgError)`: If the file could not be opened or a frame could not be
+    ///   decoded.
+    pub fn load(path: &str, options: &LoadOptions) -> Result<Self, RaelImgError> {
+        let mut frames = Vec::new();
+        let mut delays = Vec::new();
+        for frame in decode_frames(path, options)? {
+            let (pixels, delay) = process_frame(frame?, options);
+            frames.push(pixels);
+            delays.push(delay);
+        }
+        Ok(Self::new(frames, delays).expect("one delay per frame"))
+    }
+
+    /// Sets how often the animation plays.
+    pub fn with_repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Plays the frames backwards, e.g. for rewind effects.
+    ///
+    /// This reverses the current playback order, so calling it twice restores it.
+    pub fn reversed(mut self) -> Self {
+        self.sequence.reverse();
+        self.update_ends();
+        self
+    }
+
+    /// Plays the frames in a custom order. Frames can repeat or be left out.
+    ///
+    /// The pixels are shared with `self`, so one sprite sheet can back several logical
+    /// animations cheaply:
+    ///
+    /// ```no_run
+    /// use rael_img::animation::Animation;
+    /// use rael_img::LoadOptions;
+    ///
+    /// let sheet = Animation::load("./assets/hero.gif", &LoadOptions::default()).unwrap();
+    /// let walk = sheet.clone().with_order(vec![0, 1, 2, 1]).unwrap();
+    /// let wave = sheet.with_order(vec![3, 4, 5, 4]).unwrap();
+    /// ```
+    ///
+    /// Returns `None` if an index is out of range.
+    pub fn with_order(mut self, order: Vec<usize>) -> Option<Self> {
+        if order.iter().any(|&index| index >= self.frames.len()) {
+            return None;
+        }
+        self.sequence = order;
+        self.update_ends();
+        Some(self)
+    }
+
+    /// Returns the frame indices in playback order.
+    pub fn sequence(&self) -> &[usize] {
+        &self.sequence
+    }
+
+    /// Returns how often the animation plays.
+    pub fn repeat(&self) -> Repeat {
+        self.repeat
+    }
+
+    /// Returns the pixels of every frame.
+    pub fn frames(&self) -> &[Vec<(u32, u32, Color)>] {
+        &self.frames
+    }
+
+    /// Returns how long each frame is shown.
+    pub fn delays(&self) -> &[Duration] {
+        &self.delays
+    }
+
+    /// Returns the number of distinct frames, regardless of the playback order.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns `true` if the animation has no frames.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Returns the length of one loop through the playback order.
+    pub fn loop_duration(&self) -> Duration {
+        self.ends.last().copied().unwrap_or_default()
+    }
+
+    /// Returns the length of the whole playback, or `None` if it loops forever.
+    pub fn total_duration(&self) -> Option<Duration> {
+        match self.repeat {
+            Repeat::Infinite => None,
+            Repeat::Times(times) => Some(self.loop_duration() * times),
+        }
+    }
+
+    /// Returns `true` once a finite animation has played to the end.
+    pub fn is_finished(&self, elapsed: Duration) -> bool {
+        self.total_duration().is_some_and(|total| elapsed >= total)
+    }
+
+    /// Returns the index of the frame to show `elapsed` after playback started.
+    ///
+    /// Looping animations wrap around; finite ones hold their last frame once finished.
+    /// Returns `None` if there is nothing to play.
+    pub fn frame_index_at(&self, elapsed: Duration) -> Option<usize> {
+        let step = self.step_at(elapsed)?;
+        Some(self.sequence[(step % self.sequence.len() as u64) as usize])
+    }
+
+    /// Returns the pixels to show `elapsed` after playback started.
+    ///
+    /// See [`frame_index_at`](Animation::frame_index_at). An empty animation has no pixels.
+    pub fn frame_at(&self, elapsed: Duration) -> &[(u32, u32, Color)] {
+        self.frame_index_at(elapsed)
+            .map_or(&[], |index| &self.frames[index])
+    }
+
+    /// Returns how many steps of the playback order have started by `elapsed`, minus one,
+    /// counting across loops.
+    fn step_at(&self, elapsed: Duration) -> Option<u64> {
+        let steps = self.sequence.len() as u64;
+        let last = steps.checked_sub(1)?;
+        let loop_duration = self.loop_duration().as_nanos();
+        if loop_duration == 0 {
+            return Some(0);
+        }
+        if let Repeat::Times(times) = self.repeat
+            && self.is_finished(elapsed)
+        {
+            return Some((u64::from(times) * steps).max(1) - 1);
+        }
+        let loops = (elapsed.as_nanos() / loop_duration) as u64;
+        let offset = Duration::from_nanos((elapsed.as_nanos() % loop_duration) as u64);
+        let step = (self.ends.partition_point(|&end| end <= offset) as u64).min(last);
+        Some(loops * steps + step)
+    }
+
+    fn update_ends(&mut self) {
+        let delays = &self.delays;
+        self.ends = self
+            .sequence
+            .iter()
+            .scan(Duration::ZERO, |end, &index| {
+                *end += delays[index];
+                Some(*end)
+            })
+            .collect();
+    }
+}
+
+/// Events attached to animation frames, fired as playback reaches them.
+///
+/// Game logic often has to happen on a specific frame: a footstep sound when a foot touches
+/// the ground, damage when a sword swing connects. Attach those to the frames and call
+/// [`advance`](FrameEvents::advance) once per game loop with the same elapsed time used to
+/// pick the frame.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::animation::{Animation, FrameEvents};
+/// use rael_img::LoadOptions;
+/// use std::time::Instant;
+///
+/// #[derive(Clone, Debug)]
+/// enum Cue {
+///     Footstep,
+///     Hit,
+/// }
+///
+/// let attack = Animation::load("./assets/attack.gif", &LoadOptions::default()).unwrap();
+/// let mut events = FrameEvents::new().on(3, Cue::Footstep).on(5, Cue::Hit);
+/// let start = Instant::now();
+/// loop {
+///     let elapsed = start.elapsed();
+///     for cue in events.advance(&attack, elapsed) {
+///         println!("{cue:?}");
+///     }
+///     let pixels = attack.frame_at(elapsed);
+/// #   break;
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameEvents<E> {
+    events: Vec<(usize, E)>,
+    /// The last step of the playback order that was reached.
+    reached: Option<u64>,
+}
+
+impl<E> Default for FrameEvents<E> {
+    fn default() -> Self {
+        Self {
+            events: Vec::new(),
+            reached: None,
+        }
+    }
+}
+
+impl<E: Clone> FrameEvents<E> {
+    /// Creates a set without events.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attaches `event` to the frame at `frame` (an index into
+    /// [`Animation::frames`]). A frame can have several events; they fire in the order they
+    /// were attached.
+    pub fn on(mut self, frame: usize, event: E) -> Self {
+        self.events.push((frame, event));
+        self
+    }
+
+    /// Returns the events of every frame playback entered since the last call.
+    ///
+    /// Frames that a long time step skipped over still fire, in playback order, but at most
+    /// one full loop is caught up. The first call also fires the frame shown at `elapsed`.
+    /// Moving `elapsed` backwards fires nothing until playback passes the last reached
+    /// frame again; use [`reset`](FrameEvents::reset) when restarting an animation.
+    pub fn advance(&mut self, animation: &Animation, elapsed: Duration) -> Vec<E> {
+        let Some(step) = animation.step_at(elapsed) else {
+            return Vec::new();
+        };
+        let steps = animation.sequence.len() as u64;
+        let first = match self.reached {
+            Some(reached) if step <= reached => return Vec::new(),
+            Some(reached) => (reached + 1).max(step.saturating_sub(steps - 1)),
+            None => step,
+        };
+        self.reached = Some(step);
+
+        let mut fired = Vec::new();
+        for step in first..=step {
+            let frame = animation.sequence[(step % steps) as usize];
+            fired.extend(
+                self.events
+                    .iter()
+                    .filter(|(index, _)| *index == frame)
+                    .map(|(_, event)| event.clone()),
+            );
+        }
+        fired
+    }
+
+    /// Forgets how far playback got, so the next [`advance`](FrameEvents::advance) starts
+    /// over.
+    pub fn reset(&mut self) {
+        self.reached = None;
+    }
+}
+
+/// How an [`AnimationPlayer`] runs through the frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum PlayMode {
+    /// Plays once, then holds the last frame.
+    Once,
+    /// Starts over after the last frame, forever.
+    #[default]
+    Loop,
+    /// Plays forwards, then backwards, forever. The first and last frames are not shown
+    /// twice in a row.
+    PingPong,
+}
+
+/// Plays an [`Animation`], keeping track of the playback time.
+///
+/// Call [`update`](AnimationPlayer::update) once per game loop with the time the loop took,
+/// then draw [`frame`](AnimationPlayer::frame). The mode replaces the
+/// [`Repeat`] of the animation.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::animation::{Animation, AnimationPlayer, PlayMode};
+/// use rael_img::LoadOptions;
+/// use std::time::Instant;
+///
+/// let animation = Animation::load("./assets/flag.gif", &LoadOptions::default()).unwrap();
+/// let mut player = AnimationPlayer::new(animation).with_mode(PlayMode::PingPong);
+/// let mut last = Instant::now();
+/// loop {
+///     let now = Instant::now();
+///     player.update(now - last);
+///     last = now;
+///     let pixels = player.frame();
+///     // for &(x, y, color) in pixels {
+///     //     canvas.set_pixel(x as usize, y as usize, 1, color);
+///     // }
+/// #   break;
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnimationPlayer {
+    animation: Animation,
+    /// The animation with the playback order and repeat count of `mode`.
+    playback: Animation,
+    mode: PlayMode,
+    elapsed: Duration,
+    paused: bool,
+}
+
+impl AnimationPlayer {
+    /// Creates a looping player at the start of the animation.
+    pub fn new(animation: Animation) -> Self {
+        Self {
+            playback: Self::playback(&animation, PlayMode::Loop),
+            animation,
+            mode: PlayMode::Loop,
+            elapsed: Duration::ZERO,
+            paused: false,
+        }
+    }
+
+    /// Sets how the player runs through the frames. The playback time is kept.
+    pub fn with_mode(mut self, mode: PlayMode) -> Self {
+        self.set_mode(mode);
+        self
+    }
+
+    /// Changes how the player runs through the frames. The playback time is kept.
+    pub fn set_mode(&mut self, mode: PlayMode) {
+        self.playback = Self::playback(&self.animation, mode);
+        self.mode = mode;
+    }
+
+    /// Returns how the player runs through the frames.
+    pub fn mode(&self) -> PlayMode {
+        self.mode
+    }
+
+    /// Returns the animation being played.
+    pub fn animation(&self) -> &Animation {
+        &self.animation
+    }
+
+    /// Advances the playback time by `delta`, unless the player is paused.
+    pub fn update(&mut self, delta: Duration) {
+        if !self.paused {
+            self.elapsed = self.elapsed.saturating_add(delta);
+        }
+    }
+
+    /// Returns the playback time.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Jumps to a playback time.
+    pub fn seek(&mut self, elapsed: Duration) {
+        self.elapsed = elapsed;
+    }
+
+    /// Jumps back to the first frame. Whether the player is paused does not change.
+    pub fn restart(&mut self) {
+        self.elapsed = Duration::ZERO;
+    }
+
+    /// Stops the playback time until [`resume`](AnimationPlayer::resume) is called.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Lets the playback time run again after [`pause`](AnimationPlayer::pause).
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Returns `true` if the player is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Returns `true` once a [`PlayMode::Once`] player has reached the end.
+    pub fn is_finished(&self) -> bool {
+        self.playback.is_finished(self.elapsed)
+    }
+
+    /// Returns the index of the frame to show now, or `None` if there is nothing to play.
+    pub fn frame_index(&self) -> Option<usize> {
+        self.playback.frame_index_at(self.elapsed)
+    }
+
+    /// Returns the pixels to show now. An empty animation has no pixels.
+    pub fn frame(&self) -> &[(u32, u32, Color)] {
+        self.playback.frame_at(self.elapsed)
+    }
+
+    fn playback(animation: &Animation, mode: PlayMode) -> Animation {
+        let repeat = match mode {
+            PlayMode::Once => Repeat::Times(1),
+            PlayMode::Loop | PlayMode::PingPong => Repeat::Infinite,
+        };
+        let animation = animation.clone().with_repeat(repeat);
+        let sequence = animation.sequence();
+        if mode != PlayMode::PingPong || sequence.len() <= 2 {
+            return animation;
+        }
+        let order = sequence
+            .iter()
+            .chain(sequence[1..sequence.len() - 1].iter().rev())
+            .copied()
+            .collect();
+        animation
+            .with_order(order)
+            .expect("the order only repeats existing frames")
+    }
+}
+
+/// Decodes every frame of an animation file, applying the same resizing and position as
+/// [`load_image_with`](crate::load_image_with).
+///
+/// This is [`Animation::load`]; the frames and their delays are available through
+/// [`Animation::frames`] and [`Animation::delays`], and [`Animation::frame_at`] picks the
+/// frame to show at any point of playback.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::animation::load_animation;
+/// use rael_img::LoadOptions;
+///
+/// let options = LoadOptions {
+///     width: Some(32),
+///     position: (4, 2),
+///     ..LoadOptions::default()
+/// };
+/// let animation = load_animation("./assets/spinner.gif", &options).unwrap();
+/// for (pixels, delay) in animation.frames().iter().zip(animation.delays()) {
+///     println!("{} pixels for {delay:?}", pixels.len());
+/// }
+/// ```
+pub fn load_animation(path: &str, options: &LoadOptions) -> Result<Animation, RaelImgError> {
+    Animation::load(path, options)
+}
+
+/// Decodes an animation and hands its frames to `on_frame` one at a time.
+///
+/// Each frame is decoded, processed and passed to `on_frame` along with how long it should
+/// stay on screen, then dropped, so only one frame is in memory at a time. The animation is
+/// played once; call again to loop.
+///
+/// # Arguments
+///
+/// * `path` - The path of an animated GIF, WebP or PNG file.
+/// * `options` - How every frame is resized and positioned.
+/// * `on_frame` - Called with the pixels and the delay of each frame, in order.
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(usize)`: The number of frames played.
+/// - `Err(RaelImgError)`: If the file could not be opened or a frame could not be
+///   decoded. Frames before the broken one have already been played.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael::{Canvas, Color};
+/// use rael_img::animation::play_animation;
+/// use rael_img::LoadOptions;
+///
+/// let options = LoadOptions {
+///     width: Some(40),
+///     ..LoadOptions::default()
+/// };
+/// let mut canvas = Canvas::new(40, 20, Color { r: 0, g: 0, b: 0 });
+/// play_animation("./assets/spinner.gif", &options, |pixels, delay| {
+///     for &(x, y, color) in pixels {
+///         canvas.set_pixel(x as usize, y as usize, 1, color);
+///     }
+///     print!("{}", canvas.render());
+///     std::thread::sleep(delay);
+/// })
+/// .unwrap();
+/// ```
+pub fn play_animation<F>(
+    path: &str,
+    options: &LoadOptions,
+    mut on_frame: F,
+) -> Result<usize, RaelImgError>
+where
+    F: FnMut(&[(u32, u32, Color)], Duration),
+{
+    let mut count = 0;
+    for frame in decode_frames(path, options)? {
+        let (pixels, delay) = process_frame(frame?, options);
+        on_frame(&pixels, delay);
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// How intermediate frames are generated between two source frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Interpolation {
+    /// No intermediate frames; each source frame is held for its whole delay.
+    #[default]
+    None,
+    /// Crossfades between the two frames.
+    Blend,
+    /// Estimates how the whole frame moved, shifts both frames part of the way and
+    /// crossfades them. Smoother than [`Blend`](Interpolation::Blend) for pans and sliding
+    /// sprites; for other motion it degrades to a slightly offset crossfade.
+    MotionCompensated,
+}
+
+/// The largest shift, in pixels along each axis, that motion estimation considers.
+const MAX_MOTION: i64 = 4;
+
+/// Generates the frame a fraction `t` of the way from `from` to `to`.
+///
+/// Both frames are placed on the same canvas, over the box covering the two of them, so a
+/// sprite that moves between them is generated part of the way along. Pixels present in
+/// only one of them are taken from that frame.
+///
+/// # Arguments
+///
+/// * `from` - The pixels of the earlier frame.
+/// * `to` - The pixels of the later frame.
+/// * `t` - From `0.0` (gives `from`) to `1.0` (gives `to`).
+/// * `mode` - How to generate the frame. [`Interpolation::None`] returns whichever frame is
+///            closer.
+pub fn interpolate_frames(
+    from: &[(u32, u32, Color)],
+    to: &[(u32, u32, Color)],
+    t: f32,
+    mode: Interpolation,
+) -> Vec<(u32, u32, Color)> {
+    let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+    if mode == Interpolation::None {
+        return if t < 0.5 { from.to_vec() } else { to.to_vec() };
+    }
+    // Both grids cover the union of the two frames, so a sprite that moved keeps its place
+    // on the canvas and frames of different sizes still line up.
+    let (min_x, min_y, max_x, max_y) = match (crate::grid::bounds(from), crate::grid::bounds(to)) {
+        (Some(a), Some(b)) => (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3)),
+        (Some(only), None) | (None, Some(only)) => only,
+        (None, None) => return Vec::new(),
+    };
+    let (width, height) = (max_x - min_x + 1, max_y - min_y + 1);
+    let a = PixelGrid::from_region(from, (min_x, min_y), width, height);
+    let b = PixelGrid::from_region(to, (min_x, min_y), width, height);
+
+    let (shift_x, shift_y) = match mode {
+        Interpolation::MotionCompensated => estimate_motion(&a, &b),
+        _ => (0, 0),
+    };
+    let sample = |grid: &PixelGrid, x: f32, y: f32| {
+        let (x, y) = (x.round(), y.round());
+        if x < 0.0 || y < 0.0 {
+            None
+        } else {
+            grid.get(x as u32, y as u32)
+        }
+    };
+
+    let mut output = PixelGrid::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let (fx, fy) = (x as f32, y as f32);
+            let earlier = sample(&a, fx - t * shift_x as f32, fy - t * shift_y as f32);
+            let later = sample(
+                &b,
+                fx + (1.0 - t) * shift_x as f32,
+                fy + (1.0 - t) * shift_y as f32,
+            );
+            let color = match (earlier, later) {
+                (Some(earlier), Some(later)) => Some(mix(earlier, later, t)),
+                (Some(only), None) | (None, Some(only)) => Some(only),
+                (None, None) => None,
+            };
+            if let Some(color) = color {
+                output.set(i64::from(x), i64::from(y), color);
+            }
+        }
+    }
+    output.to_pixels((min_x, min_y))
+}
+
+/// Decodes an animation like [`play_animation`], inserting intermediate frames so frames are
+/// delivered about every `step`.
+///
+/// Each source frame is followed by as many generated frames as fit in its delay, which
+/// smooths low frame rate GIFs when the render loop runs faster than the source. The delays
+/// passed to `on_frame` add up to the source delays. The last frame is held as is.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::animation::{play_animation_interpolated, Interpolation};
+/// use rael_img::LoadOptions;
+/// use std::time::Duration;
+///
+/// let step = Duration::from_millis(1000 / 30);
+/// play_animation_interpolated(
+///     "./assets/walk.gif",
+///     &LoadOptions::default(),
+///     step,
+///     Interpolation::Blend,
+///     |pixels, delay| {
+///         // Draw `pixels`, then wait for `delay`.
+///         std::thread::sleep(delay);
+///     },
+/// )
+/// .unwrap();
+/// ```
+pub fn play_animation_interpolated<F>(
+    path: &str,
+    options: &LoadOptions,
+    step: Duration,
+    mode: Interpolation,
+    mut on_frame: F,
+) -> Result<usize, RaelImgError>
+where
+    F: FnMut(&[(u32, u32, Color)], Duration),
+{
+    let mut count = 0;
+    let mut previous: Option<(Vec<_>, Duration)> = None;
+    for frame in decode_frames(path, options)? {
+        let next = process_frame(frame?, options);
+        if let Some((pixels, delay)) = previous.take() {
+            let steps = if mode == Interpolation::None || step.is_zero() {
+                1
+            } else {
+                (delay.as_secs_f64() / step.as_secs_f64()).round().max(1.0) as u32
+            };
+            on_frame(&pixels, delay / steps);
+            for index in 1..steps {
+                let t = index as f32 / steps as f32;
+                on_frame(
+                    &interpolate_frames(&pixels, &next.0, t, mode),
+                    delay / steps,
+                );
+            }
+            count += steps as usize;
+        }
+        previous = Some(next);
+    }
+    if let Some((pixels, delay)) = previous {
+        on_frame(&pixels, delay);
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Finds the whole-frame shift `(dx, dy)` for which `b(x, y)` best matches
+/// `a(x - dx, y - dy)`, by the mean absolute difference over the overlap. Shifts that
+/// overlap less than half of the pixels of `b` are skipped.
+fn estimate_motion(a: &PixelGrid, b: &PixelGrid) -> (i64, i64) {
+    let present = b.cells.iter().filter(|cell| cell.is_some()).count() as u64;
+    let mut best = ((0, 0), f64::MAX);
+    for dy in -MAX_MOTION..=MAX_MOTION {
+        for dx in -MAX_MOTION..=MAX_MOTION {
+            let (mut error, mut samples) = (0u64, 0u64);
+            for y in 0..i64::from(b.height) {
+                for x in 0..i64::from(b.width) {
+                    let (sx, sy) = (x - dx, y - dy);
+                    if sx < 0 || sy < 0 {
+                        continue;
+                    }
+                    let (Some(p), Some(q)) =
+                        (a.get(sx as u32, sy as u32), b.get(x as u32, y as u32))
+                    else {
+                        continue;
+                    };
+                    error += u64::from(p.r.abs_diff(q.r))
+                        + u64::from(p.g.abs_diff(q.g))
+                        + u64::from(p.b.abs_diff(q.b));
+                    samples += 1;
+                }
+            }
+            // Require a decent overlap so large shifts do not win on a sliver of pixels.
+            if samples == 0 || samples * 2 < present {
+                continue;
+            }
+            // Prefer smaller shifts on ties, so static frames are not shifted.
+            let score = error as f64 / samples as f64 + (dx.abs() + dy.abs()) as f64 * 1e-6;
+            if score < best.1 {
+                best = ((dx, dy), score);
+            }
+        }
+    }
+    best.0
+}
+
+fn mix(a: Color, b: Color, t: f32) -> Color {
+    let channel = |a: u8, b: u8| (f32::from(a) + (f32::from(b) - f32::from(a)) * t).round() as u8;
+    Color {
+        r: channel(a.r, b.r),
+        g: channel(a.g, b.g),
+        b: channel(a.b, b.b),
+    }
+}
+
+/// Opens an animation and returns an iterator decoding its frames lazily.
+///
+/// The format is detected from the content. Still PNG and WebP files give one frame. The
+/// options are only [checked](LoadOptions::validate); the frames are processed by the caller.
+pub(crate) fn decode_frames(
+    path: &str,
+    options: &LoadOptions,
+) -> Result<Frames<'static>, RaelImgError> {
+    options.validate()?;
+    let mut file = BufReader::new(File::open(path)?);
+    let format = image::guess_format(file.fill_buf()?)?;
+    match format {
+        ImageFormat::Gif => Ok(GifDecoder::new(file)?.into_frames()),
+        ImageFormat::WebP => {
+            let decoder = WebPDecoder::new(file)?;
+            if decoder.has_animation() {
+                return Ok(decoder.into_frames());
+            }
+            Ok(still(DynamicImage::from_decoder(decoder)?))
+        }
+        ImageFormat::Png => {
+            let decoder = PngDecoder::new(file)?;
+            if decoder.is_apng()? {
+                return Ok(decoder.apng()?.into_frames());
+            }
+            Ok(still(DynamicImage::from_decoder(decoder)?))
+        }
+        format => Err(RaelImgError::Unsupported(format!(
+            "{format:?} files cannot be animated"
+        ))),
+    }
+}
+
+/// A single frame shown for the default delay.
+fn still(image: DynamicImage) -> Frames<'static> {
+    let frame = Frame::new(image.into_rgba8());
+    Frames::new(Box::new(std::iter::once(Ok(frame))))
+}
+
+/// Resizes and positions one decoded frame and returns its pixels and delay.
+pub(crate) fn process_frame(
+    frame: Frame,
+    options: &LoadOptions,
+) -> (Vec<(u32, u32, Color)>, Duration) {
+    let delay = frame_delay(&frame);
+    let image = DynamicImage::ImageRgba8(frame.into_buffer());
+    (process_image(&image, options), delay)
+}
+
+fn frame_delay(frame: &Frame) -> Duration {
+    let (numerator, denominator) = frame.delay().numer_denom_ms();
+    let delay =
+        Duration::from_secs_f64(f64::from(numerator) / f64::from(denominator.max(1)) / 1000.0);
+    if delay < MIN_DELAY {
+        DEFAULT_DELAY
+    } else {
+        delay
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPRITE: [Color; 4] = [
+        Color { r: 255, g: 0, b: 0 },
+        Color { r: 0, g: 255, b: 0 },
+        Color { r: 0, g: 0, b: 255 },
+        Color {
+            r: 255,
+            g: 255,
+            b: 0,
+        },
+    ];
+
+    /// A 2 × 2 sprite of four colors with its top-left corner at `(x, 0)`.
+    fn sprite_at(x: u32) -> Vec<(u32, u32, Color)> {
+        (0..4)
+            .map(|index| (x + index % 2, index / 2, SPRITE[index as usize]))
+            .collect()
+    }
+
+    fn sorted(mut pixels: Vec<(u32, u32, Color)>) -> Vec<(u32, u32, Color)> {
+        pixels.sort_unstable_by_key(|&(x, y, _)| (y, x));
+        pixels
+    }
+
+    #[test]
+    fn motion_compensated_midpoint_moves_the_sprite_halfway() {
+        let middle = interpolate_frames(
+            &sprite_at(10),
+            &sprite_at(12),
+            0.5,
+            Interpolation::MotionCompensated,
+        );
+        assert_eq!(sorted(middle), sorted(sprite_at(11)));
+    }
+
+    #[test]
+    fn blend_midpoint_keeps_both_frames_in_place() {
+        let middle = interpolate_frames(&sprite_at(10), &sprite_at(12), 0.5, Interpolation::Blend);
+        let mut expected = sprite_at(10);
+        expected.extend(sprite_at(12));
+        assert_eq!(sorted(middle), sorted(expected));
+    }
+
+    #[test]
+    fn blend_mixes_frames_of_different_sizes() {
+        let wide = vec![
+            (4, 4, Color { r: 0, g: 0, b: 0 }),
+            (5, 4, Color { r: 0, g: 0, b: 0 }),
+        ];
+        let narrow = vec![(
+            4,
+            4,
+            Color {
+                r: 200,
+                g: 100,
+                b: 50,
+            },
+        )];
+        let middle = interpolate_frames(&wide, &narrow, 0.5, Interpolation::Blend);
+        assert_eq!(
+            sorted(middle),
+            [
+                (
+                    4,
+                    4,
+                    Color {
+                        r: 100,
+                        g: 50,
+                        b: 25
+                    }
+                ),
+                (5, 4, Color { r: 0, g: 0, b: 0 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn no_interpolation_returns_the_closer_frame() {
+        let (from, to) = (sprite_at(10), sprite_at(12));
+        assert_eq!(
+            interpolate_frames(&from, &to, 0.25, Interpolation::None),
+            from
+        );
+        assert_eq!(
+            interpolate_frames(&from, &to, 0.75, Interpolation::None),
+            to
+        );
+    }
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    /// Three one-pixel frames, shown for 100, 200 and 100 ms.
+    fn three_frames() -> Animation {
+        let frames = (0..3)
+            .map(|index| vec![(index, 0, SPRITE[index as usize])])
+            .collect();
+        Animation::new(frames, vec![ms(100), ms(200), ms(100)]).unwrap()
+    }
+
+    #[test]
+    fn frames_follow_their_delays_and_loop() {
+        let animation = three_frames();
+        assert_eq!(animation.loop_duration(), ms(400));
+        let at = |millis| animation.frame_index_at(ms(millis));
+        assert_eq!(
+            [at(0), at(99), at(100), at(299), at(300)],
+            [0, 0, 1, 1, 2].map(Some)
+        );
+        assert_eq!([at(400), at(550)], [Some(0), Some(1)]);
+    }
+
+    #[test]
+    fn finite_animations_hold_the_last_frame() {
+        let animation = three_frames().with_repeat(Repeat::Times(2));
+        assert_eq!(animation.total_duration(), Some(ms(800)));
+        assert!(!animation.is_finished(ms(799)));
+        assert!(animation.is_finished(ms(800)));
+        assert_eq!(animation.frame_index_at(ms(5_000)), Some(2));
+    }
+
+    #[test]
+    fn order_and_reverse_change_the_sequence() {
+        assert_eq!(three_frames().reversed().sequence(), [2, 1, 0]);
+        let custom = three_frames().with_order(vec![1, 1, 0]).unwrap();
+        assert_eq!(custom.loop_duration(), ms(500));
+        assert_eq!(custom.frame_index_at(ms(450)), Some(0));
+        assert!(three_frames().with_order(vec![3]).is_none());
+        assert!(Animation::new(vec![Vec::new()], Vec::new()).is_none());
+    }
+
+    #[test]
+    fn frame_events_catch_up_on_skipped_frames() {
+        let animation = three_frames();
+        let mut events = FrameEvents::new().on(1, "step").on(2, "swing");
+        assert!(events.advance(&animation, ms(0)).is_empty());
+        assert_eq!(events.advance(&animation, ms(350)), ["step", "swing"]);
+        assert!(events.advance(&animation, ms(360)).is_empty());
+        assert_eq!(events.advance(&animation, ms(500)), ["step"]);
+        events.reset();
+        assert_eq!(events.advance(&animation, ms(350)), ["swing"]);
+    }
+
+    #[test]
+    fn ping_pong_does_not_repeat_the_ends() {
+        let player = AnimationPlayer::new(three_frames()).with_mode(PlayMode::PingPong);
+        let indices: Vec<_> = [0, 100, 300, 400, 600]
+            .into_iter()
+            .map(|millis| {
+                let mut player = player.clone();
+                player.seek(ms(millis));
+                player.frame_index().unwrap()
+            })
+            .collect();
+        assert_eq!(indices, [0, 1, 2, 1, 0]);
+    }
+
+    #[test]
+    fn paused_players_keep_their_time() {
+        let mut player = AnimationPlayer::new(three_frames()).with_mode(PlayMode::Once);
+        player.update(ms(150));
+        player.pause();
+        player.update(ms(1_000));
+        assert_eq!(player.elapsed(), ms(150));
+        assert_eq!(player.frame_index(), Some(1));
+        player.resume();
+        player.update(ms(1_000));
+        assert!(player.is_finished());
+        assert_eq!(player.frame(), three_frames().frames()[2]);
+    }
+
+    #[test]
+    fn apng_frames_load_with_their_delays() {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, 2, 1);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_animated(2, 0).unwrap();
+        let mut writer = encoder.write_header().unwrap();
+        writer.set_frame_delay(1, 20).unwrap();
+        writer
+            .write_image_data(&[255, 0, 0, 255, 0, 0, 0, 0])
+            .unwrap();
+        writer.set_frame_delay(3, 20).unwrap();
+        writer
+            .write_image_data(&[0, 0, 0, 0, 0, 0, 255, 255])
+            .unwrap();
+        writer.finish().unwrap();
+        let path = std::env::temp_dir().join(format!("rael_img-{}-anim.png", std::process::id()));
+        std::fs::write(&path, &bytes).unwrap();
+
+        let options = LoadOptions {
+            alpha_threshold: 1,
+            ..LoadOptions::default()
+        };
+        let animation = Animation::load(&path.to_string_lossy(), &options);
+        std::fs::remove_file(&path).unwrap();
+        let animation = animation.unwrap();
+
+        assert_eq!(animation.delays(), [ms(50), ms(150)]);
+        assert_eq!(animation.frames()[0], [(0, 0, SPRITE[0])]);
+        assert_eq!(animation.frames()[1], [(1, 0, SPRITE[2])]);
+    }
+}
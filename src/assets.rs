@@ -0,0 +1,345 @@
+//! A registry of images under logical names.
+//!
+//! Instead of repeating paths and option tuples wherever an image is drawn, game code
+//! registers every asset once, usually from a manifest file, and then asks for it by name:
+//!
+//! ```toml
+//! [assets.player_idle]
+//! path = "sprites/player_idle.png"
+//! width = 32
+//! height = 32
+//! stretch = true
+//!
+//! [assets.title]
+//! path = "title.rimg"
+//! ```
+//!
+//! Every field of [`LoadOptions`] can be given next to `path`. Images are loaded on first
+//! use and kept until they are reloaded or the registry is dropped. Files ending in `.rimg` are read with
+//! [`load_baked`](crate::baked::load_baked), everything else with [`load_image_with`].
+
+use crate::baked::load_baked;
+use crate::error::RaelImgError;
+use crate::pipeline::Placement;
+use crate::{LoadOptions, load_image_with};
+use rael::Color;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Where an asset comes from and how it is processed.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AssetEntry {
+    /// The image file, relative to the registry's root directory unless absolute.
+    pub path: PathBuf,
+    /// The processing options.
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub options: LoadOptions,
+}
+
+impl AssetEntry {
+    /// Creates an entry for `path` with default options.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            options: LoadOptions::default(),
+        }
+    }
+
+    /// Sets the processing options.
+    pub fn with_options(mut self, options: LoadOptions) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+/// The on-disk layout of a manifest.
+#[cfg(feature = "presets")]
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    assets: BTreeMap<String, AssetEntry>,
+}
+
+/// A stable reference to a registered asset.
+///
+/// Handles stay valid for the lifetime of the [`Assets`] they came from, across reloads and
+/// re-registrations, so draw sites can keep them instead of borrowing pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AssetHandle(usize);
+
+/// The outcome of [`Assets::reload_changed`].
+#[derive(Debug, Default)]
+pub struct ReloadReport {
+    /// The assets whose pixels were replaced.
+    pub reloaded: Vec<AssetHandle>,
+    /// The assets whose file changed but could not be loaded. They keep their previous
+    /// pixels and are retried on the next check.
+    pub failed: Vec<(AssetHandle, RaelImgError)>,
+}
+
+#[derive(Debug, Clone)]
+struct Slot {
+    entry: AssetEntry,
+    pixels: Option<Vec<(u32, u32, Color)>>,
+    generation: u64,
+    modified: Option<SystemTime>,
+}
+
+/// Images registered under logical names and loaded on first use.
+///
+/// Every asset has a [generation](Assets::generation) that increases whenever its pixels
+/// change, either because [`reload_changed`](Assets::reload_changed) noticed a newer file
+/// or because the asset was registered again. Code that derives data from an asset, such as
+/// a scaled or tinted copy, can compare generations to know when to rebuild it.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::assets::{AssetEntry, Assets};
+/// use rael_img::LoadOptions;
+///
+/// let mut assets = Assets::new("./assets");
+/// let sprite = LoadOptions {
+///     width: Some(32),
+///     height: Some(32),
+///     ..LoadOptions::default()
+/// };
+/// let player = assets.register("player_idle", AssetEntry::new("sprites/player_idle.png").with_options(sprite));
+///
+/// loop {
+///     // Pick up edited files once per frame; failed reloads keep the old pixels.
+///     assets.reload_changed();
+///     let pixels = assets.pixels(player).unwrap();
+///     // for &(x, y, color) in pixels {
+///     //     canvas.set_pixel(x as usize, y as usize, 1, color);
+///     // }
+/// #   break;
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Assets {
+    root: PathBuf,
+    names: BTreeMap<String, AssetHandle>,
+    slots: Vec<Slot>,
+}
+
+impl Assets {
+    /// Creates an empty registry whose relative paths are resolved against `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Reads a TOML or JSON manifest. Relative paths in it are resolved against the
+    /// manifest's own directory.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is:
+    /// - `Ok(Assets)`: The registry with every manifest entry; no image is loaded yet.
+    /// - `Err(io::Error)`: If the manifest could not be read or parsed, or its extension is
+    ///   neither `.toml` nor `.json` (kind `Unsupported`).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rael_img::assets::Assets;
+    ///
+    /// let mut assets = Assets::load_manifest("./assets/manifest.toml").unwrap();
+    /// let title = assets.get("title").unwrap();
+    /// ```
+    #[cfg(feature = "presets")]
+    pub fn load_manifest(path: impl AsRef<Path>) -> io::Result<Self> {
+        use crate::preset::PresetFormat;
+
+        let path = path.as_ref();
+        let format = PresetFormat::from_path(path).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("unknown manifest file extension: {}", path.display()),
+            )
+        })?;
+        let text = std::fs::read_to_string(path)?;
+        let manifest: Manifest = match format {
+            PresetFormat::Toml => toml::from_str(&text)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?,
+            PresetFormat::Json => serde_json::from_str(&text)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?,
+        };
+
+        let mut assets = Self::new(path.parent().unwrap_or(Path::new("")));
+        for (name, entry) in manifest.assets {
+            assets.register(name, entry);
+        }
+        Ok(assets)
+    }
+
+    /// Registers an asset and returns its handle.
+    ///
+    /// Registering a name again keeps its handle, replaces the entry and drops the loaded
+    /// pixels, which are then loaded from the new entry on next use.
+    pub fn register(&mut self, name: impl Into<String>, entry: AssetEntry) -> AssetHandle {
+        let name = name.into();
+        if let Some(&handle) = self.names.get(&name) {
+            let slot = &mut self.slots[handle.0];
+            slot.entry = entry;
+            slot.pixels = None;
+            slot.modified = None;
+            slot.generation += 1;
+            return handle;
+        }
+        let handle = AssetHandle(self.slots.len());
+        self.slots.push(Slot {
+            entry,
+            pixels: None,
+            generation: 0,
+            modified: None,
+        });
+        self.names.insert(name, handle);
+        handle
+    }
+
+    /// Returns the handle of the asset called `name`.
+    pub fn handle(&self, name: &str) -> Option<AssetHandle> {
+        self.names.get(name).copied()
+    }
+
+    /// Returns the entry registered as `name`.
+    pub fn entry(&self, name: &str) -> Option<&AssetEntry> {
+        self.handle(name).map(|handle| &self.slots[handle.0].entry)
+    }
+
+    /// Returns the registered names in sorted order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.names.keys().map(String::as_str)
+    }
+
+    /// Returns the pixels of the asset called `name`, loading it on first use.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is:
+    /// - `Ok(&[(u32, u32, rael::Color)])`: The processed pixels.
+    /// - `Err(RaelImgError)`: If no asset has that name (an I/O error of kind
+    ///   `NotFound`) or the image could not be loaded.
+    pub fn get(&mut self, name: &str) -> Result<&[(u32, u32, Color)], RaelImgError> {
+        let handle = self.handle(name).ok_or_else(|| unknown(name))?;
+        self.pixels(handle)
+    }
+
+    /// Returns the current pixels of an asset, loading it on first use.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `handle` belongs to a different registry.
+    pub fn pixels(&mut self, handle: AssetHandle) -> Result<&[(u32, u32, Color)], RaelImgError> {
+        let root = &self.root;
+        let slot = &mut self.slots[handle.0];
+        if slot.pixels.is_none() {
+            slot.modified = modified(root, &slot.entry);
+            slot.pixels = Some(load_entry(root, &slot.entry)?);
+        }
+        Ok(slot.pixels.as_deref().unwrap_or_default())
+    }
+
+    /// Returns how often the pixels of an asset have changed since it was registered.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `handle` belongs to a different registry.
+    pub fn generation(&self, handle: AssetHandle) -> u64 {
+        self.slots[handle.0].generation
+    }
+
+    /// Reloads an asset from disk now, whether or not its file changed.
+    ///
+    /// On failure the previous pixels are kept.
+    pub fn reload(&mut self, handle: AssetHandle) -> Result<(), RaelImgError> {
+        let root = &self.root;
+        let slot = &mut self.slots[handle.0];
+        let stamp = modified(root, &slot.entry);
+        let pixels = load_entry(root, &slot.entry)?;
+        slot.pixels = Some(pixels);
+        slot.modified = stamp;
+        slot.generation += 1;
+        Ok(())
+    }
+
+    /// Reloads every loaded asset whose file has been modified since it was loaded.
+    ///
+    /// Assets that were never loaded are skipped; they read the current file on first use
+    /// anyway. Checking only compares modification times, so it is cheap enough to call once
+    /// per frame.
+    pub fn reload_changed(&mut self) -> ReloadReport {
+        let mut report = ReloadReport::default();
+        for index in 0..self.slots.len() {
+            let slot = &self.slots[index];
+            if slot.pixels.is_none() {
+                continue;
+            }
+            let stamp = modified(&self.root, &slot.entry);
+            if stamp.is_none() || stamp == slot.modified {
+                continue;
+            }
+            let handle = AssetHandle(index);
+            match self.reload(handle) {
+                Ok(()) => report.reloaded.push(handle),
+                Err(error) => report.failed.push((handle, error)),
+            }
+        }
+        report
+    }
+
+    /// Loads every registered asset that is not loaded yet, e.g. behind a loading screen.
+    ///
+    /// Stops at the first asset that fails to load.
+    pub fn load_all(&mut self) -> Result<(), RaelImgError> {
+        for index in 0..self.slots.len() {
+            self.pixels(AssetHandle(index))?;
+        }
+        Ok(())
+    }
+
+    /// Drops the loaded pixels of every asset; they are reloaded on next use.
+    pub fn unload_all(&mut self) {
+        for slot in &mut self.slots {
+            if slot.pixels.take().is_some() {
+                slot.generation += 1;
+            }
+        }
+    }
+}
+
+fn modified(root: &Path, entry: &AssetEntry) -> Option<SystemTime> {
+    std::fs::metadata(root.join(&entry.path))
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+/// Loads the pixels of one entry, resolving its path against `root`.
+fn load_entry(root: &Path, entry: &AssetEntry) -> Result<Vec<(u32, u32, Color)>, RaelImgError> {
+    let path = root.join(&entry.path);
+    let path = path.to_string_lossy();
+    let baked = entry
+        .path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("rimg"));
+    if baked {
+        let placement = Placement::new(&entry.options);
+        Ok(placement.apply(load_baked(&path, placement.offset)?))
+    } else {
+        load_image_with(&path, &entry.options)
+    }
+}
+
+fn unknown(name: &str) -> RaelImgError {
+    RaelImgError::Io(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("unknown asset `{name}`"),
+    ))
+}
@@ -0,0 +1,87 @@
+//! Pluggable decoder backends.
+//!
+//! Every load in this crate decodes through the `image` crate by default. Applications that
+//! need a faster or platform-specific codec, such as zune-image, libjpeg-turbo or the codecs of
+//! the operating system, implement [`Decoder`] for it and pass it to the `_with_decoder`
+//! functions; everything after decoding (resizing, finishing, positioning) stays the same.
+//!
+//! A backend only has to handle the formats it is better at: returning the result of
+//! [`ImageCrate`] for everything else keeps all formats working.
+//!
+//! ```no_run
+//! use image::DynamicImage;
+//! use rael_img::error::RaelImgError;
+//! use rael_img::backend::{load_image_with_decoder, Decoder, ImageCrate};
+//! use rael_img::decode::{DecodePolicy, PartialImage};
+//! use rael_img::LoadOptions;
+//!
+//! struct FastJpeg;
+//!
+//! impl Decoder for FastJpeg {
+//!     fn decode(&self, bytes: &[u8], policy: DecodePolicy) -> Result<PartialImage, RaelImgError> {
+//!         if !bytes.starts_with(&[0xff, 0xd8]) {
+//!             return ImageCrate.decode(bytes, policy);
+//!         }
+//!         # let image: DynamicImage = todo!();
+//!         // Decode with the faster library here.
+//!         Ok(PartialImage { image, warning: None })
+//!     }
+//! }
+//!
+//! let pixels = load_image_with_decoder("./assets/photo.jpg", &LoadOptions::default(), &FastJpeg);
+//! ```
+
+use crate::LoadOptions;
+use crate::decode::{DecodePolicy, PartialImage, decode_with_policy};
+use crate::error::RaelImgError;
+use crate::pipeline::process_bytes_with;
+use rael::Color;
+
+/// Turns encoded image bytes into an image.
+///
+/// Implementations should honour `policy` as far as their codec allows: under
+/// [`DecodePolicy::Strict`] damaged input is an error, under [`DecodePolicy::Lenient`] a
+/// truncated image may be returned with a warning.
+///
+/// Closures with the same signature as [`decode`](Decoder::decode) are decoders too.
+pub trait Decoder {
+    /// Decodes `bytes`, which may be in any format.
+    fn decode(&self, bytes: &[u8], policy: DecodePolicy) -> Result<PartialImage, RaelImgError>;
+}
+
+impl<F> Decoder for F
+where
+    F: Fn(&[u8], DecodePolicy) -> Result<PartialImage, RaelImgError>,
+{
+    fn decode(&self, bytes: &[u8], policy: DecodePolicy) -> Result<PartialImage, RaelImgError> {
+        self(bytes, policy)
+    }
+}
+
+/// The default backend, the `image` crate with the formats enabled by its features.
+///
+/// This is [`decode_with_policy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ImageCrate;
+
+impl Decoder for ImageCrate {
+    fn decode(&self, bytes: &[u8], policy: DecodePolicy) -> Result<PartialImage, RaelImgError> {
+        decode_with_policy(bytes, policy)
+    }
+}
+
+/// Loads an image like [`load_image_with`](crate::load_image_with), decoding it with
+/// `decoder`.
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(Vec<(u32, u32, rael::Color)>)`: The processed pixels, offset by `position`.
+/// - `Err(RaelImgError)`: If the file could not be read or `decoder` failed.
+pub fn load_image_with_decoder(
+    path: &str,
+    options: &LoadOptions,
+    decoder: &dyn Decoder,
+) -> Result<Vec<(u32, u32, Color)>, RaelImgError> {
+    process_bytes_with(&std::fs::read(path)?, options, decoder).map(|loaded| loaded.pixels)
+}
@@ -0,0 +1,195 @@
+//! Background color detection.
+//!
+//! Screenshots, icons and sprites exported without alpha usually sit on a flat background.
+//! [`detect_background`] finds that color by sampling the border of the image, so the
+//! canvas can be cleared with the same color, and [`remove_background`] turns it into
+//! transparency so only the subject is drawn.
+
+use crate::grid::PixelGrid;
+use rael::Color;
+use std::collections::HashMap;
+
+/// Options for [`detect_background`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct BackgroundOptions {
+    /// The largest per-channel difference for two colors to count as the same background,
+    /// absorbing compression noise and gradients.
+    pub tolerance: u8,
+    /// The share of border pixels, from `0.0` to `1.0`, that must match for a color to be
+    /// reported as the background.
+    pub min_coverage: f32,
+}
+
+impl Default for BackgroundOptions {
+    fn default() -> Self {
+        Self {
+            tolerance: 12,
+            min_coverage: 0.6,
+        }
+    }
+}
+
+/// Detects the background color of an image from the pixels along its border.
+///
+/// Border pixels are grouped by similarity and the largest group wins if it covers at least
+/// [`min_coverage`](BackgroundOptions::min_coverage) of the border. Missing pixels on the
+/// border are ignored.
+///
+/// # Returns
+///
+/// The average color of the winning group, or `None` if the border has no dominant color.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael::{Canvas, Color};
+/// use rael_img::background::{detect_background, BackgroundOptions};
+/// use rael_img::load_image;
+///
+/// let pixels = load_image("./assets/screenshot.png", Some(80), None, (0, 0), false, 1.0).unwrap();
+/// let clear = detect_background(&pixels, &BackgroundOptions::default())
+///     .unwrap_or(Color { r: 0, g: 0, b: 0 });
+/// let mut canvas = Canvas::new(80, 24, clear);
+/// ```
+pub fn detect_background(
+    pixels: &[(u32, u32, Color)],
+    options: &BackgroundOptions,
+) -> Option<Color> {
+    let grid = PixelGrid::from_pixels(pixels);
+    let border: Vec<Color> = border_cells(&grid)
+        .filter_map(|(x, y)| grid.get(x, y))
+        .collect();
+    if border.is_empty() {
+        return None;
+    }
+
+    // Bucket by coarse color first, then grow the largest bucket by the tolerance.
+    let mut buckets: HashMap<(u8, u8, u8), usize> = HashMap::new();
+    for color in &border {
+        *buckets
+            .entry((color.r >> 4, color.g >> 4, color.b >> 4))
+            .or_default() += 1;
+    }
+    let (&seed, _) = buckets.iter().max_by_key(|&(&key, &count)| (count, key))?;
+    let seed_members: Vec<&Color> = border
+        .iter()
+        .filter(|color| (color.r >> 4, color.g >> 4, color.b >> 4) == seed)
+        .collect();
+    let center = average(seed_members.into_iter());
+
+    let matching: Vec<&Color> = border
+        .iter()
+        .filter(|&&color| is_close(color, center, options.tolerance))
+        .collect();
+    if (matching.len() as f32) < border.len() as f32 * options.min_coverage.clamp(0.0, 1.0) {
+        return None;
+    }
+    Some(average(matching.into_iter()))
+}
+
+/// Removes the background connected to the border of the image.
+///
+/// Pixels within `tolerance` of `background` are dropped if they can be reached from the
+/// border through other such pixels, so areas of the same color inside the subject, like
+/// the white of an eye on a white background, are kept.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::background::{detect_background, remove_background, BackgroundOptions};
+/// use rael_img::load_image;
+///
+/// let pixels = load_image("./assets/icon.jpg", Some(32), Some(32), (0, 0), false, 1.0).unwrap();
+/// let options = BackgroundOptions::default();
+/// let sprite = match detect_background(&pixels, &options) {
+///     Some(background) => remove_background(&pixels, background, options.tolerance),
+///     None => pixels,
+/// };
+/// ```
+pub fn remove_background(
+    pixels: &[(u32, u32, Color)],
+    background: Color,
+    tolerance: u8,
+) -> Vec<(u32, u32, Color)> {
+    let grid = PixelGrid::from_pixels(pixels);
+    if grid.is_empty() {
+        return Vec::new();
+    }
+    let (width, height) = (grid.width as usize, grid.height as usize);
+    let matches = |x: u32, y: u32| {
+        grid.get(x, y)
+            .is_some_and(|color| is_close(color, background, tolerance))
+    };
+
+    let mut removed = vec![false; width * height];
+    let mut stack: Vec<(u32, u32)> = border_cells(&grid)
+        .filter(|&(x, y)| matches(x, y))
+        .collect();
+    while let Some((x, y)) = stack.pop() {
+        let index = y as usize * width + x as usize;
+        if removed[index] {
+            continue;
+        }
+        removed[index] = true;
+        let neighbours = [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ];
+        for (nx, ny) in neighbours {
+            if (nx as usize) < width
+                && (ny as usize) < height
+                && !removed[ny as usize * width + nx as usize]
+                && matches(nx, ny)
+            {
+                stack.push((nx, ny));
+            }
+        }
+    }
+
+    let (min_x, min_y) = match crate::grid::bounds(pixels) {
+        Some((min_x, min_y, _, _)) => (min_x, min_y),
+        None => return Vec::new(),
+    };
+    pixels
+        .iter()
+        .copied()
+        .filter(|&(x, y, _)| !removed[(y - min_y) as usize * width + (x - min_x) as usize])
+        .collect()
+}
+
+/// Iterates over the local coordinates of the cells on the grid's border.
+fn border_cells(grid: &PixelGrid) -> impl Iterator<Item = (u32, u32)> + '_ {
+    let (width, height) = (grid.width, grid.height);
+    (0..height).flat_map(move |y| {
+        let edge_row = y == 0 || y + 1 == height;
+        (0..width)
+            .filter(move |&x| edge_row || x == 0 || x + 1 == width)
+            .map(move |x| (x, y))
+    })
+}
+
+fn is_close(a: Color, b: Color, tolerance: u8) -> bool {
+    a.r.abs_diff(b.r) <= tolerance
+        && a.g.abs_diff(b.g) <= tolerance
+        && a.b.abs_diff(b.b) <= tolerance
+}
+
+fn average<'a>(colors: impl Iterator<Item = &'a Color>) -> Color {
+    let (mut r, mut g, mut b, mut count) = (0u64, 0u64, 0u64, 0u64);
+    for color in colors {
+        r += u64::from(color.r);
+        g += u64::from(color.g);
+        b += u64::from(color.b);
+        count += 1;
+    }
+    let count = count.max(1);
+    Color {
+        r: ((r + count / 2) / count) as u8,
+        g: ((g + count / 2) / count) as u8,
+        b: ((b + count / 2) / count) as u8,
+    }
+}
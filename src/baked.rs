@@ -0,0 +1,470 @@
+//! A compact pre-processed image format for shipping assets.
+//!
+//! Baking runs the processing pipeline once, ahead of time: the image is resized, quantized
+//! to a small palette (optionally with dithering) and stored as run-length encoded palette
+//! indices. Loading a baked asset skips decoding and resizing entirely, and the files are
+//! usually a fraction of the size of the source PNG.
+//!
+//! # Format
+//!
+//! All integers are little-endian.
+//!
+//! | Bytes       | Content                                         |
+//! |-------------|-------------------------------------------------|
+//! | 4           | The magic `RIMG`                                |
+//! | 1           | The format version, currently `2`               |
+//! | 4 + 4       | Width and height in pixels                      |
+//! | 4 + 4       | The `(x, y)` position of the top-left corner    |
+//! | 2           | The palette length `n`, at most 256             |
+//! | 3 × `n`     | The palette as RGB triples                      |
+//! | …           | Runs covering the image in row-major order      |
+//!
+//! Version `1` files have no position and load at `(0, 0)`.
+//!
+//! A run starts with a byte `b`. Its length is `(b & 0x7F) + 1` pixels. If the high bit is
+//! set the pixels are transparent, otherwise one palette index byte follows.
+//!
+//! [`save_rimg`] and [`load_rimg`] bake and store a processed image, and load it back with
+//! the same size and position, in one call each.
+
+use crate::error::RaelImgError;
+use crate::indexed::IndexedImage;
+use crate::loaded::LoadedImage;
+use crate::quantize::{median_cut, remap, remap_dithered};
+use image::ImageError;
+use image::error::{DecodingError, ImageFormatHint};
+use rael::Color;
+
+/// The magic bytes every baked file starts with.
+pub const MAGIC: &[u8; 4] = b"RIMG";
+
+/// The format version written by [`encode_baked`].
+pub const VERSION: u8 = 2;
+
+const TRANSPARENT_RUN: u8 = 0x80;
+const MAX_RUN: usize = 128;
+
+/// Options for [`bake`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct BakeOptions {
+    /// The maximum palette size, from 1 to 256.
+    pub max_colors: usize,
+    /// If `true`, Floyd–Steinberg dithering hides the banding of a small palette.
+    pub dither: bool,
+}
+
+impl Default for BakeOptions {
+    fn default() -> Self {
+        Self {
+            max_colors: 256,
+            dither: false,
+        }
+    }
+}
+
+/// Quantizes a pixel list into an [`IndexedImage`] ready to be encoded.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::baked::{bake, save_baked, BakeOptions};
+/// use rael_img::load_image;
+///
+/// let pixels = load_image("./assets/hero.png", Some(32), Some(32), (0, 0), false, 1.0).unwrap();
+/// let image = bake(&pixels, &BakeOptions { max_colors: 16, dither: true });
+/// save_baked("./assets/hero.rimg", &image).unwrap();
+/// ```
+pub fn bake(pixels: &[(u32, u32, Color)], options: &BakeOptions) -> IndexedImage {
+    let palette = median_cut(pixels, options.max_colors.clamp(1, 256));
+    let quantized = if options.dither {
+        remap_dithered(pixels, &palette)
+    } else {
+        remap(pixels, &palette)
+    };
+    IndexedImage::from_pixels(&quantized).expect("the palette has at most 256 colors")
+}
+
+/// Encodes an indexed image in the baked format.
+///
+/// Pixels are stored relative to the image's bounding box, so the position the image was
+/// loaded at is not preserved. [`save_rimg`] keeps the box of a [`LoadedImage`].
+pub fn encode_baked(image: &IndexedImage) -> Vec<u8> {
+    let (origin, size) = match crate::grid::bounds(image.indices()) {
+        Some((min_x, min_y, max_x, max_y)) => {
+            ((min_x, min_y), (max_x - min_x + 1, max_y - min_y + 1))
+        }
+        None => ((0, 0), (0, 0)),
+    };
+    encode(image, origin, size, (0, 0))
+}
+
+/// Encodes the `width` × `height` box of `image` whose top-left corner is `origin`, storing
+/// `position` as where it is loaded. Pixels outside the box are left out.
+fn encode(
+    image: &IndexedImage,
+    origin: (u32, u32),
+    (width, height): (u32, u32),
+    position: (u32, u32),
+) -> Vec<u8> {
+    let mut cells: Vec<Option<u8>> = vec![None; width as usize * height as usize];
+    for &(x, y, index) in image.indices() {
+        let (Some(x), Some(y)) = (x.checked_sub(origin.0), y.checked_sub(origin.1)) else {
+            continue;
+        };
+        if x < width && y < height {
+            cells[y as usize * width as usize + x as usize] = Some(index);
+        }
+    }
+
+    let palette = image.palette();
+    let mut bytes = Vec::with_capacity(23 + palette.len() * 3 + cells.len() / 4);
+    bytes.extend_from_slice(MAGIC);
+    bytes.push(VERSION);
+    bytes.extend_from_slice(&width.to_le_bytes());
+    bytes.extend_from_slice(&height.to_le_bytes());
+    bytes.extend_from_slice(&position.0.to_le_bytes());
+    bytes.extend_from_slice(&position.1.to_le_bytes());
+    bytes.extend_from_slice(&(palette.len() as u16).to_le_bytes());
+    for color in palette {
+        bytes.extend_from_slice(&[color.r, color.g, color.b]);
+    }
+
+    let mut start = 0;
+    while start < cells.len() {
+        let value = cells[start];
+        let mut length = 1;
+        while length < MAX_RUN && start + length < cells.len() && cells[start + length] == value {
+            length += 1;
+        }
+        match value {
+            Some(index) => bytes.extend_from_slice(&[(length - 1) as u8, index]),
+            None => bytes.push(TRANSPARENT_RUN | (length - 1) as u8),
+        }
+        start += length;
+    }
+    bytes
+}
+
+/// Decodes a baked image.
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(IndexedImage)`: The image, with its top-left corner at `(0, 0)`.
+/// - `Err(RaelImgError)`: A decoding error if the data is not a valid baked image.
+pub fn decode_baked(bytes: &[u8]) -> Result<IndexedImage, RaelImgError> {
+    decode(bytes).map(|baked| baked.image)
+}
+
+/// A decoded baked image with the box stored in its header.
+struct Baked {
+    /// The pixels, with the top-left corner of the box at `(0, 0)`.
+    image: IndexedImage,
+    width: u32,
+    height: u32,
+    position: (u32, u32),
+}
+
+fn decode(bytes: &[u8]) -> Result<Baked, RaelImgError> {
+    let mut reader = Reader { bytes, offset: 0 };
+    if reader.take(4)? != MAGIC {
+        return Err(invalid("missing RIMG magic"));
+    }
+    let version = reader.take(1)?[0];
+    if version != 1 && version != VERSION {
+        return Err(invalid(&format!("unsupported version {version}")));
+    }
+
+    let width = reader.u32()?;
+    let height = reader.u32()?;
+    let offset = if version == 1 {
+        (0, 0)
+    } else {
+        (reader.u32()?, reader.u32()?)
+    };
+    let palette_len = usize::from(reader.u16()?);
+    if palette_len > 256 {
+        return Err(invalid("palette has more than 256 colors"));
+    }
+    let palette: Vec<Color> = reader
+        .take(palette_len * 3)?
+        .chunks_exact(3)
+        .map(|rgb| Color {
+            r: rgb[0],
+            g: rgb[1],
+            b: rgb[2],
+        })
+        .collect();
+
+    let total = u64::from(width) * u64::from(height);
+    let mut pixels = Vec::new();
+    let mut position = 0u64;
+    while position < total {
+        let header = reader.take(1)?[0];
+        let length = u64::from(header & !TRANSPARENT_RUN) + 1;
+        if position + length > total {
+            return Err(invalid("run extends past the end of the image"));
+        }
+        if header & TRANSPARENT_RUN == 0 {
+            let index = reader.take(1)?[0];
+            for cell in position..position + length {
+                pixels.push((
+                    (cell % u64::from(width)) as u32,
+                    (cell / u64::from(width)) as u32,
+                    index,
+                ));
+            }
+        }
+        position += length;
+    }
+
+    let image =
+        IndexedImage::new(pixels, palette).ok_or_else(|| invalid("palette index out of range"))?;
+    Ok(Baked {
+        image,
+        width,
+        height,
+        position: offset,
+    })
+}
+
+/// Writes a baked image to `path`.
+pub fn save_baked(path: &str, image: &IndexedImage) -> Result<(), RaelImgError> {
+    std::fs::write(path, encode_baked(image))?;
+    Ok(())
+}
+
+/// Bakes a processed image and writes it to `path`, usually a `.rimg` file, keeping its
+/// size and position so [`load_rimg`] gives the same box back.
+///
+/// # Arguments
+///
+/// * `path` - The file to write.
+/// * `image` - The processed image.
+/// * `options` - How the colors are quantized.
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(())`: If the file was written.
+/// - `Err(RaelImgError)`: If the file could not be written.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::baked::{save_rimg, BakeOptions};
+/// use rael_img::loaded::LoadedImage;
+/// use rael_img::LoadOptions;
+///
+/// // Ahead of time, e.g. in a build script:
+/// let options = LoadOptions {
+///     width: Some(120),
+///     ..LoadOptions::default()
+/// };
+/// let splash = LoadedImage::load("./art/splash.png", &options).unwrap();
+/// save_rimg("./assets/splash.rimg", &splash, &BakeOptions::default()).unwrap();
+/// ```
+pub fn save_rimg(
+    path: &str,
+    image: &LoadedImage,
+    options: &BakeOptions,
+) -> Result<(), RaelImgError> {
+    let baked = bake(image.pixels(), options);
+    let size = (image.width(), image.height());
+    let bytes = encode(&baked, image.position(), size, image.position());
+    std::fs::write(path, bytes)?;
+    Ok(())
+}
+
+/// Loads a baked image file written by [`save_rimg`] or [`save_baked`].
+///
+/// Nothing is decoded or resized; the image has the size and colors it was baked with.
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(LoadedImage)`: The image, with the size and position stored in the file.
+/// - `Err(RaelImgError)`: If the file could not be read or is not a valid baked image.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael::{Canvas, Color};
+/// use rael_img::baked::load_rimg;
+/// use rael_img::canvas::CanvasImageExt;
+///
+/// let splash = load_rimg("./assets/splash.rimg").unwrap();
+/// let mut canvas = Canvas::new(120, 40, Color { r: 0, g: 0, b: 0 });
+/// canvas.draw_image(&splash, 1);
+/// ```
+pub fn load_rimg(path: &str) -> Result<LoadedImage, RaelImgError> {
+    let baked = decode(&std::fs::read(path)?)?;
+    let (x, y) = baked.position;
+    let pixels = baked
+        .image
+        .to_pixels()
+        .into_iter()
+        .map(|(px, py, color)| (px + x, py + y, color))
+        .collect();
+    Ok(LoadedImage::with_bounds(
+        pixels,
+        baked.width,
+        baked.height,
+        baked.position,
+    ))
+}
+
+/// Loads a baked image file and returns its pixels, ready to be drawn onto a `rael::Canvas`.
+///
+/// # Arguments
+///
+/// * `path` - The path of a file written by [`save_baked`].
+/// * `position` - A tuple `(u32, u32)` representing the `(x, y)` offset to apply to each
+///                pixel's coordinates.
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(Vec<(u32, u32, rael::Color)>)`: The pixels of the baked image, offset by `position`.
+/// - `Err(RaelImgError)`: If the file could not be read or is not a valid baked image.
+pub fn load_baked(
+    path: &str,
+    position: (u32, u32),
+) -> Result<Vec<(u32, u32, Color)>, RaelImgError> {
+    let image = decode_baked(&std::fs::read(path)?)?;
+    Ok(image
+        .to_pixels()
+        .into_iter()
+        .map(|(x, y, color)| (x + position.0, y + position.1, color))
+        .collect())
+}
+
+fn invalid(message: &str) -> RaelImgError {
+    RaelImgError::Image(ImageError::Decoding(DecodingError::new(
+        ImageFormatHint::Name("rimg".to_string()),
+        message.to_string(),
+    )))
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], RaelImgError> {
+        let end = self
+            .offset
+            .checked_add(count)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| invalid("unexpected end of data"))?;
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn u16(&mut self) -> Result<u16, RaelImgError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, RaelImgError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Color = Color { r: 255, g: 0, b: 0 };
+    const BLUE: Color = Color { r: 0, g: 0, b: 255 };
+
+    fn temp_path(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("rael_img-{}-{name}", std::process::id()));
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn rimg_round_trip_keeps_the_box() {
+        // A 4 × 3 box at (5, 7) whose pixels cover only part of it.
+        let pixels = vec![(6, 8, RED), (7, 8, BLUE), (7, 9, RED)];
+        let image = LoadedImage::with_bounds(pixels.clone(), 4, 3, (5, 7));
+        let path = temp_path("round-trip.rimg");
+        save_rimg(&path, &image, &BakeOptions::default()).unwrap();
+        let loaded = load_rimg(&path);
+        std::fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+
+        assert_eq!((loaded.width(), loaded.height()), (4, 3));
+        assert_eq!(loaded.position(), (5, 7));
+        let mut restored = loaded.pixels().to_vec();
+        restored.sort_unstable_by_key(|&(x, y, _)| (y, x));
+        assert_eq!(restored, pixels);
+    }
+
+    #[test]
+    fn encode_baked_stores_the_bounding_box_at_the_origin() {
+        let image = bake(&[(3, 3, RED), (4, 3, BLUE)], &BakeOptions::default());
+        let decoded = decode(&encode_baked(&image)).unwrap();
+        assert_eq!((decoded.width, decoded.height), (2, 1));
+        assert_eq!(decoded.position, (0, 0));
+        assert_eq!(decoded.image.to_pixels(), [(0, 0, RED), (1, 0, BLUE)]);
+    }
+
+    #[test]
+    fn version_one_files_still_decode() {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(1);
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&[255, 0, 0]);
+        bytes.extend_from_slice(&[1, 0]);
+
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!((decoded.width, decoded.height), (2, 1));
+        assert_eq!(decoded.position, (0, 0));
+        assert_eq!(decoded.image.to_pixels(), [(0, 0, RED), (1, 0, RED)]);
+    }
+
+    #[test]
+    fn truncated_data_is_rejected() {
+        let image = bake(&[(0, 0, RED), (1, 0, BLUE)], &BakeOptions::default());
+        let bytes = encode_baked(&image);
+        assert!(decode_baked(&bytes[..bytes.len() - 1]).is_err());
+        assert!(decode_baked(b"RIMX").is_err());
+    }
+
+    #[test]
+    fn bake_limits_the_palette() {
+        let pixels: Vec<_> = (0..16u32)
+            .map(|x| {
+                let level = (x * 16) as u8;
+                (
+                    x,
+                    0,
+                    Color {
+                        r: level,
+                        g: level,
+                        b: level,
+                    },
+                )
+            })
+            .collect();
+        for dither in [false, true] {
+            let image = bake(
+                &pixels,
+                &BakeOptions {
+                    max_colors: 4,
+                    dither,
+                },
+            );
+            assert!(image.palette().len() <= 4);
+            assert_eq!(image.indices().len(), 16);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! Deterministic draw ordering for overlapping pixel lists.
+
+use crate::grid::{PixelGrid, bounds};
+use rael::Color;
+
+/// A pixel list queued in a [`DrawBatch`].
+#[derive(Debug, Clone, Copy)]
+pub struct DrawItem<'a> {
+    /// The draw priority. Higher values are drawn on top.
+    pub z_index: i32,
+    /// The pixels to draw.
+    pub pixels: &'a [(u32, u32, Color)],
+}
+
+/// A batch of pixel lists with explicit z-indices.
+///
+/// When several images overlap, drawing them straight onto a canvas makes the result
+/// depend on the order of the draw calls. A `DrawBatch` collects the lists first and
+/// resolves overlaps by z-index, breaking ties by insertion order (a stable sort), so the
+/// same inputs always produce the same output.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::{batch::DrawBatch, load_image};
+///
+/// let background = load_image("./assets/bg.png", None, None, (0, 0), false, 1.0).unwrap();
+/// let player = load_image("./assets/player.png", None, None, (12, 8), false, 1.0).unwrap();
+///
+/// let mut batch = DrawBatch::new();
+/// batch.push(10, &player);
+/// batch.push(0, &background);
+///
+/// // The player is on top even though it was pushed first.
+/// let pixels = batch.flatten();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DrawBatch<'a> {
+    items: Vec<DrawItem<'a>>,
+}
+
+impl<'a> DrawBatch<'a> {
+    /// Creates an empty batch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a pixel list at the given z-index. Higher z-indices are drawn on top.
+    pub fn push(&mut self, z_index: i32, pixels: &'a [(u32, u32, Color)]) {
+        self.items.push(DrawItem { z_index, pixels });
+    }
+
+    /// Returns the number of pixel lists in the batch.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns `true` if the batch is empty.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns the queued items in draw order, from bottom to top.
+    pub fn sorted(&self) -> Vec<DrawItem<'a>> {
+        let mut items = self.items.clone();
+        items.sort_by_key(|item| item.z_index);
+        items
+    }
+
+    /// Resolves all overlaps and returns the visible pixels.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `(x, y, color)` tuples with exactly one entry per covered coordinate,
+    /// in row-major order. Drawing it in any order gives the same result.
+    pub fn flatten(&self) -> Vec<(u32, u32, Color)> {
+        let sorted = self.sorted();
+
+        let mut extent: Option<(u32, u32, u32, u32)> = None;
+        for item in &sorted {
+            if let Some(b) = bounds(item.pixels) {
+                extent = Some(match extent {
+                    Some(e) => (e.0.min(b.0), e.1.min(b.1), e.2.max(b.2), e.3.max(b.3)),
+                    None => b,
+                });
+            }
+        }
+        let Some((min_x, min_y, max_x, max_y)) = extent else {
+            return Vec::new();
+        };
+
+        let mut target = PixelGrid::new(max_x - min_x + 1, max_y - min_y + 1);
+        for item in sorted {
+            for &(x, y, color) in item.pixels {
+                target.set(i64::from(x - min_x), i64::from(y - min_y), color);
+            }
+        }
+        target.to_pixels((min_x, min_y))
+    }
+}
@@ -0,0 +1,717 @@
+//! `rael-img`: previews images in the terminal exactly the way `rael_img` processes them.
+//!
+//! The flags mirror the parameters of `load_image`, so a call like
+//! `load_image(path, Some(60), None, (0, 0), false, 1.0)` can be checked with
+//! `rael-img path --width 60`. With `--watch`, the preview is redrawn whenever the image or
+//! the preset file changes, or the terminal is resized.
+//!
+//! `rael-img bake in.png out.rimg --colors 16 --dither` runs the same pipeline once and
+//! writes the result in the baked format of `rael_img::baked`.
+
+use clap::{Parser, Subcommand, ValueEnum};
+use image::ImageDecoder;
+use image::metadata::Orientation;
+use rael::{Canvas, Color};
+use rael_img::adaptive::{AdaptiveRenderer, OutputMode};
+use rael_img::baked::{self, BakeOptions};
+use rael_img::canvas::{CanvasImageExt, DEFAULT_LAYER};
+use rael_img::checkerboard::Checkerboard;
+use rael_img::chroma::ColorKey;
+use rael_img::console::ConsoleProfile;
+use rael_img::decode::DecodePolicy;
+use rael_img::dither::{DitherMethod, DitherOptions, TerminalPalette};
+use rael_img::effects::ColorMode;
+use rael_img::mosaic::GlyphSet;
+use rael_img::outline::Outline;
+use rael_img::pipeline::target_size;
+use rael_img::preset::Presets;
+use rael_img::resample::{AxisFilters, ResizeFilter};
+use rael_img::shadow::Shadow;
+use rael_img::size::{Size, terminal_canvas_size};
+use rael_img::terminal::TermCaps;
+use rael_img::transform::Rotation;
+use rael_img::{LoadOptions, load_image_with};
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, SystemTime};
+
+/// Preview an image in the terminal the way rael_img processes it.
+#[derive(Debug, Parser)]
+#[command(name = "rael-img", version)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// The image to show.
+    #[arg(required = true)]
+    path: Option<String>,
+    #[command(flatten)]
+    load: LoadArgs,
+    /// How to draw the image.
+    #[arg(short, long, value_enum, default_value_t = Mode::Auto)]
+    mode: Mode,
+    /// Keep running and redraw whenever the image or the preset file changes.
+    #[arg(long)]
+    watch: bool,
+    /// How often to check for changes in watch mode, in milliseconds.
+    #[arg(long, default_value_t = 200, requires = "watch")]
+    interval: u64,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Process an image once and write it in the baked `.rimg` format.
+    Bake(BakeArgs),
+}
+
+#[derive(Debug, clap::Args)]
+struct BakeArgs {
+    /// The source image.
+    input: String,
+    /// Where to write the baked image.
+    output: String,
+    #[command(flatten)]
+    load: LoadArgs,
+    /// The maximum number of palette colors, from 1 to 256.
+    #[arg(short, long, default_value_t = 256, value_parser = clap::value_parser!(u16).range(1..=256))]
+    colors: u16,
+    /// Dither to hide the banding of a small palette.
+    #[arg(short, long)]
+    dither: bool,
+}
+
+/// The flags that mirror `LoadOptions`.
+#[derive(Debug, clap::Args)]
+struct LoadArgs {
+    /// Show only a region of the source image, as `X,Y,WIDTH,HEIGHT` in source pixels.
+    #[arg(long, value_parser = parse_crop)]
+    crop: Option<(u32, u32, u32, u32)>,
+    /// Ignore the EXIF orientation and show the image as it is stored.
+    #[arg(long)]
+    no_auto_orient: bool,
+    /// Rotate the image clockwise.
+    #[arg(long, value_enum, value_name = "DEGREES")]
+    rotate: Option<Rotate>,
+    /// Mirror the image left to right.
+    #[arg(long)]
+    flip_horizontal: bool,
+    /// Mirror the image top to bottom.
+    #[arg(long)]
+    flip_vertical: bool,
+    /// Target width in pixels, one pixel per terminal column; or a percentage of the
+    /// terminal width like `50%`, or `fit` for all of it.
+    #[arg(short = 'W', long, value_parser = parse_dimension)]
+    width: Option<Size>,
+    /// Target height in pixels, two pixels per terminal row; or a percentage of the
+    /// terminal height like `50%`, or `fit` for all of it.
+    #[arg(short = 'H', long, value_parser = parse_dimension)]
+    height: Option<Size>,
+    /// Offset of the top-left pixel, as `X,Y` in pixels.
+    #[arg(short, long, value_parser = parse_position)]
+    position: Option<(i32, i32)>,
+    /// Stretch to exactly `--width` by `--height` instead of keeping the aspect ratio.
+    #[arg(short, long)]
+    stretch: bool,
+    /// Scaling factor for dimensions that are not given.
+    #[arg(long)]
+    scale: Option<f32>,
+    /// The resampling filter for both axes.
+    #[arg(short, long, value_enum)]
+    filter: Option<Filter>,
+    /// The resampling filter along rows; overrides `--filter`.
+    #[arg(long, value_enum)]
+    horizontal_filter: Option<Filter>,
+    /// The resampling filter along columns; overrides `--filter`.
+    #[arg(long, value_enum)]
+    vertical_filter: Option<Filter>,
+    /// Shrink through an image this many times the target size, from 1 to 4, to keep thin
+    /// lines and text.
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..=4))]
+    supersample: Option<u32>,
+    /// Resize in linear light, keeping thin bright lines and text from darkening.
+    #[arg(long)]
+    linear_light: bool,
+    /// Boost contrast, saturation and sharpness for readability at terminal resolution.
+    #[arg(short, long)]
+    optimize: bool,
+    /// Blur the image with a Gaussian of this standard deviation, in pixels.
+    #[arg(long, value_name = "SIGMA")]
+    blur: Option<f32>,
+    /// Sharpen the image with an unsharp mask of this strength.
+    #[arg(long, value_name = "AMOUNT")]
+    sharpen: Option<f32>,
+    /// Fill blocks of this many pixels square with their average color.
+    #[arg(long, value_name = "SIZE")]
+    pixelate: Option<u32>,
+    /// Add this amount to every color channel, from -1.0 to 1.0.
+    #[arg(long, allow_hyphen_values = true)]
+    brightness: Option<f32>,
+    /// Multiply the contrast by this factor.
+    #[arg(long)]
+    contrast: Option<f32>,
+    /// Multiply the saturation by this factor; 0 gives grays.
+    #[arg(long)]
+    saturation: Option<f32>,
+    /// Apply this gamma; values above 1 brighten the midtones.
+    #[arg(long)]
+    gamma: Option<f32>,
+    /// Show the image in grays.
+    #[arg(long, conflicts_with_all = ["sepia", "monochrome"])]
+    grayscale: bool,
+    /// Show the image in sepia tones.
+    #[arg(long, conflicts_with = "monochrome")]
+    sepia: bool,
+    /// Show the image in black and white, splitting at this luma from 0 to 255.
+    #[arg(long, value_name = "THRESHOLD", num_args = 0..=1, default_missing_value = "128")]
+    monochrome: Option<u8>,
+    /// Show transparency as a checkerboard with squares of this many pixels.
+    #[arg(long, value_name = "SIZE", num_args = 0..=1, default_missing_value = "4")]
+    checkerboard: Option<u32>,
+    /// Blend semi-transparent pixels over this color, as `#RRGGBB`.
+    #[arg(long, value_name = "COLOR", value_parser = parse_color)]
+    background: Option<Color>,
+    /// Treat pixels of this color as transparent, as `#RRGGBB`.
+    #[arg(long, value_name = "COLOR", value_parser = parse_color)]
+    transparent_color: Option<Color>,
+    /// How far each channel may differ from `--transparent-color` and still match.
+    #[arg(long, value_name = "AMOUNT", requires = "transparent_color")]
+    key_tolerance: Option<u8>,
+    /// Outline the opaque part of transparent images in this color, as `#RRGGBB`.
+    #[arg(long, value_name = "COLOR", value_parser = parse_color)]
+    outline: Option<Color>,
+    /// Cast a shadow of transparent images at this offset, as `X,Y` in pixels.
+    #[arg(long, value_name = "X,Y", value_parser = parse_offset, allow_hyphen_values = true)]
+    shadow: Option<(i32, i32)>,
+    /// The color of `--shadow`, as `#RRGGBB`.
+    #[arg(long, value_name = "COLOR", value_parser = parse_color, requires = "shadow")]
+    shadow_color: Option<Color>,
+    /// The opacity of `--shadow`, from 0.0 to 1.0.
+    #[arg(long, value_name = "OPACITY", requires = "shadow")]
+    shadow_opacity: Option<f32>,
+    /// Reduce the colors to a terminal palette, with dithering.
+    #[arg(long, value_enum)]
+    palette: Option<Palette>,
+    /// How to dither to `--palette`.
+    #[arg(long, value_enum, requires = "palette", default_value_t = Dither::FloydSteinberg)]
+    dither_method: Dither,
+    /// Leave out pixels with an alpha below this value, from 0 to 255.
+    #[arg(long, value_name = "ALPHA")]
+    alpha_threshold: Option<u8>,
+    /// Repeat the image to cover a region, as `WIDTH,HEIGHT` in pixels.
+    #[arg(long, value_parser = parse_size)]
+    tile: Option<(u32, u32)>,
+    /// Draw a grid, the bounding box and the origin over the image.
+    #[arg(long)]
+    debug: bool,
+    /// Tolerate damaged files: skip checksum checks and show what decodes of truncated ones.
+    #[arg(long)]
+    lenient: bool,
+    /// A TOML or JSON preset file to take the options from. Flags override the preset.
+    #[arg(long, requires = "preset")]
+    presets: Option<PathBuf>,
+    /// The name of the preset to use from `--presets`.
+    #[arg(long, requires = "presets")]
+    preset: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Mode {
+    /// The best mode the terminal supports.
+    Auto,
+    /// Half blocks drawn by a `rael::Canvas`, like a game would.
+    Canvas,
+    /// The kitty graphics protocol.
+    Kitty,
+    /// The iTerm2 inline image protocol.
+    Iterm2,
+    /// Sixel graphics.
+    Sixel,
+    /// Block, quadrant, sextant and braille glyphs.
+    Mosaic,
+    /// Mosaic glyphs in the xterm 256-color palette.
+    Mosaic256,
+    /// Colored spaces.
+    Spaces,
+    /// The 16-color Windows console.
+    Console,
+    /// Characters by density, without color.
+    Density,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Filter {
+    /// The nearest pixel; crisp pixel art.
+    Nearest,
+    /// The average of the covered pixels.
+    Box,
+    /// Linear interpolation.
+    Triangle,
+    /// A sharp cubic filter.
+    CatmullRom,
+    /// A soft Gaussian filter.
+    Gaussian,
+    /// The sharpest filter.
+    Lanczos3,
+}
+
+impl From<Filter> for ResizeFilter {
+    fn from(filter: Filter) -> Self {
+        match filter {
+            Filter::Nearest => ResizeFilter::Nearest,
+            Filter::Box => ResizeFilter::Box,
+            Filter::Triangle => ResizeFilter::Triangle,
+            Filter::CatmullRom => ResizeFilter::CatmullRom,
+            Filter::Gaussian => ResizeFilter::Gaussian,
+            Filter::Lanczos3 => ResizeFilter::Lanczos3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Rotate {
+    /// A quarter turn.
+    #[value(name = "90")]
+    Quarter,
+    /// A half turn.
+    #[value(name = "180")]
+    Half,
+    /// Three quarter turns.
+    #[value(name = "270")]
+    ThreeQuarters,
+}
+
+impl From<Rotate> for Rotation {
+    fn from(rotate: Rotate) -> Self {
+        match rotate {
+            Rotate::Quarter => Rotation::Rotate90,
+            Rotate::Half => Rotation::Rotate180,
+            Rotate::ThreeQuarters => Rotation::Rotate270,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Palette {
+    /// The 16 ANSI colors.
+    Ansi16,
+    /// The 16 colors of the classic Windows console.
+    Windows16,
+    /// The 256 xterm colors.
+    Xterm256,
+}
+
+impl From<Palette> for TerminalPalette {
+    fn from(palette: Palette) -> Self {
+        match palette {
+            Palette::Ansi16 => TerminalPalette::Ansi16,
+            Palette::Windows16 => TerminalPalette::WindowsConsole16,
+            Palette::Xterm256 => TerminalPalette::Xterm256,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Dither {
+    /// The nearest color, without dithering.
+    None,
+    /// Error diffusion; the closest result.
+    FloydSteinberg,
+    /// A 4x4 ordered pattern; stable in animations.
+    Bayer4,
+    /// An 8x8 ordered pattern.
+    Bayer8,
+}
+
+impl From<Dither> for DitherMethod {
+    fn from(dither: Dither) -> Self {
+        match dither {
+            Dither::None => DitherMethod::None,
+            Dither::FloydSteinberg => DitherMethod::FloydSteinberg,
+            Dither::Bayer4 => DitherMethod::Bayer4,
+            Dither::Bayer8 => DitherMethod::Bayer8,
+        }
+    }
+}
+
+fn parse_position(value: &str) -> Result<(i32, i32), String> {
+    let (x, y) = value
+        .split_once(',')
+        .ok_or_else(|| format!("expected X,Y, got `{value}`"))?;
+    // The preview starts at the top-left corner of the terminal, so there is nothing to
+    // scroll past.
+    let parse = |part: &str| {
+        part.trim()
+            .parse::<u32>()
+            .map_err(|error| error.to_string())
+            .and_then(|offset| i32::try_from(offset).map_err(|error| error.to_string()))
+    };
+    Ok((parse(x)?, parse(y)?))
+}
+
+fn parse_offset(value: &str) -> Result<(i32, i32), String> {
+    let (x, y) = value
+        .split_once(',')
+        .ok_or_else(|| format!("expected X,Y, got `{value}`"))?;
+    let parse = |part: &str| {
+        part.trim()
+            .parse::<i32>()
+            .map_err(|error| error.to_string())
+    };
+    Ok((parse(x)?, parse(y)?))
+}
+
+fn parse_dimension(value: &str) -> Result<Size, String> {
+    value.parse()
+}
+
+fn parse_size(value: &str) -> Result<(u32, u32), String> {
+    let (width, height) = value
+        .split_once(',')
+        .ok_or_else(|| format!("expected WIDTH,HEIGHT, got `{value}`"))?;
+    let parse = |part: &str| {
+        part.trim()
+            .parse::<u32>()
+            .map_err(|error| error.to_string())
+    };
+    Ok((parse(width)?, parse(height)?))
+}
+
+fn parse_crop(value: &str) -> Result<(u32, u32, u32, u32), String> {
+    let parts = value
+        .split(',')
+        .map(|part| {
+            part.trim()
+                .parse::<u32>()
+                .map_err(|error| error.to_string())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    match parts[..] {
+        [x, y, width, height] => Ok((x, y, width, height)),
+        _ => Err(format!("expected X,Y,WIDTH,HEIGHT, got `{value}`")),
+    }
+}
+
+fn parse_color(value: &str) -> Result<Color, String> {
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    let channel = |range: std::ops::Range<usize>| {
+        hex.get(range)
+            .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+    };
+    match (hex.len(), channel(0..2), channel(2..4), channel(4..6)) {
+        (6, Some(r), Some(g), Some(b)) => Ok(Color { r, g, b }),
+        _ => Err(format!("expected a color like #ff8800, got `{value}`")),
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("rael-img: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    if let Some(Command::Bake(bake_args)) = &args.command {
+        return bake(bake_args);
+    }
+    let path = args.path.as_deref().unwrap_or_default();
+    if args.watch {
+        return watch(args, path);
+    }
+    let output = render(path, &preview_options(&args.load)?, args.mode)?;
+    print(&output)
+}
+
+/// Runs the pipeline once and writes the baked image.
+fn bake(args: &BakeArgs) -> Result<(), String> {
+    let options = load_options(&args.load)?;
+    let pixels = load_image_with(&args.input, &options)
+        .map_err(|error| format!("{}: {error}", args.input))?;
+    let image = baked::bake(
+        &pixels,
+        &BakeOptions {
+            max_colors: usize::from(args.colors),
+            dither: args.dither,
+        },
+    );
+    let bytes = baked::encode_baked(&image);
+    std::fs::write(&args.output, &bytes).map_err(|error| format!("{}: {error}", args.output))?;
+
+    let source = std::fs::metadata(&args.input).map_or(0, |meta| meta.len());
+    println!(
+        "{}: {} pixels, {} colors, {} bytes (source {} bytes)",
+        args.output,
+        image.indices().len(),
+        image.palette().len(),
+        bytes.len(),
+        source
+    );
+    Ok(())
+}
+
+/// Writes a preview, clearing the screen first when stdout is a terminal.
+fn print(output: &str) -> Result<(), String> {
+    let mut stdout = std::io::stdout().lock();
+    let result = if stdout.is_terminal() {
+        write!(stdout, "\u{1b}[2J{output}")
+    } else {
+        write!(stdout, "{output}")
+    };
+    result
+        .and_then(|()| stdout.flush())
+        .map_err(|error| error.to_string())
+}
+
+/// Redraws the preview every time the watched files or the terminal size change.
+///
+/// Errors while loading are shown instead of the image and do not stop watching, because an
+/// editor may be halfway through saving the file.
+fn watch(args: &Args, path: &str) -> Result<(), String> {
+    let interval = Duration::from_millis(args.interval.max(10));
+    let mut last = None;
+    loop {
+        let state = (
+            modified(Path::new(path)),
+            args.load.presets.as_deref().and_then(modified),
+            crossterm::terminal::size().ok(),
+        );
+        if last.as_ref() != Some(&state) {
+            last = Some(state);
+            let output = preview_options(&args.load)
+                .and_then(|options| render(path, &options, args.mode))
+                .unwrap_or_else(|message| format!("\u{1b}[1;1Hrael-img: {message}\r\n"));
+            print(&output)?;
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+/// Builds the load options from the preset and the flags.
+fn load_options(args: &LoadArgs) -> Result<LoadOptions, String> {
+    let mut options = match (&args.presets, &args.preset) {
+        (Some(file), Some(name)) => {
+            let presets =
+                Presets::load(file).map_err(|error| format!("{}: {error}", file.display()))?;
+            *presets
+                .get(name)
+                .ok_or_else(|| format!("no preset named `{name}` in {}", file.display()))?
+        }
+        _ => LoadOptions::default(),
+    };
+
+    options.crop = args.crop.or(options.crop);
+    options.auto_orient &= !args.no_auto_orient;
+    if let Some(rotate) = args.rotate {
+        options.orientation.rotation = rotate.into();
+    }
+    options.orientation.flip_horizontal |= args.flip_horizontal;
+    options.orientation.flip_vertical |= args.flip_vertical;
+    if args.width.is_some() || args.height.is_some() {
+        let canvas = terminal_canvas_size();
+        if let Some(width) = args.width {
+            options.width = width.resolve(canvas.0);
+        }
+        if let Some(height) = args.height {
+            options.height = height.resolve(canvas.1);
+        }
+    }
+    options.position = args.position.unwrap_or(options.position);
+    options.stretch |= args.stretch;
+    options.scale = args.scale.unwrap_or(options.scale);
+    if args.lenient {
+        options.policy = DecodePolicy::Lenient;
+    }
+    options.supersample = args.supersample.unwrap_or(options.supersample);
+    options.linear_light |= args.linear_light;
+    let detail = &mut options.detail;
+    detail.blur = args.blur.unwrap_or(detail.blur);
+    detail.sharpen = args.sharpen.unwrap_or(detail.sharpen);
+    detail.pixelate = args.pixelate.unwrap_or(detail.pixelate);
+    let adjustments = &mut options.adjustments;
+    adjustments.brightness = args.brightness.unwrap_or(adjustments.brightness);
+    adjustments.contrast = args.contrast.unwrap_or(adjustments.contrast);
+    adjustments.saturation = args.saturation.unwrap_or(adjustments.saturation);
+    adjustments.gamma = args.gamma.unwrap_or(adjustments.gamma);
+    if args.grayscale {
+        options.color_mode = ColorMode::Grayscale;
+    } else if args.sepia {
+        options.color_mode = ColorMode::Sepia;
+    } else if let Some(threshold) = args.monochrome {
+        options.color_mode = ColorMode::Monochrome(threshold);
+    }
+    options.optimize |= args.optimize;
+    options.tile = args.tile.or(options.tile);
+    options.debug |= args.debug;
+    options.alpha_threshold = args.alpha_threshold.unwrap_or(options.alpha_threshold);
+    options.background = args.background.or(options.background);
+    if let Some(color) = args.transparent_color {
+        options.transparent_color = Some(ColorKey::new(color, args.key_tolerance.unwrap_or(0)));
+    }
+    if let Some(color) = args.outline {
+        options.outline = Some(Outline::new(color));
+    }
+    if let Some(offset) = args.shadow {
+        let color = args.shadow_color.unwrap_or(Color { r: 0, g: 0, b: 0 });
+        options.shadow = Some(Shadow::new(
+            offset,
+            color,
+            args.shadow_opacity.unwrap_or(0.5),
+        ));
+    }
+    if let Some(cell_size) = args.checkerboard {
+        options.checkerboard = Some(Checkerboard {
+            cell_size,
+            ..Checkerboard::default()
+        });
+    }
+    if let Some(palette) = args.palette {
+        options.dither = Some(DitherOptions {
+            palette: palette.into(),
+            method: args.dither_method.into(),
+        });
+    }
+    if let Some(filter) = args.filter {
+        options.filters = AxisFilters::uniform(filter.into());
+    }
+    if let Some(filter) = args.horizontal_filter {
+        options.filters.horizontal = filter.into();
+    }
+    if let Some(filter) = args.vertical_filter {
+        options.filters.vertical = filter.into();
+    }
+    Ok(options)
+}
+
+/// Builds the load options for a preview, fitting unsized images into the terminal.
+fn preview_options(args: &LoadArgs) -> Result<LoadOptions, String> {
+    let mut options = load_options(args)?;
+    // Without any size, fit the image into the terminal, keeping a row for the prompt.
+    if options.width.is_none() && options.height.is_none() && options.scale == 1.0 {
+        let (columns, rows) = crossterm::terminal::size().unwrap_or((80, 24));
+        let (x, y) = offset(&options);
+        options.width = Some(u32::from(columns).saturating_sub(x).max(1));
+        options.height = Some(
+            (u32::from(rows).saturating_sub(1) * 2)
+                .saturating_sub(y)
+                .max(2),
+        );
+    }
+    Ok(options)
+}
+
+/// Renders the image and returns the escape sequences, ending on the line below it.
+fn render(path: &str, options: &LoadOptions, mode: Mode) -> Result<String, String> {
+    let OutputSize {
+        fitted,
+        grown: (width, height),
+    } = output_size(path, options)?;
+    let area = (width, height.div_ceil(2));
+    let offset = offset(options);
+    let position = (offset.0, offset.1 / 2);
+    let below = format!("\u{1b}[0m\u{1b}[{};1H", position.1 + area.1 + 1);
+
+    let mode = match mode {
+        Mode::Auto => OutputMode::for_caps(&TermCaps::detect()),
+        Mode::Canvas => {
+            let pixels = load_image_with(path, options).map_err(|error| error.to_string())?;
+            let columns = offset.0 + width;
+            let rows = position.1 + area.1;
+            let mut canvas =
+                Canvas::new(columns as usize, rows as usize, Color { r: 0, g: 0, b: 0 });
+            canvas.draw_image(&pixels, DEFAULT_LAYER);
+            return Ok(canvas.render() + &below);
+        }
+        Mode::Kitty => OutputMode::Kitty,
+        Mode::Iterm2 => OutputMode::Iterm2,
+        Mode::Sixel => OutputMode::Sixel,
+        Mode::Mosaic => OutputMode::Mosaic(GlyphSet::ALL),
+        Mode::Mosaic256 => OutputMode::Palette256(GlyphSet::ALL),
+        Mode::Spaces => OutputMode::Spaces,
+        Mode::Console => OutputMode::Console(ConsoleProfile::default()),
+        Mode::Density => OutputMode::Density,
+    };
+
+    let caps = TermCaps::detect();
+    let rendered = AdaptiveRenderer::with_mode(mode)
+        .ambiguous_width(caps.ambiguous_width)
+        .load_options(*options)
+        .stretch(true)
+        .render(path, (fitted.0, fitted.1.div_ceil(2)), position)
+        .map_err(|error| error.to_string())?;
+    Ok(rendered.to_ansi() + &below)
+}
+
+/// The position of the image, which is never negative in the preview.
+fn offset(options: &LoadOptions) -> (u32, u32) {
+    (
+        options.position.0.max(0).unsigned_abs(),
+        options.position.1.max(0).unsigned_abs(),
+    )
+}
+
+/// The sizes of an image loaded with the CLI options.
+struct OutputSize {
+    /// The size `load_image` resizes the image to.
+    fitted: (u32, u32),
+    /// The size of the result, grown by the outline and the shadow.
+    grown: (u32, u32),
+}
+
+/// Computes the sizes `load_image` would produce, reading only the image header.
+fn output_size(path: &str, options: &LoadOptions) -> Result<OutputSize, String> {
+    let header = |error: image::ImageError| format!("{path}: {error}");
+    let mut decoder = image::ImageReader::open(path)
+        .map_err(|error| format!("{path}: {error}"))?
+        .with_guessed_format()
+        .map_err(|error| format!("{path}: {error}"))?
+        .into_decoder()
+        .map_err(header)?;
+    let (mut image_width, mut image_height) = decoder.dimensions();
+    let upright = decoder.orientation().map_err(header)?;
+    if options.auto_orient
+        && matches!(
+            upright,
+            Orientation::Rotate90
+                | Orientation::Rotate270
+                | Orientation::Rotate90FlipH
+                | Orientation::Rotate270FlipH
+        )
+    {
+        (image_width, image_height) = (image_height, image_width);
+    }
+    if let Some(tile) = options.tile {
+        return Ok(OutputSize {
+            fitted: tile,
+            grown: tile,
+        });
+    }
+
+    let fitted = target_size(image_width, image_height, options);
+    let (mut width, mut height) = fitted;
+    // Only transparent images get an outline and a shadow, and a color key makes them so.
+    if decoder.color_type().has_alpha() || options.transparent_color.is_some() {
+        if let Some(outline) = options.outline {
+            width += 2 * outline.thickness;
+            height += 2 * outline.thickness;
+        }
+        if let Some(shadow) = options.shadow {
+            width += shadow.offset.0.unsigned_abs();
+            height += shadow.offset.1.unsigned_abs();
+        }
+    }
+    Ok(OutputSize {
+        fitted,
+        grown: (width, height),
+    })
+}
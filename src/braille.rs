@@ -0,0 +1,196 @@
+//! Braille rendering for high-resolution monochrome output.
+//!
+//! Every terminal cell becomes one braille pattern, whose 2×4 dots show a block of 2×4
+//! pixels, eight times the resolution of one pixel per cell. Each dot is either on or off,
+//! so the pixels are first reduced to ink and paper by their luma, with a plain threshold
+//! or with dithering for photos and gradients. The result suits line art, logos, QR codes
+//! and plots; colors survive only as one ink color per cell.
+//!
+//! The [mosaic renderer](crate::mosaic) can mix braille with block glyphs and two colors
+//! per cell instead, at the cost of a slower search and output that needs a background
+//! color.
+
+use crate::dither::{DitherMethod, bayer};
+use crate::grid::PixelGrid;
+use rael::Color;
+
+/// The braille dot bit of each pixel of a 2×4 block, indexed by `row * 2 + column`.
+const DOTS: [u32; 8] = [0x01, 0x08, 0x02, 0x10, 0x04, 0x20, 0x40, 0x80];
+
+/// The blank braille pattern, whose code point is the base of all others.
+const BLANK: u32 = 0x2800;
+
+/// Options for [`render_braille`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct BrailleOptions {
+    /// Pixels with a luma from 0 to 255 at or above this value are ink.
+    pub threshold: u8,
+    /// How pixels are binarized. [`DitherMethod::None`] compares each pixel with the
+    /// threshold; the other methods dither around it, trading crisp edges for gradients.
+    pub dither: DitherMethod,
+    /// If `true`, dark pixels are ink. Use this for dark line art on a light background
+    /// shown on a dark terminal.
+    pub invert: bool,
+    /// The color of every dot. If `None`, each cell takes the mean color of its ink pixels.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_color::option"))]
+    pub ink: Option<Color>,
+    /// The `(column, row)` offset applied to every cell.
+    pub position: (u32, u32),
+}
+
+impl Default for BrailleOptions {
+    fn default() -> Self {
+        Self {
+            threshold: 128,
+            dither: DitherMethod::None,
+            invert: false,
+            ink: None,
+            position: (0, 0),
+        }
+    }
+}
+
+/// Renders a pixel list as braille patterns, one cell per 2×4 pixel block.
+///
+/// Cells whose block has no pixels at all are skipped; cells without ink get the blank
+/// pattern `U+2800`, so they still cover what is under them.
+///
+/// # Returns
+///
+/// A vector of `(column, row, char, Color)` tuples in row-major order. Print them with
+/// [`char_cells_to_ansi`](crate::mosaic::char_cells_to_ansi).
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::braille::{render_braille, BrailleOptions};
+/// use rael_img::load_image;
+/// use rael_img::mosaic::char_cells_to_ansi;
+///
+/// // 40x20 cells.
+/// let pixels = load_image("./assets/qr.png", Some(80), Some(80), (0, 0), true, 1.0).unwrap();
+/// let cells = render_braille(&pixels, &BrailleOptions::default());
+/// print!("{}", char_cells_to_ansi(&cells));
+/// ```
+pub fn render_braille(
+    pixels: &[(u32, u32, Color)],
+    options: &BrailleOptions,
+) -> Vec<(u32, u32, char, Color)> {
+    let grid = PixelGrid::from_pixels(pixels);
+    let ink = binarize(&grid, options);
+    let columns = grid.width.div_ceil(2);
+    let rows = grid.height.div_ceil(4);
+    let mut cells = Vec::with_capacity(columns as usize * rows as usize);
+
+    for row in 0..rows {
+        for column in 0..columns {
+            let mut code = 0;
+            let mut sums = [0u32; 4];
+            let mut present = false;
+            for (dot, bit) in DOTS.iter().enumerate() {
+                let (x, y) = (column * 2 + dot as u32 % 2, row * 4 + dot as u32 / 2);
+                let Some(color) = grid.get(x, y) else {
+                    continue;
+                };
+                present = true;
+                if ink[(y * grid.width + x) as usize] {
+                    code |= bit;
+                    sums[0] += u32::from(color.r);
+                    sums[1] += u32::from(color.g);
+                    sums[2] += u32::from(color.b);
+                    sums[3] += 1;
+                }
+            }
+            if !present {
+                continue;
+            }
+            let color = options.ink.unwrap_or_else(|| match sums[3] {
+                0 => Color { r: 0, g: 0, b: 0 },
+                n => Color {
+                    r: ((sums[0] + n / 2) / n) as u8,
+                    g: ((sums[1] + n / 2) / n) as u8,
+                    b: ((sums[2] + n / 2) / n) as u8,
+                },
+            });
+            let glyph = char::from_u32(BLANK + code).unwrap_or(' ');
+            cells.push((
+                column + options.position.0,
+                row + options.position.1,
+                glyph,
+                color,
+            ));
+        }
+    }
+    cells
+}
+
+/// Decides for every pixel of the grid, row-major, whether it is ink.
+fn binarize(grid: &PixelGrid, options: &BrailleOptions) -> Vec<bool> {
+    let threshold = f32::from(options.threshold);
+    let mut levels: Vec<f32> = grid
+        .cells
+        .iter()
+        .map(|cell| {
+            let level = cell.map_or(0.0, luma);
+            if options.invert { 255.0 - level } else { level }
+        })
+        .collect();
+    let width = grid.width as usize;
+
+    match options.dither {
+        DitherMethod::None => levels.iter().map(|&level| level >= threshold).collect(),
+        DitherMethod::Bayer4 | DitherMethod::Bayer8 => {
+            let size = if options.dither == DitherMethod::Bayer4 {
+                4
+            } else {
+                8
+            };
+            let steps = (size * size) as f32;
+            levels
+                .iter()
+                .enumerate()
+                .map(|(index, &level)| {
+                    let (x, y) = ((index % width) as u32, (index / width) as u32);
+                    let rank = bayer(x % size, y % size, size) as f32;
+                    // Spread the thresholds over the whole range, centered on `threshold`.
+                    let offset = ((rank + 0.5) / steps - 0.5) * 255.0;
+                    level >= threshold + offset
+                })
+                .collect()
+        }
+        DitherMethod::FloydSteinberg => {
+            let mut ink = vec![false; levels.len()];
+            for index in 0..levels.len() {
+                let (x, y) = (index % width, index / width);
+                let on = levels[index] >= threshold;
+                ink[index] = on;
+                if grid.cells[index].is_none() {
+                    continue;
+                }
+                let error = levels[index] - if on { 255.0 } else { 0.0 };
+                let mut spread = |dx: isize, dy: usize, weight: f32| {
+                    let nx = x as isize + dx;
+                    if nx < 0 || nx as usize >= width || y + dy >= grid.height as usize {
+                        return;
+                    }
+                    let target = (y + dy) * width + nx as usize;
+                    if grid.cells[target].is_some() {
+                        levels[target] += error * weight;
+                    }
+                };
+                spread(1, 0, 7.0 / 16.0);
+                spread(-1, 1, 3.0 / 16.0);
+                spread(0, 1, 5.0 / 16.0);
+                spread(1, 1, 1.0 / 16.0);
+            }
+            ink
+        }
+    }
+}
+
+/// Rec. 709 luma of a color, from 0 to 255.
+fn luma(color: Color) -> f32 {
+    0.2126 * f32::from(color.r) + 0.7152 * f32::from(color.g) + 0.0722 * f32::from(color.b)
+}
@@ -0,0 +1,162 @@
+//! Packed pixel buffers.
+//!
+//! A pixel list stores the coordinates of every pixel next to its color, 16 bytes per pixel.
+//! A [`PixelBuffer`] stores only the colors, row by row, and one origin for all of them,
+//! which takes a third of the memory for dense images and turns lookups, blitting and
+//! comparisons into plain slice operations.
+
+use crate::error::RaelImgError;
+use crate::grid::bounds;
+use crate::{LoadOptions, load_image_with};
+use rael::Color;
+
+/// A rectangle of colors in row-major order, placed at an origin on the canvas.
+///
+/// Every cell holds a color, so pixels missing from the source, such as transparent ones,
+/// take a fill color when a buffer is built from a pixel list.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael::{Canvas, Color};
+/// use rael_img::buffer::PixelBuffer;
+/// use rael_img::canvas::CanvasImageExt;
+/// use rael_img::load_image;
+///
+/// let pixels = load_image("./assets/wallpaper.jpg", Some(160), None, (0, 0), false, 1.0).unwrap();
+/// let black = Color { r: 0, g: 0, b: 0 };
+/// let wallpaper = PixelBuffer::from_pixels(&pixels, black);
+/// drop(pixels);
+///
+/// let mut canvas = Canvas::new(160, 45, black);
+/// canvas.draw_buffer(&wallpaper, 0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PixelBuffer {
+    /// The number of columns.
+    pub width: u32,
+    /// The number of rows.
+    pub height: u32,
+    /// The canvas coordinate of the top-left cell.
+    pub origin: (u32, u32),
+    /// The colors, `width * height` of them, row by row.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_color::list"))]
+    pub data: Vec<Color>,
+}
+
+impl PixelBuffer {
+    /// Creates a buffer of `width` by `height` cells at `origin`, all set to `fill`.
+    pub fn new(width: u32, height: u32, origin: (u32, u32), fill: Color) -> Self {
+        Self {
+            width,
+            height,
+            origin,
+            data: vec![fill; width as usize * height as usize],
+        }
+    }
+
+    /// Packs a pixel list into a buffer covering its bounding box.
+    ///
+    /// Cells without a pixel are set to `fill`. When the list contains the same coordinate
+    /// twice, the last entry wins. An empty list gives an empty buffer.
+    pub fn from_pixels(pixels: &[(u32, u32, Color)], fill: Color) -> Self {
+        let Some((min_x, min_y, max_x, max_y)) = bounds(pixels) else {
+            return Self::default();
+        };
+        let mut buffer = Self::new(max_x - min_x + 1, max_y - min_y + 1, (min_x, min_y), fill);
+        for &(x, y, color) in pixels {
+            buffer.set(x, y, color);
+        }
+        buffer
+    }
+
+    /// Returns `true` if the buffer has no cells.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns the index into [`data`](PixelBuffer::data) of a canvas coordinate, or `None`
+    /// if the buffer does not cover it.
+    pub fn index_of(&self, x: u32, y: u32) -> Option<usize> {
+        let (column, row) = (x.checked_sub(self.origin.0)?, y.checked_sub(self.origin.1)?);
+        (column < self.width && row < self.height)
+            .then(|| row as usize * self.width as usize + column as usize)
+    }
+
+    /// Returns the color at a canvas coordinate, or `None` outside the buffer.
+    pub fn get(&self, x: u32, y: u32) -> Option<Color> {
+        self.index_of(x, y).map(|index| self.data[index])
+    }
+
+    /// Sets the color at a canvas coordinate. Coordinates outside the buffer are ignored.
+    pub fn set(&mut self, x: u32, y: u32, color: Color) {
+        if let Some(index) = self.index_of(x, y) {
+            self.data[index] = color;
+        }
+    }
+
+    /// Returns the colors of row `row`, counted from the top of the buffer, or `None` if it
+    /// has fewer rows.
+    pub fn row(&self, row: u32) -> Option<&[Color]> {
+        let width = self.width as usize;
+        let start = (row < self.height).then(|| row as usize * width)?;
+        Some(&self.data[start..start + width])
+    }
+
+    /// Returns the rows of the buffer from top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &[Color]> {
+        self.data.chunks(self.width.max(1) as usize)
+    }
+
+    /// Returns every cell as an `(x, y, color)` tuple at its canvas coordinate, in
+    /// row-major order.
+    pub fn pixels(&self) -> impl Iterator<Item = (u32, u32, Color)> + '_ {
+        let width = self.width.max(1) as usize;
+        self.data.iter().enumerate().map(move |(index, &color)| {
+            let (column, row) = ((index % width) as u32, (index / width) as u32);
+            (self.origin.0 + column, self.origin.1 + row, color)
+        })
+    }
+
+    /// Unpacks the buffer into a pixel list.
+    pub fn to_pixels(&self) -> Vec<(u32, u32, Color)> {
+        self.pixels().collect()
+    }
+
+    /// Returns the cells of `new` whose color differs from this buffer, like
+    /// [`diff_frames`](crate::diff::diff_frames).
+    ///
+    /// Buffers of the same size at the same origin are compared cell by cell; otherwise
+    /// every cell of `new` is returned.
+    pub fn diff(&self, new: &PixelBuffer) -> Vec<(u32, u32, Color)> {
+        let same_shape =
+            (self.width, self.height, self.origin) == (new.width, new.height, new.origin);
+        if !same_shape {
+            return new.to_pixels();
+        }
+        new.pixels()
+            .zip(&self.data)
+            .filter(|&((_, _, color), &old)| color != old)
+            .map(|(pixel, _)| pixel)
+            .collect()
+    }
+}
+
+/// Loads an image like [`load_image_with`] and packs it into a buffer.
+///
+/// Pixels that are left out, such as transparent ones, take
+/// [`LoadOptions::background`], or black without one.
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(PixelBuffer)`: The packed pixels.
+/// - `Err(RaelImgError)`: If there was an error loading or processing the image.
+pub fn load_image_buffer(path: &str, options: &LoadOptions) -> Result<PixelBuffer, RaelImgError> {
+    let fill = options.background.unwrap_or(Color { r: 0, g: 0, b: 0 });
+    Ok(PixelBuffer::from_pixels(
+        &load_image_with(path, options)?,
+        fill,
+    ))
+}
@@ -0,0 +1,179 @@
+//! Memoized image loads.
+//!
+//! Menus and overlays often load the same image with the same options every frame.
+//! [`ImageCache`] keeps the most recently used results, keyed by path and options, and
+//! hands them back without decoding again. Before an entry is reused, the modification
+//! time of its file is compared with the one it was loaded at, so edited files are picked
+//! up on the next load.
+
+use crate::LoadOptions;
+use crate::error::RaelImgError;
+use crate::loaded::LoadedImage;
+use std::time::SystemTime;
+
+/// How many images an [`ImageCache::default`] keeps.
+pub const DEFAULT_CAPACITY: usize = 16;
+
+/// A least recently used cache of loaded images.
+///
+/// Lookups compare the options of every entry, so the cache is meant for the handful to a
+/// few dozen images an interface shows, not for thousands.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::cache::ImageCache;
+/// use rael_img::LoadOptions;
+///
+/// let mut cache = ImageCache::new(8);
+/// let options = LoadOptions {
+///     width: Some(24),
+///     ..LoadOptions::default()
+/// };
+/// loop {
+///     // Decoded on the first frame, reused on the following ones.
+///     let icon = cache.load("./assets/icon.png", &options).unwrap();
+///     // for &(x, y, color) in icon.pixels() {
+///     //     canvas.set_pixel(x as usize, y as usize, 1, color);
+///     // }
+/// #   break;
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ImageCache {
+    capacity: usize,
+    /// The entries from least to most recently used.
+    entries: Vec<Entry>,
+    hits: u64,
+    misses: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    path: String,
+    options: LoadOptions,
+    modified: Option<SystemTime>,
+    image: LoadedImage,
+}
+
+impl ImageCache {
+    /// Creates a cache that keeps up to `capacity` images. A capacity of 0 is raised to 1.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: Vec::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Loads an image like [`LoadedImage::load`], reusing the cached result if the same
+    /// path was loaded with the same options and the file has not changed since.
+    ///
+    /// Failed loads are not cached.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is:
+    /// - `Ok(&LoadedImage)`: The processed image.
+    /// - `Err(RaelImgError)`: If there was an error loading or processing the image.
+    pub fn load(
+        &mut self,
+        path: &str,
+        options: &LoadOptions,
+    ) -> Result<&LoadedImage, RaelImgError> {
+        let modified = modified(path);
+        let found = self
+            .entries
+            .iter()
+            .position(|entry| entry.path == path && entry.options == *options);
+        let entry = match found {
+            Some(index) if modified.is_some() && self.entries[index].modified == modified => {
+                self.hits += 1;
+                self.entries.remove(index)
+            }
+            found => {
+                if let Some(index) = found {
+                    self.entries.remove(index);
+                }
+                self.misses += 1;
+                let image = LoadedImage::load(path, options)?;
+                if self.entries.len() >= self.capacity {
+                    self.entries.remove(0);
+                }
+                Entry {
+                    path: path.to_string(),
+                    options: *options,
+                    modified,
+                    image,
+                }
+            }
+        };
+        self.entries.push(entry);
+        Ok(&self.entries[self.entries.len() - 1].image)
+    }
+
+    /// Returns the cached image for `path` and `options` without loading or checking the
+    /// file, if there is one.
+    pub fn get(&self, path: &str, options: &LoadOptions) -> Option<&LoadedImage> {
+        self.entries
+            .iter()
+            .find(|entry| entry.path == path && entry.options == *options)
+            .map(|entry| &entry.image)
+    }
+
+    /// Drops every cached image of `path`, whatever its options.
+    pub fn invalidate(&mut self, path: &str) {
+        self.entries.retain(|entry| entry.path != path);
+    }
+
+    /// Drops every cached image.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns how many images the cache keeps at most.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Changes the capacity, dropping the least recently used images that no longer fit.
+    /// A capacity of 0 is raised to 1.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        let excess = self.entries.len().saturating_sub(self.capacity);
+        self.entries.drain(..excess);
+    }
+
+    /// Returns how many images are cached.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no image is cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns how many loads were answered from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Returns how many loads had to decode the file.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+}
+
+impl Default for ImageCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+fn modified(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
@@ -0,0 +1,106 @@
+//! Drawing pixel lists onto a `rael` canvas.
+//!
+//! [`CanvasImageExt`] replaces the loop every caller otherwise writes:
+//!
+//! ```no_run
+//! use rael::{Canvas, Color};
+//! use rael_img::canvas::CanvasImageExt;
+//! use rael_img::load_image;
+//!
+//! let mut canvas = Canvas::new(80, 24, Color { r: 0, g: 0, b: 0 });
+//! let logo = load_image("./assets/logo.png", Some(40), None, (0, 0), false, 1.0).unwrap();
+//! canvas.draw_image(&logo, 1);
+//! // The same pixels again, moved 60 pixels right and 4 up, partly off the canvas.
+//! canvas.draw_image_at(&logo, 60, -4, 2);
+//! print!("{}", canvas.render());
+//! ```
+//!
+//! A [`LoadedImage`] carries the layer it belongs on, so scenes with several layers set
+//! it once where the image is loaded:
+//!
+//! ```no_run
+//! use rael::{Canvas, Color};
+//! use rael_img::canvas::CanvasImageExt;
+//! use rael_img::loader::ImageLoader;
+//!
+//! let mut canvas = Canvas::new(80, 24, Color { r: 0, g: 0, b: 0 });
+//! let background = ImageLoader::new("./assets/bg.png").layer(0).load_image().unwrap();
+//! let player = ImageLoader::new("./assets/player.png").layer(2).load_image().unwrap();
+//! canvas.draw_loaded(&player);
+//! canvas.draw_loaded(&background);
+//! ```
+
+use crate::buffer::PixelBuffer;
+use crate::loaded::LoadedImage;
+use rael::{Canvas, Color};
+
+/// The layer [`LoadedImage`]s are drawn on unless they are given another.
+pub const DEFAULT_LAYER: usize = 1;
+
+/// Draws pixel lists onto a canvas, clipped to its bounds.
+pub trait CanvasImageExt {
+    /// Draws every pixel at its own coordinates on `layer`.
+    ///
+    /// Pixels outside the canvas are skipped, as is everything on a layer the canvas does
+    /// not have.
+    fn draw_image(&mut self, pixels: &(impl AsRef<[(u32, u32, Color)]> + ?Sized), layer: usize);
+
+    /// Draws every pixel offset by `(x, y)` on `layer`.
+    ///
+    /// The offset may be negative, so images can move partly off the left and top edges.
+    fn draw_image_at(
+        &mut self,
+        pixels: &(impl AsRef<[(u32, u32, Color)]> + ?Sized),
+        x: i64,
+        y: i64,
+        layer: usize,
+    );
+
+    /// Draws a loaded image on its own [layer](LoadedImage::layer).
+    fn draw_loaded(&mut self, image: &LoadedImage) {
+        self.draw_image(image, image.layer());
+    }
+
+    /// Draws every cell of a packed buffer at its canvas coordinate on `layer`.
+    ///
+    /// Cells outside the canvas are skipped.
+    fn draw_buffer(&mut self, buffer: &PixelBuffer, layer: usize);
+}
+
+impl CanvasImageExt for Canvas {
+    fn draw_image(&mut self, pixels: &(impl AsRef<[(u32, u32, Color)]> + ?Sized), layer: usize) {
+        self.draw_image_at(pixels, 0, 0, layer);
+    }
+
+    fn draw_image_at(
+        &mut self,
+        pixels: &(impl AsRef<[(u32, u32, Color)]> + ?Sized),
+        x: i64,
+        y: i64,
+        layer: usize,
+    ) {
+        // The canvas has two pixel rows per terminal row.
+        let (width, height) = (self.width as i64, self.height as i64 * 2);
+        for &(px, py, color) in pixels.as_ref() {
+            let (cx, cy) = (i64::from(px) + x, i64::from(py) + y);
+            if (0..width).contains(&cx) && (0..height).contains(&cy) {
+                self.set_pixel(cx as usize, cy as usize, layer, color);
+            }
+        }
+    }
+
+    fn draw_buffer(&mut self, buffer: &PixelBuffer, layer: usize) {
+        let (width, height) = (self.width as u64, self.height as u64 * 2);
+        let (left, top) = (u64::from(buffer.origin.0), u64::from(buffer.origin.1));
+        let columns = width.saturating_sub(left).min(u64::from(buffer.width)) as usize;
+        for (row, colors) in buffer.rows().enumerate() {
+            let y = top + row as u64;
+            if y >= height {
+                break;
+            }
+            for (column, &color) in colors.iter().take(columns).enumerate() {
+                self.set_pixel(left as usize + column, y as usize, layer, color);
+            }
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! Previewing transparency over a checkerboard.
+//!
+//! Pixel lists have no alpha channel, so by default the color of transparent pixels is
+//! used as is, which usually shows as black. Image viewers instead composite transparent
+//! images over a gray checkerboard; [`LoadOptions::checkerboard`](crate::LoadOptions::checkerboard)
+//! does the same, which helps when inspecting sprites outside a game.
+
+use image::RgbaImage;
+use rael::Color;
+
+/// A checkerboard pattern to composite transparent images over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Checkerboard {
+    /// The side of each square in pixels, at least 1.
+    pub cell_size: u32,
+    /// The color of the square at the image's top-left corner.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_color"))]
+    pub light: Color,
+    /// The color of the other squares.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_color"))]
+    pub dark: Color,
+}
+
+impl Default for Checkerboard {
+    fn default() -> Self {
+        Self {
+            cell_size: 4,
+            light: Color {
+                r: 204,
+                g: 204,
+                b: 204,
+            },
+            dark: Color {
+                r: 153,
+                g: 153,
+                b: 153,
+            },
+        }
+    }
+}
+
+impl Checkerboard {
+    /// Returns the pattern color at `(x, y)`, relative to the image's top-left corner.
+    pub fn color_at(&self, x: u32, y: u32) -> Color {
+        let size = self.cell_size.max(1);
+        if (x / size + y / size).is_multiple_of(2) {
+            self.light
+        } else {
+            self.dark
+        }
+    }
+
+    /// Composites an image over the pattern and returns its pixels offset by `position`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rael_img::checkerboard::Checkerboard;
+    ///
+    /// let sprite = image::open("./assets/sprite.png").unwrap().to_rgba8();
+    /// let pixels = Checkerboard::default().composite(&sprite, (0, 0));
+    /// ```
+    pub fn composite(&self, image: &RgbaImage, position: (u32, u32)) -> Vec<(u32, u32, Color)> {
+        image
+            .enumerate_pixels()
+            .map(|(x, y, pixel)| {
+                let [r, g, b, a] = pixel.0;
+                let color = blend(Color { r, g, b }, a, self.color_at(x, y));
+                (x + position.0, y + position.1, color)
+            })
+            .collect()
+    }
+}
+
+/// Blends `color` with an opacity of `alpha` over `under`.
+pub(crate) fn blend(color: Color, alpha: u8, under: Color) -> Color {
+    let mix = |top: u8, bottom: u8| {
+        ((u16::from(top) * u16::from(alpha) + u16::from(bottom) * u16::from(255 - alpha) + 127)
+            / 255) as u8
+    };
+    Color {
+        r: mix(color.r, under.r),
+        g: mix(color.g, under.g),
+        b: mix(color.b, under.b),
+    }
+}
@@ -0,0 +1,91 @@
+//! Color-key transparency.
+//!
+//! Many retro sprite sheets have no alpha channel and mark their transparent areas with a
+//! key color instead, usually magenta. [`LoadOptions::transparent_color`] makes the pixels
+//! of that color transparent before the image is resized, so the key does not bleed into
+//! the edges of the sprite, and leaves them out of the result like transparent pixels with
+//! [`LoadOptions::alpha_threshold`] set.
+//!
+//! [`LoadOptions::transparent_color`]: crate::LoadOptions::transparent_color
+//! [`LoadOptions::alpha_threshold`]: crate::LoadOptions::alpha_threshold
+
+use image::{DynamicImage, GenericImageView};
+use rael::Color;
+use std::borrow::Cow;
+
+/// A color that stands for transparency.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::chroma::ColorKey;
+/// use rael_img::{load_image_with, LoadOptions};
+///
+/// let options = LoadOptions {
+///     transparent_color: Some(ColorKey::MAGENTA),
+///     ..LoadOptions::default()
+/// };
+/// let sprite = load_image_with("./assets/hero.bmp", &options).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColorKey {
+    /// The key color.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_color"))]
+    pub color: Color,
+    /// How far each channel may differ from the key and still match, for keys that went
+    /// through lossy compression. `0` matches the exact color only.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tolerance: u8,
+}
+
+impl ColorKey {
+    /// Pure magenta, `(255, 0, 255)`, the most common key.
+    pub const MAGENTA: ColorKey = ColorKey {
+        color: Color {
+            r: 255,
+            g: 0,
+            b: 255,
+        },
+        tolerance: 0,
+    };
+
+    /// Creates a key for `color` with the given tolerance.
+    pub const fn new(color: Color, tolerance: u8) -> Self {
+        Self { color, tolerance }
+    }
+
+    /// Returns `true` if `color` counts as the key.
+    pub fn matches(&self, color: Color) -> bool {
+        let close = |a: u8, b: u8| a.abs_diff(b) <= self.tolerance;
+        close(color.r, self.color.r) && close(color.g, self.color.g) && close(color.b, self.color.b)
+    }
+
+    /// Leaves out every pixel of a list that matches the key.
+    pub fn remove_from(&self, pixels: &[(u32, u32, Color)]) -> Vec<(u32, u32, Color)> {
+        pixels
+            .iter()
+            .copied()
+            .filter(|&(_, _, color)| !self.matches(color))
+            .collect()
+    }
+
+    /// Returns the image with every pixel that matches the key made fully transparent,
+    /// borrowing it if no pixel matches.
+    pub fn apply<'a>(&self, image: &'a DynamicImage) -> Cow<'a, DynamicImage> {
+        let keyed = |pixel: image::Rgba<u8>| {
+            let [r, g, b, a] = pixel.0;
+            a > 0 && self.matches(Color { r, g, b })
+        };
+        if !image.pixels().any(|(_, _, pixel)| keyed(pixel)) {
+            return Cow::Borrowed(image);
+        }
+        let mut rgba = image.to_rgba8();
+        for pixel in rgba.pixels_mut() {
+            if keyed(*pixel) {
+                pixel.0[3] = 0;
+            }
+        }
+        Cow::Owned(DynamicImage::ImageRgba8(rgba))
+    }
+}
@@ -0,0 +1,229 @@
+//! Flattening several images into one.
+//!
+//! A static background, a frame and a few decorations drawn every frame cost one canvas
+//! write per pixel of every image. A [`Compositor`] stacks them once, blending layers with
+//! transparency over the ones below, and returns a single pixel list to draw instead.
+
+use crate::checkerboard::blend;
+use crate::loaded::LoadedImage;
+use rael::Color;
+
+/// Identifies a layer inside a [`Compositor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LayerId(usize);
+
+#[derive(Debug, Clone)]
+struct Layer {
+    pixels: Vec<(u32, u32, Color, u8)>,
+    offset: (i32, i32),
+    z_index: i32,
+    opacity: f32,
+    visible: bool,
+}
+
+/// A stack of images flattened into one pixel list.
+///
+/// Layers with a higher z-index are drawn over those with a lower one; layers with the same
+/// z-index are drawn in the order they were added. Every layer has an offset added to the
+/// coordinates of its pixels and an opacity from `0.0` to `1.0`, which multiplies the alpha
+/// of its pixels.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::compose::Compositor;
+/// use rael_img::loaded::LoadedImage;
+/// use rael_img::{load_image_rgba, LoadOptions};
+///
+/// let options = LoadOptions::default();
+/// let background = LoadedImage::load("./assets/background.png", &options).unwrap();
+/// let frame = load_image_rgba("./assets/frame.png", &options).unwrap();
+///
+/// let mut compositor = Compositor::new();
+/// compositor.add(&background, (0, 0), 0);
+/// let shade = compositor.add(&background, (2, 2), 1);
+/// compositor.set_opacity(shade, 0.3);
+/// compositor.add_rgba(&frame, (0, 0), 2);
+///
+/// // Draw this every frame instead of the three layers.
+/// let screen = compositor.flatten();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Compositor {
+    layers: Vec<Layer>,
+    background: Option<Color>,
+}
+
+impl Compositor {
+    /// Creates an empty compositor.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an opaque image as a layer.
+    pub fn add(&mut self, image: &LoadedImage, offset: (i32, i32), z_index: i32) -> LayerId {
+        self.add_pixels(image.pixels(), offset, z_index)
+    }
+
+    /// Adds an opaque pixel list as a layer.
+    pub fn add_pixels(
+        &mut self,
+        pixels: &[(u32, u32, Color)],
+        offset: (i32, i32),
+        z_index: i32,
+    ) -> LayerId {
+        let pixels = pixels
+            .iter()
+            .map(|&(x, y, color)| (x, y, color, 255))
+            .collect();
+        self.push(pixels, offset, z_index)
+    }
+
+    /// Adds a pixel list with alpha, such as the result of
+    /// [`load_image_rgba`](crate::load_image_rgba), as a layer.
+    pub fn add_rgba(
+        &mut self,
+        pixels: &[(u32, u32, Color, u8)],
+        offset: (i32, i32),
+        z_index: i32,
+    ) -> LayerId {
+        self.push(pixels.to_vec(), offset, z_index)
+    }
+
+    fn push(
+        &mut self,
+        pixels: Vec<(u32, u32, Color, u8)>,
+        offset: (i32, i32),
+        z_index: i32,
+    ) -> LayerId {
+        self.layers.push(Layer {
+            pixels,
+            offset,
+            z_index,
+            opacity: 1.0,
+            visible: true,
+        });
+        LayerId(self.layers.len() - 1)
+    }
+
+    /// Sets the color that translucent pixels with nothing below them are blended over.
+    /// Without one, they keep their own color.
+    pub fn set_background(&mut self, background: Option<Color>) {
+        self.background = background;
+    }
+
+    /// Moves a layer.
+    pub fn set_offset(&mut self, id: LayerId, offset: (i32, i32)) {
+        if let Some(layer) = self.layers.get_mut(id.0) {
+            layer.offset = offset;
+        }
+    }
+
+    /// Sets a layer's draw priority.
+    pub fn set_z_index(&mut self, id: LayerId, z_index: i32) {
+        if let Some(layer) = self.layers.get_mut(id.0) {
+            layer.z_index = z_index;
+        }
+    }
+
+    /// Sets a layer's opacity, clamped to `0.0..=1.0`.
+    pub fn set_opacity(&mut self, id: LayerId, opacity: f32) {
+        if let Some(layer) = self.layers.get_mut(id.0) {
+            layer.opacity = if opacity.is_nan() {
+                0.0
+            } else {
+                opacity.clamp(0.0, 1.0)
+            };
+        }
+    }
+
+    /// Shows or hides a layer.
+    pub fn set_visible(&mut self, id: LayerId, visible: bool) {
+        if let Some(layer) = self.layers.get_mut(id.0) {
+            layer.visible = visible;
+        }
+    }
+
+    /// Returns the number of layers.
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Returns `true` if there are no layers.
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Flattens the visible layers.
+    ///
+    /// Pixels that land left of or above the canvas origin are left out.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `(x, y, color)` tuples with at most one entry per coordinate, in
+    /// row-major order.
+    pub fn flatten(&self) -> Vec<(u32, u32, Color)> {
+        let mut order: Vec<&Layer> = self
+            .layers
+            .iter()
+            .filter(|layer| layer.visible && layer.opacity > 0.0)
+            .collect();
+        order.sort_by_key(|layer| layer.z_index);
+
+        let bounds = order.iter().flat_map(|layer| placed(layer)).fold(
+            None,
+            |bounds: Option<(u32, u32, u32, u32)>, (x, y, _, _)| match bounds {
+                None => Some((x, y, x, y)),
+                Some((a, b, c, d)) => Some((a.min(x), b.min(y), c.max(x), d.max(y))),
+            },
+        );
+        let Some((min_x, min_y, max_x, max_y)) = bounds else {
+            return Vec::new();
+        };
+        let width = (max_x - min_x + 1) as usize;
+        let height = (max_y - min_y + 1) as usize;
+        let mut cells: Vec<Option<Color>> = vec![None; width * height];
+
+        for layer in order {
+            for (x, y, color, alpha) in placed(layer) {
+                let cell = &mut cells[(y - min_y) as usize * width + (x - min_x) as usize];
+                *cell = Some(match cell.or(self.background) {
+                    Some(under) if alpha < 255 => blend(color, alpha, under),
+                    _ => color,
+                });
+            }
+        }
+
+        cells
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, cell)| {
+                let color = cell?;
+                let (x, y) = (index % width, index / width);
+                Some((x as u32 + min_x, y as u32 + min_y, color))
+            })
+            .collect()
+    }
+
+    /// Flattens the visible layers into a [`LoadedImage`], see
+    /// [`flatten`](Compositor::flatten).
+    pub fn flatten_image(&self) -> LoadedImage {
+        LoadedImage::from_pixels(self.flatten())
+    }
+}
+
+/// The pixels of a layer at their final coordinates, with the opacity applied to their
+/// alpha, leaving out those that land off the canvas or are fully transparent.
+fn placed(layer: &Layer) -> impl Iterator<Item = (u32, u32, Color, u8)> + '_ {
+    let shift = |value: u32, by: i32| u32::try_from(i64::from(value) + i64::from(by));
+    layer
+        .pixels
+        .iter()
+        .filter_map(move |&(x, y, color, alpha)| {
+            let alpha = (f32::from(alpha) * layer.opacity).round() as u8;
+            match (shift(x, layer.offset.0), shift(y, layer.offset.1)) {
+                (Ok(x), Ok(y)) if alpha > 0 => Some((x, y, color, alpha)),
+                _ => None,
+            }
+        })
+}
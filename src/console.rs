@@ -0,0 +1,177 @@
+//! Rendering for 16-color consoles such as the classic Windows console.
+//!
+//! With only 16 colors, a flat color per cell bands badly. This renderer instead picks, for
+//! every cell, a foreground color, a background color and a glyph whose ink coverage mixes
+//! the two into the closest approximation of the cell's average color. With the ASCII glyph
+//! set this works on consoles that cannot display any Unicode at all.
+
+use crate::grid::PixelGrid;
+use crate::quantize::{ANSI_16, WINDOWS_CONSOLE_16};
+use rael::Color;
+use std::fmt::Write;
+
+/// The glyphs a console profile may print, with their approximate ink coverage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConsoleGlyphs {
+    /// Printable ASCII only: space, `.`, `:`, `+` and `#` at increasing coverage.
+    Ascii,
+    /// The code page 437 shade blocks `░ ▒ ▓`, which the legacy console fonts include.
+    Shades,
+}
+
+impl ConsoleGlyphs {
+    fn ramp(self) -> &'static [(char, f32)] {
+        match self {
+            ConsoleGlyphs::Ascii => &[(' ', 0.0), ('.', 0.1), (':', 0.2), ('+', 0.3), ('#', 0.5)],
+            ConsoleGlyphs::Shades => &[(' ', 0.0), ('░', 0.25), ('▒', 0.5), ('▓', 0.75)],
+        }
+    }
+}
+
+/// A 16-color palette and glyph repertoire for a console.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConsoleProfile {
+    /// The actual colors the console shows for the 16 SGR colors.
+    pub palette: [Color; 16],
+    /// The glyphs that may be printed.
+    pub glyphs: ConsoleGlyphs,
+}
+
+impl ConsoleProfile {
+    /// The classic Windows console: its legacy palette and ASCII glyphs only.
+    pub const WINDOWS_LEGACY: ConsoleProfile = ConsoleProfile {
+        palette: WINDOWS_CONSOLE_16,
+        glyphs: ConsoleGlyphs::Ascii,
+    };
+
+    /// A generic 16-color terminal using xterm's palette and ASCII glyphs.
+    pub const ANSI_ASCII: ConsoleProfile = ConsoleProfile {
+        palette: ANSI_16,
+        glyphs: ConsoleGlyphs::Ascii,
+    };
+}
+
+impl Default for ConsoleProfile {
+    fn default() -> Self {
+        Self::WINDOWS_LEGACY
+    }
+}
+
+/// One console cell with palette indices instead of RGB colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConsoleCell {
+    /// The terminal column.
+    pub column: u32,
+    /// The terminal row.
+    pub row: u32,
+    /// The character to print.
+    pub glyph: char,
+    /// The foreground palette index, `0..16`.
+    pub fg: u8,
+    /// The background palette index, `0..16`.
+    pub bg: u8,
+}
+
+/// Renders a pixel list for a 16-color console, one cell per two vertical pixels.
+///
+/// Pixel coordinates follow the `rael` canvas: every terminal row holds two pixel rows,
+/// which are averaged into one cell color.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::console::{console_cells_to_ansi, render_console, ConsoleProfile};
+/// use rael_img::load_image;
+///
+/// let pixels = load_image("./assets/logo.png", Some(60), Some(40), (0, 0), false, 1.0).unwrap();
+/// let cells = render_console(&pixels, &ConsoleProfile::WINDOWS_LEGACY);
+/// print!("{}", console_cells_to_ansi(&cells));
+/// ```
+pub fn render_console(pixels: &[(u32, u32, Color)], profile: &ConsoleProfile) -> Vec<ConsoleCell> {
+    let grid = PixelGrid::from_pixels(pixels);
+    let origin = crate::grid::bounds(pixels).map_or((0, 0), |(x, y, _, _)| (x, y / 2));
+
+    // Every (fg, bg, glyph) combination and the color it appears as from a distance.
+    let mut mixes = Vec::new();
+    for (glyph, coverage) in profile.glyphs.ramp().iter().copied() {
+        for bg in 0..16u8 {
+            for fg in 0..16u8 {
+                if coverage == 0.0 && fg != 0 {
+                    continue;
+                }
+                let (f, b) = (profile.palette[fg as usize], profile.palette[bg as usize]);
+                let mix = |f: u8, b: u8| f32::from(b) + (f32::from(f) - f32::from(b)) * coverage;
+                mixes.push(([mix(f.r, b.r), mix(f.g, b.g), mix(f.b, b.b)], glyph, fg, bg));
+            }
+        }
+    }
+
+    let mut cells = Vec::new();
+    for row in 0..grid.height.div_ceil(2) {
+        for column in 0..grid.width {
+            let samples: Vec<Color> = [grid.get(column, row * 2), grid.get(column, row * 2 + 1)]
+                .into_iter()
+                .flatten()
+                .collect();
+            if samples.is_empty() {
+                continue;
+            }
+            let n = samples.len() as f32;
+            let target = [
+                samples.iter().map(|c| f32::from(c.r)).sum::<f32>() / n,
+                samples.iter().map(|c| f32::from(c.g)).sum::<f32>() / n,
+                samples.iter().map(|c| f32::from(c.b)).sum::<f32>() / n,
+            ];
+
+            let distance =
+                |mix: &[f32; 3]| (0..3).map(|i| (mix[i] - target[i]).powi(2)).sum::<f32>();
+            let &(_, glyph, fg, bg) = mixes
+                .iter()
+                .min_by(|a, b| distance(&a.0).total_cmp(&distance(&b.0)))
+                .expect("at least one mix");
+
+            cells.push(ConsoleCell {
+                column: column + origin.0,
+                row: row + origin.1,
+                glyph,
+                fg,
+                bg,
+            });
+        }
+    }
+    cells
+}
+
+/// Converts console cells into 16-color SGR escape sequences.
+///
+/// Only the classic `30–37`/`90–97` and `40–47`/`100–107` codes are used, which the
+/// Windows console understands once virtual terminal processing is enabled.
+pub fn console_cells_to_ansi(cells: &[ConsoleCell]) -> String {
+    let sgr = |index: u8, base: u8| {
+        if index < 8 {
+            base + index
+        } else {
+            base + 60 + index - 8
+        }
+    };
+
+    let mut output = String::new();
+    let mut cursor: Option<(u32, u32)> = None;
+    let mut colors: Option<(u8, u8)> = None;
+    for cell in cells {
+        if cursor != Some((cell.column, cell.row)) {
+            let _ = write!(output, "\u{1b}[{};{}H", cell.row + 1, cell.column + 1);
+        }
+        if colors != Some((cell.fg, cell.bg)) {
+            let _ = write!(output, "\u{1b}[{};{}m", sgr(cell.fg, 30), sgr(cell.bg, 40));
+            colors = Some((cell.fg, cell.bg));
+        }
+        output.push(cell.glyph);
+        cursor = Some((cell.column + 1, cell.row));
+    }
+    if !cells.is_empty() {
+        output.push_str("\u{1b}[0m");
+    }
+    output
+}
@@ -0,0 +1,110 @@
+//! A debug overlay for tuning layouts.
+//!
+//! [`debug_overlay`] draws three things over a pixel list:
+//!
+//! - A coordinate grid in canvas coordinates, every [`grid_spacing`](DebugOverlay::grid_spacing)
+//!   pixels, blended into the image so it stays visible.
+//! - The bounding box of the image.
+//! - A marker at the canvas origin `(0, 0)`, so the offset of the image is visible too.
+//!
+//! Setting [`LoadOptions::debug`](crate::LoadOptions::debug) applies the default overlay to
+//! everything a load returns.
+
+use rael::Color;
+use std::collections::BTreeMap;
+
+/// The colors and spacing of a [`debug_overlay`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DebugOverlay {
+    /// The distance between grid lines in pixels; `0` draws no grid.
+    pub grid_spacing: u32,
+    /// The grid color, mixed half and half with the pixels below.
+    pub grid_color: Color,
+    /// The color of the bounding box outline.
+    pub bounds_color: Color,
+    /// The color of the origin marker.
+    pub origin_color: Color,
+}
+
+impl Default for DebugOverlay {
+    fn default() -> Self {
+        Self {
+            grid_spacing: 8,
+            grid_color: Color {
+                r: 255,
+                g: 255,
+                b: 255,
+            },
+            bounds_color: Color {
+                r: 255,
+                g: 0,
+                b: 255,
+            },
+            origin_color: Color { r: 0, g: 255, b: 0 },
+        }
+    }
+}
+
+/// The length of each arm of the origin marker, in pixels.
+const MARKER_LENGTH: u32 = 3;
+
+/// Draws a coordinate grid, the bounding box and an origin marker over a pixel list.
+///
+/// The result contains every input pixel, recolored where the overlay covers it, plus the
+/// bounding box and origin marker pixels that fall outside the image.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::debug::{debug_overlay, DebugOverlay};
+/// use rael_img::load_image;
+///
+/// let pixels = load_image("./assets/logo.png", Some(40), None, (10, 4), false, 1.0).unwrap();
+/// let pixels = debug_overlay(&pixels, &DebugOverlay::default());
+/// ```
+pub fn debug_overlay(
+    pixels: &[(u32, u32, Color)],
+    overlay: &DebugOverlay,
+) -> Vec<(u32, u32, Color)> {
+    let Some((min_x, min_y, max_x, max_y)) = crate::grid::bounds(pixels) else {
+        return Vec::new();
+    };
+    let mut canvas: BTreeMap<(u32, u32), Color> = pixels
+        .iter()
+        .map(|&(x, y, color)| ((y, x), color))
+        .collect();
+
+    if overlay.grid_spacing > 0 {
+        for (&(y, x), color) in canvas.iter_mut() {
+            if x % overlay.grid_spacing == 0 || y % overlay.grid_spacing == 0 {
+                *color = mix(*color, overlay.grid_color);
+            }
+        }
+    }
+    for x in min_x..=max_x {
+        canvas.insert((min_y, x), overlay.bounds_color);
+        canvas.insert((max_y, x), overlay.bounds_color);
+    }
+    for y in min_y..=max_y {
+        canvas.insert((y, min_x), overlay.bounds_color);
+        canvas.insert((y, max_x), overlay.bounds_color);
+    }
+    for offset in 0..MARKER_LENGTH {
+        canvas.insert((0, offset), overlay.origin_color);
+        canvas.insert((offset, 0), overlay.origin_color);
+    }
+
+    canvas
+        .into_iter()
+        .map(|((y, x), color)| (x, y, color))
+        .collect()
+}
+
+fn mix(a: Color, b: Color) -> Color {
+    let channel = |a: u8, b: u8| ((u16::from(a) + u16::from(b)) / 2) as u8;
+    Color {
+        r: channel(a.r, b.r),
+        g: channel(a.g, b.g),
+        b: channel(a.b, b.b),
+    }
+}
@@ -0,0 +1,627 @@
+//! Decoding that copes with damaged input.
+//!
+//! [`load_image`](crate::load_image) fails as soon as the decoder reports an error, which
+//! is the right behavior for asset pipelines. Image viewers would rather show the part of an
+//! interrupted download that did arrive. The functions here decode as much of a truncated or
+//! corrupt file as possible and report what was missing in a [`DecodeWarning`].
+//!
+//! Partial decoding is currently implemented for PNG, which covers both interrupted
+//! downloads and damaged chunks. Other formats are decoded normally and still fail on error.
+//!
+//! How much damage is tolerated is governed by [`DecodePolicy`], which is also part of
+//! [`LoadOptions`]: asset pipelines want every violation reported, viewers want a picture.
+//!
+//! For input from untrusted sources, [`load_image_hardened`] enforces size limits, turns
+//! decoder panics into errors and gives up after a timeout.
+
+use crate::LoadOptions;
+use crate::error::RaelImgError;
+use crate::pipeline::{process_bytes_with, process_image, target_size};
+use image::error::{DecodingError, ImageFormatHint};
+use image::error::{LimitError, LimitErrorKind};
+use image::metadata::Orientation;
+use image::{
+    DynamicImage, GrayAlphaImage, GrayImage, ImageError, ImageFormat, RgbImage, RgbaImage,
+};
+use image::{ImageDecoder, ImageReader, Limits};
+use rael::Color;
+use std::io::{self, Cursor, Read};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Describes how much of a damaged image could be decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DecodeWarning {
+    /// The number of rows, from the top, that decoded completely.
+    pub complete_rows: u32,
+    /// The height of the image according to its header.
+    pub total_rows: u32,
+    /// The decoder error that ended decoding.
+    pub message: String,
+}
+
+/// An image that may be incomplete.
+#[derive(Debug, Clone)]
+pub struct PartialImage {
+    /// The decoded image at its full size. Rows that could not be decoded are transparent
+    /// black.
+    pub image: DynamicImage,
+    /// Set if the image is incomplete.
+    pub warning: Option<DecodeWarning>,
+}
+
+/// The pixels of an image that may be incomplete.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialPixels {
+    /// The processed pixels. Rows that could not be decoded are left out.
+    pub pixels: Vec<(u32, u32, Color)>,
+    /// Set if the image is incomplete.
+    pub warning: Option<DecodeWarning>,
+}
+
+/// How to treat recoverable format violations such as bad checksums, damaged ancillary
+/// chunks or missing data at the end of the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum DecodePolicy {
+    /// Every violation the decoder can detect is an error, including CRC and Adler-32
+    /// mismatches. Use this for asset pipelines, where a damaged file should be noticed.
+    #[default]
+    Strict,
+    /// Checksums are not verified, damaged text and color profile chunks are skipped and a
+    /// truncated image keeps the rows that decoded. Use this for viewers.
+    ///
+    /// The relaxed checks apply to PNG; other formats are decoded as usual.
+    Lenient,
+}
+
+/// Limits for decoding untrusted images with [`load_image_hardened`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct HardenedOptions {
+    /// The largest accepted file, in bytes.
+    pub max_file_size: u64,
+    /// The largest accepted width, both of the encoded image and of the resized output.
+    pub max_width: u32,
+    /// The largest accepted height, both of the encoded image and of the resized output.
+    pub max_height: u32,
+    /// The most memory the decoder may allocate, in bytes.
+    pub max_alloc: u64,
+    /// How long decoding and resizing may take. `None` waits forever.
+    pub timeout: Option<Duration>,
+}
+
+impl Default for HardenedOptions {
+    fn default() -> Self {
+        Self {
+            max_file_size: 64 * 1024 * 1024,
+            max_width: 8192,
+            max_height: 8192,
+            max_alloc: 256 * 1024 * 1024,
+            timeout: Some(Duration::from_secs(5)),
+        }
+    }
+}
+
+/// Decodes an encoded image, keeping whatever decoded before an error.
+///
+/// This is [`decode_with_policy`] with [`DecodePolicy::Lenient`].
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(PartialImage)`: The image, with a warning if it is incomplete.
+/// - `Err(RaelImgError)`: If the format is unknown, the header is unreadable or no
+///   row at all could be decoded.
+pub fn decode_partial(bytes: &[u8]) -> Result<PartialImage, RaelImgError> {
+    decode_with_policy(bytes, DecodePolicy::Lenient)
+}
+
+/// Decodes an encoded image under the given policy.
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(PartialImage)`: The image. Only [`DecodePolicy::Lenient`] returns incomplete
+///   images, with a warning.
+/// - `Err(RaelImgError)`: If the image violates the policy or cannot be decoded.
+pub fn decode_with_policy(
+    bytes: &[u8],
+    policy: DecodePolicy,
+) -> Result<PartialImage, RaelImgError> {
+    decode_as(bytes, None, policy)
+}
+
+/// Decodes an encoded image like [`decode_with_policy`], assuming `fallback` if the bytes
+/// do not identify their format, as TGA files do not.
+pub(crate) fn decode_as(
+    bytes: &[u8],
+    fallback: Option<ImageFormat>,
+    policy: DecodePolicy,
+) -> Result<PartialImage, RaelImgError> {
+    let format = match (image::guess_format(bytes), fallback) {
+        (Ok(format), _) | (Err(_), Some(format)) => format,
+        (Err(error), None) => return Err(error.into()),
+    };
+    match format {
+        ImageFormat::Png => decode_png(bytes, policy),
+        format => Ok(PartialImage {
+            image: image::load_from_memory_with_format(bytes, format)?,
+            warning: None,
+        }),
+    }
+}
+
+/// Reads the orientation stored in the EXIF metadata of an encoded image.
+///
+/// Returns `None` if the image has no orientation or its format is unknown. JPEG, TIFF,
+/// WebP and PNG files can carry one.
+pub fn exif_orientation(bytes: &[u8]) -> Option<Orientation> {
+    let reader = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .ok()?;
+    match reader.into_decoder().ok()?.orientation().ok()? {
+        Orientation::NoTransforms => None,
+        orientation => Some(orientation),
+    }
+}
+
+/// Turns a decoded image upright according to the EXIF orientation in `bytes`, if
+/// `options` ask for it.
+pub(crate) fn orient(image: &mut DynamicImage, bytes: &[u8], options: &LoadOptions) {
+    if options.auto_orient
+        && let Some(orientation) = exif_orientation(bytes)
+    {
+        image.apply_orientation(orientation);
+    }
+}
+
+/// Loads an image like [`load_image_with`](crate::load_image_with), but returns the rows
+/// that decoded instead of failing on a truncated or corrupt file.
+///
+/// The image is scaled as if it were complete, so a partial image has the same width and
+/// position as the full one would, just fewer rows.
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(PartialPixels)`: The pixels that could be decoded, with a warning if some are
+///   missing.
+/// - `Err(RaelImgError)`: If the file could not be read or nothing could be decoded.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::decode::load_image_partial;
+/// use rael_img::LoadOptions;
+///
+/// let loaded = load_image_partial("./downloads/photo.png", &LoadOptions::default()).unwrap();
+/// if let Some(warning) = &loaded.warning {
+///     eprintln!(
+///         "showing {} of {} rows: {}",
+///         warning.complete_rows, warning.total_rows, warning.message
+///     );
+/// }
+/// ```
+pub fn load_image_partial(
+    path: &str,
+    options: &LoadOptions,
+) -> Result<PartialPixels, RaelImgError> {
+    load_with_policy(path, options, DecodePolicy::Lenient)
+}
+
+/// Loads and processes an image under `policy`, scaling incomplete images as if complete.
+pub(crate) fn load_with_policy(
+    path: &str,
+    options: &LoadOptions,
+    policy: DecodePolicy,
+) -> Result<PartialPixels, RaelImgError> {
+    let options = LoadOptions { policy, ..*options };
+    process_bytes_with(&std::fs::read(path)?, &options, &file_decoder(path))
+}
+
+/// Returns a decoder that falls back to the format the extension of `path` names.
+pub(crate) fn file_decoder(
+    path: &str,
+) -> impl Fn(&[u8], DecodePolicy) -> Result<PartialImage, RaelImgError> {
+    let fallback = ImageFormat::from_path(path).ok();
+    move |bytes, policy| decode_as(bytes, fallback, policy)
+}
+
+/// Loads an image that may be malicious, like [`load_image_with`](crate::load_image_with)
+/// but with every safeguard of [`HardenedOptions`] applied.
+///
+/// Decoding and resizing run on a separate thread. A decoder panic is caught and returned as
+/// an error; it is still reported by the panic hook. When the timeout expires the call
+/// returns, but the worker thread cannot be stopped and finishes in the background.
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(Vec<(u32, u32, rael::Color)>)`: The processed pixels, offset by `position`.
+/// - `Err(RaelImgError)`: A limit error if the file, the image or the output is too
+///   large, an I/O error of kind `TimedOut` on timeout, or a decoding error if the file is
+///   invalid or the decoder panicked.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::decode::{load_image_hardened, HardenedOptions};
+/// use rael_img::LoadOptions;
+///
+/// let options = LoadOptions {
+///     width: Some(40),
+///     ..LoadOptions::default()
+/// };
+/// match load_image_hardened("./uploads/avatar.png", &options, &HardenedOptions::default()) {
+///     Ok(pixels) => println!("{} pixels", pixels.len()),
+///     Err(error) => eprintln!("refusing to show the image: {error}"),
+/// }
+/// ```
+pub fn load_image_hardened(
+    path: &str,
+    options: &LoadOptions,
+    hardened: &HardenedOptions,
+) -> Result<Vec<(u32, u32, Color)>, RaelImgError> {
+    let file = std::fs::File::open(path)?;
+    if file.metadata()?.len() > hardened.max_file_size {
+        return Err(limit_error(LimitErrorKind::InsufficientMemory));
+    }
+    let mut bytes = Vec::new();
+    file.take(hardened.max_file_size + 1)
+        .read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > hardened.max_file_size {
+        return Err(limit_error(LimitErrorKind::InsufficientMemory));
+    }
+
+    let (options, hardened) = (*options, *hardened);
+    guarded(hardened.timeout, move || {
+        let mut limits = Limits::default();
+        limits.max_image_width = Some(hardened.max_width);
+        limits.max_image_height = Some(hardened.max_height);
+        limits.max_alloc = Some(hardened.max_alloc);
+
+        let mut reader = ImageReader::new(Cursor::new(&bytes[..])).with_guessed_format()?;
+        reader.limits(limits);
+        let mut image = reader.decode()?;
+        orient(&mut image, &bytes, &options);
+
+        let (width, height) = target_size(image.width(), image.height(), &options);
+        if width > hardened.max_width || height > hardened.max_height {
+            return Err(limit_error(LimitErrorKind::DimensionError));
+        }
+        Ok(process_image(&image, &options))
+    })
+}
+
+/// Runs `work` on its own thread, catching panics and giving up after `timeout`.
+fn guarded<T: Send + 'static>(
+    timeout: Option<Duration>,
+    work: impl FnOnce() -> Result<T, RaelImgError> + Send + 'static,
+) -> Result<T, RaelImgError> {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::Builder::new()
+        .name("rael_img-decode".to_string())
+        .spawn(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(work));
+            let _ = sender.send(result);
+        })?;
+
+    let result = match timeout {
+        Some(timeout) => receiver
+            .recv_timeout(timeout)
+            .map_err(|error| match error {
+                mpsc::RecvTimeoutError::Timeout => {
+                    io::Error::new(io::ErrorKind::TimedOut, "decoding timed out")
+                }
+                mpsc::RecvTimeoutError::Disconnected => io::Error::other("decoder thread exited"),
+            })?,
+        None => receiver
+            .recv()
+            .map_err(|_| io::Error::other("decoder thread exited"))?,
+    };
+    result.unwrap_or_else(|_| {
+        Err(RaelImgError::Image(ImageError::Decoding(
+            DecodingError::new(ImageFormatHint::Unknown, "the decoder panicked"),
+        )))
+    })
+}
+
+fn limit_error(kind: LimitErrorKind) -> RaelImgError {
+    RaelImgError::Image(ImageError::Limits(LimitError::from_kind(kind)))
+}
+
+fn decode_png(bytes: &[u8], policy: DecodePolicy) -> Result<PartialImage, RaelImgError> {
+    let lenient = policy == DecodePolicy::Lenient;
+    let mut decode_options = png::DecodeOptions::default();
+    decode_options.set_ignore_crc(lenient);
+    decode_options.set_ignore_adler32(lenient);
+    decode_options.set_ignore_text_chunk(lenient);
+    decode_options.set_ignore_iccp_chunk(lenient);
+    decode_options.set_skip_ancillary_crc_failures(lenient);
+
+    let mut decoder = png::Decoder::new_with_options(Cursor::new(bytes), decode_options);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info().map_err(png_error)?;
+
+    let (width, height) = (reader.info().width, reader.info().height);
+    let interlaced = reader.info().interlaced;
+    let (color_type, _) = reader.output_color_type();
+    let channels = color_type.samples();
+    let stride = width as usize * channels;
+    // The same limits the `image` crate applies by default, checked before allocating.
+    let mut limits = Limits::default();
+    limits.check_dimensions(width, height)?;
+    let per_pixel = channels as u64 + u64::from(interlaced);
+    limits.reserve(u64::from(width) * u64::from(height) * per_pixel)?;
+    let mut data = vec![0u8; stride * height as usize];
+    // One byte per pixel marking what an interlaced pass has filled in. Rows of other
+    // images arrive in order, so counting them is enough.
+    let mut coverage = if interlaced {
+        vec![0u8; width as usize * height as usize]
+    } else {
+        Vec::new()
+    };
+
+    let mut next_line = 0u32;
+    let mut failure = None;
+    loop {
+        match reader.next_interlaced_row() {
+            Ok(Some(row)) => match row.interlace() {
+                png::InterlaceInfo::Null(_) => {
+                    let start = next_line as usize * stride;
+                    data[start..start + stride].copy_from_slice(&row.data()[..stride]);
+                    next_line += 1;
+                }
+                png::InterlaceInfo::Adam7(info) => {
+                    let bits = (channels * 8) as u8;
+                    png::splat_interlaced_row(&mut data, stride, row.data(), info, bits);
+                    let marks = vec![u8::MAX; row.data().len() / channels];
+                    png::splat_interlaced_row(&mut coverage, width as usize, &marks, info, 8);
+                }
+            },
+            Ok(None) => break,
+            Err(error) => {
+                failure = Some(error);
+                break;
+            }
+        }
+    }
+
+    let complete_rows = if interlaced {
+        coverage
+            .chunks_exact(width.max(1) as usize)
+            .take_while(|row| row.iter().all(|&mark| mark != 0))
+            .count() as u32
+    } else {
+        next_line
+    };
+
+    let warning = match failure {
+        None => None,
+        Some(error) if !lenient || complete_rows == 0 => return Err(png_error(error)),
+        Some(error) => Some(DecodeWarning {
+            complete_rows,
+            total_rows: height,
+            message: error.to_string(),
+        }),
+    };
+
+    let image = match color_type {
+        png::ColorType::Grayscale => {
+            GrayImage::from_raw(width, height, data).map(DynamicImage::ImageLuma8)
+        }
+        png::ColorType::GrayscaleAlpha => {
+            GrayAlphaImage::from_raw(width, height, data).map(DynamicImage::ImageLumaA8)
+        }
+        png::ColorType::Rgb => RgbImage::from_raw(width, height, data).map(DynamicImage::ImageRgb8),
+        _ => RgbaImage::from_raw(width, height, data).map(DynamicImage::ImageRgba8),
+    }
+    .ok_or_else(|| decoding_error("decoded buffer has the wrong size".to_string()))?;
+
+    Ok(PartialImage { image, warning })
+}
+
+fn png_error(error: png::DecodingError) -> RaelImgError {
+    match error {
+        png::DecodingError::IoError(error) => RaelImgError::Io(error),
+        error => decoding_error(error.to_string()),
+    }
+}
+
+fn decoding_error(message: String) -> RaelImgError {
+    RaelImgError::Image(ImageError::Decoding(DecodingError::new(
+        ImageFormatHint::Exact(ImageFormat::Png),
+        message,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::GenericImageView;
+
+    /// Encodes a `width` × `height` RGB PNG, red in its first column and black elsewhere,
+    /// that carries an EXIF orientation of `orientation`.
+    fn oriented_png(width: u32, height: u32, orientation: u8) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, width, height);
+        encoder.set_color(png::ColorType::Rgb);
+        let mut writer = encoder.write_header().unwrap();
+        let exif = [
+            b'M',
+            b'M',
+            0,
+            42,
+            0,
+            0,
+            0,
+            8, // TIFF header, first IFD at 8
+            0,
+            1, // one entry
+            0x01,
+            0x12,
+            0,
+            3,
+            0,
+            0,
+            0,
+            1,
+            0,
+            orientation,
+            0,
+            0, // Orientation, SHORT
+            0,
+            0,
+            0,
+            0, // no next IFD
+        ];
+        writer
+            .write_chunk(png::chunk::ChunkType(*b"eXIf"), &exif)
+            .unwrap();
+        let data: Vec<u8> = (0..height)
+            .flat_map(|_| (0..width).flat_map(|x| if x == 0 { [255, 0, 0] } else { [0; 3] }))
+            .collect();
+        writer.write_image_data(&data).unwrap();
+        writer.finish().unwrap();
+        bytes
+    }
+
+    /// Encodes a `size` × `size` RGB PNG of noise, which does not compress, so cutting the
+    /// file short loses the rows after the cut.
+    fn noise_png(size: u32) -> Vec<u8> {
+        let mut state = 1u32;
+        let data: Vec<u8> = (0..size * size * 3)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (state >> 16) as u8
+            })
+            .collect();
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, size, size);
+        encoder.set_color(png::ColorType::Rgb);
+        let mut writer = encoder.write_header().unwrap();
+        // Small IDAT chunks, as most encoders write, so the cut falls between two of them.
+        let mut stream = writer.stream_writer_with_size(1024).unwrap();
+        std::io::Write::write_all(&mut stream, &data).unwrap();
+        stream.finish().unwrap();
+        writer.finish().unwrap();
+        bytes
+    }
+
+    fn temp_file(name: &str, bytes: &[u8]) -> String {
+        let path = std::env::temp_dir().join(format!("rael_img-{}-{name}", std::process::id()));
+        std::fs::write(&path, bytes).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn hardened_load_turns_the_image_upright() {
+        // Orientation 6 is stored turned a quarter left, so the upright image is 2 × 3 with
+        // the red column on top.
+        let path = temp_file("hardened-oriented.png", &oriented_png(3, 2, 6));
+        let pixels =
+            load_image_hardened(&path, &LoadOptions::default(), &HardenedOptions::default());
+        std::fs::remove_file(&path).unwrap();
+        let pixels = pixels.unwrap();
+
+        let red = Color { r: 255, g: 0, b: 0 };
+        assert_eq!(crate::grid::bounds(&pixels), Some((0, 0, 1, 2)));
+        let top: Vec<_> = pixels.iter().filter(|p| p.1 == 0).map(|p| p.2).collect();
+        assert_eq!(top, [red, red]);
+    }
+
+    #[test]
+    fn lenient_png_keeps_the_rows_before_a_cut() {
+        let bytes = noise_png(256);
+        let truncated = &bytes[..bytes.len() / 2];
+
+        let partial = decode_with_policy(truncated, DecodePolicy::Lenient).unwrap();
+        let warning = partial.warning.unwrap();
+        assert_eq!(warning.total_rows, 256);
+        assert!((64..128).contains(&warning.complete_rows), "{warning:?}");
+        assert_eq!(partial.image.dimensions(), (256, 256));
+
+        assert!(decode_with_policy(truncated, DecodePolicy::Strict).is_err());
+    }
+
+    #[test]
+    fn complete_png_has_no_warning() {
+        let partial = decode_with_policy(&noise_png(8), DecodePolicy::Lenient).unwrap();
+        assert!(partial.warning.is_none());
+        assert_eq!(partial.image.dimensions(), (8, 8));
+    }
+
+    #[test]
+    fn exif_orientation_reads_the_tag() {
+        assert_eq!(
+            exif_orientation(&oriented_png(3, 2, 6)),
+            Some(Orientation::Rotate90)
+        );
+        assert_eq!(exif_orientation(&oriented_png(3, 2, 1)), None);
+        assert_eq!(exif_orientation(&noise_png(4)), None);
+    }
+
+    #[test]
+    fn auto_orient_can_be_disabled() {
+        let path = temp_file("stored-as-is.png", &oriented_png(3, 2, 6));
+        let options = LoadOptions {
+            auto_orient: false,
+            ..LoadOptions::default()
+        };
+        let loaded = load_with_policy(&path, &options, DecodePolicy::Strict);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            crate::grid::bounds(&loaded.unwrap().pixels),
+            Some((0, 0, 2, 1))
+        );
+    }
+
+    #[test]
+    fn a_fallback_format_decodes_headerless_files() {
+        let mut tga = Vec::new();
+        DynamicImage::new_rgb8(3, 2)
+            .write_to(&mut Cursor::new(&mut tga), ImageFormat::Tga)
+            .unwrap();
+        assert!(decode_as(&tga, None, DecodePolicy::Strict).is_err());
+        let decoded = decode_as(&tga, Some(ImageFormat::Tga), DecodePolicy::Strict).unwrap();
+        assert_eq!(decoded.image.dimensions(), (3, 2));
+
+        let path = temp_file("headerless.tga", &tga);
+        let loaded = load_with_policy(&path, &LoadOptions::default(), DecodePolicy::Strict);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap().pixels.len(), 6);
+    }
+
+    #[test]
+    fn hardened_load_enforces_its_limits() {
+        let path = temp_file("hardened-limits.png", &noise_png(16));
+        let load = |hardened: HardenedOptions| {
+            load_image_hardened(&path, &LoadOptions::default(), &hardened)
+        };
+        let small_file = load(HardenedOptions {
+            max_file_size: 16,
+            ..HardenedOptions::default()
+        });
+        let narrow = load(HardenedOptions {
+            max_width: 8,
+            ..HardenedOptions::default()
+        });
+        let fine = load(HardenedOptions::default());
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(
+            small_file,
+            Err(RaelImgError::Image(ImageError::Limits(_)))
+        ));
+        assert!(matches!(
+            narrow,
+            Err(RaelImgError::Image(ImageError::Limits(_)))
+        ));
+        assert_eq!(fine.unwrap().len(), 16 * 16);
+    }
+}
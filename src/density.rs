@@ -0,0 +1,196 @@
+//! Character-density rendering, with or without color.
+//!
+//! Every cell becomes one character from a ramp ordered from the least to the most ink, so
+//! the output survives pagers, log files and terminals that cannot show color at all.
+//! [`to_ascii`] keeps the color of every character, for terminals whose font lacks block
+//! characters.
+
+use crate::grid::PixelGrid;
+use crate::width::{AmbiguousWidth, is_single_cell};
+use rael::Color;
+
+/// The default ramp, from no ink to the most ink.
+pub const DEFAULT_RAMP: &str = " .:-=+*#%@";
+
+/// Options for [`render_density`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DensityOptions {
+    /// The characters to use, ordered from the least to the most ink. Characters that are not
+    /// one cell wide are skipped, and a ramp with fewer than two usable characters falls back
+    /// to [`DEFAULT_RAMP`].
+    pub ramp: String,
+    /// The exponent applied to the normalized brightness before a character is picked.
+    /// Values below `1.0` brighten the midtones, values above darken them.
+    pub gamma: f32,
+    /// If `true`, dark pixels get the densest characters. Use this for dark text on a light
+    /// background, such as most pagers and editors in light themes.
+    pub invert: bool,
+    /// How the terminal draws ambiguous-width characters, used to filter the ramp.
+    pub ambiguous_width: AmbiguousWidth,
+}
+
+impl Default for DensityOptions {
+    fn default() -> Self {
+        Self {
+            ramp: DEFAULT_RAMP.to_string(),
+            gamma: 1.0,
+            invert: false,
+            ambiguous_width: AmbiguousWidth::Narrow,
+        }
+    }
+}
+
+/// Renders a pixel list as lines of plain text, one character per two vertical pixels.
+///
+/// Pixel coordinates follow the `rael` canvas: every line holds two pixel rows, which are
+/// averaged. Missing pixels become spaces and trailing spaces are trimmed from every line.
+///
+/// # Returns
+///
+/// The lines of text, top to bottom, without line terminators.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::density::{render_density, DensityOptions};
+/// use rael_img::load_image;
+///
+/// let pixels = load_image("./assets/logo.png", Some(80), Some(80), (0, 0), false, 1.0).unwrap();
+/// let options = DensityOptions {
+///     gamma: 0.8,
+///     ..DensityOptions::default()
+/// };
+/// for line in render_density(&pixels, &options) {
+///     println!("{line}");
+/// }
+/// ```
+pub fn render_density(pixels: &[(u32, u32, Color)], options: &DensityOptions) -> Vec<String> {
+    let ramp = Ramp::new(options);
+    let grid = PixelGrid::from_pixels(pixels);
+    let mut lines = Vec::with_capacity(grid.height.div_ceil(2) as usize);
+    for row in 0..grid.height.div_ceil(2) {
+        let mut line = String::with_capacity(grid.width as usize);
+        for column in 0..grid.width {
+            let samples = samples(&grid, column, row);
+            line.push(ramp.pick(&samples).unwrap_or(' '));
+        }
+        lines.push(line.trim_end().to_string());
+    }
+    lines
+}
+
+/// Converts a pixel list into colored characters, one per two vertical pixels, picked from
+/// the ramp of `options` by luminance.
+///
+/// This is [`render_density`] with the mean color of every cell kept, for terminals that
+/// show color but have no block characters in their font. Coordinates are relative to the
+/// bounding box of the pixels, and cells without pixels are left out.
+///
+/// # Returns
+///
+/// A vector of `(x, y, char, Color)` tuples in row-major order, where `x` is the column
+/// and `y` the line. Print them with
+/// [`char_cells_to_ansi`](crate::mosaic::char_cells_to_ansi).
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::density::{to_ascii, DensityOptions};
+/// use rael_img::load_image;
+/// use rael_img::mosaic::char_cells_to_ansi;
+///
+/// let pixels = load_image("./assets/logo.png", Some(80), Some(80), (0, 0), false, 1.0).unwrap();
+/// let cells = to_ascii(&pixels, &DensityOptions::default());
+/// print!("{}", char_cells_to_ansi(&cells));
+/// ```
+pub fn to_ascii(
+    pixels: &[(u32, u32, Color)],
+    options: &DensityOptions,
+) -> Vec<(u32, u32, char, Color)> {
+    let ramp = Ramp::new(options);
+    let grid = PixelGrid::from_pixels(pixels);
+    let mut cells = Vec::new();
+    for row in 0..grid.height.div_ceil(2) {
+        for column in 0..grid.width {
+            let samples = samples(&grid, column, row);
+            if let Some(glyph) = ramp.pick(&samples) {
+                cells.push((column, row, glyph, mean(&samples)));
+            }
+        }
+    }
+    cells
+}
+
+/// The usable characters of a ramp and how brightness maps onto them.
+struct Ramp {
+    chars: Vec<char>,
+    gamma: f32,
+    invert: bool,
+}
+
+impl Ramp {
+    fn new(options: &DensityOptions) -> Self {
+        let mut chars: Vec<char> = options
+            .ramp
+            .chars()
+            .filter(|&ch| is_single_cell(ch, options.ambiguous_width))
+            .collect();
+        if chars.len() < 2 {
+            chars = DEFAULT_RAMP.chars().collect();
+        }
+        let gamma = if options.gamma.is_finite() && options.gamma > 0.0 {
+            options.gamma
+        } else {
+            1.0
+        };
+        Self {
+            chars,
+            gamma,
+            invert: options.invert,
+        }
+    }
+
+    /// Picks the character for the mean luminance of `samples`, or `None` without samples.
+    fn pick(&self, samples: &[Color]) -> Option<char> {
+        if samples.is_empty() {
+            return None;
+        }
+        let luma =
+            samples.iter().map(|&color| luminance(color)).sum::<f32>() / samples.len() as f32;
+        let mut level = luma.powf(self.gamma);
+        if self.invert {
+            level = 1.0 - level;
+        }
+        let index = (level * (self.chars.len() - 1) as f32).round() as usize;
+        Some(self.chars[index.min(self.chars.len() - 1)])
+    }
+}
+
+/// The pixels of the two rows that make up a line of text.
+fn samples(grid: &PixelGrid, column: u32, row: u32) -> Vec<Color> {
+    [grid.get(column, row * 2), grid.get(column, row * 2 + 1)]
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+fn mean(samples: &[Color]) -> Color {
+    let n = samples.len() as u32;
+    let channel = |value: fn(&Color) -> u8| {
+        let sum: u32 = samples.iter().map(|color| u32::from(value(color))).sum();
+        ((sum + n / 2) / n) as u8
+    };
+    Color {
+        r: channel(|color| color.r),
+        g: channel(|color| color.g),
+        b: channel(|color| color.b),
+    }
+}
+
+/// Rec. 709 luma of a color, in `0.0..=1.0`.
+fn luminance(color: Color) -> f32 {
+    (0.2126 * f32::from(color.r) + 0.7152 * f32::from(color.g) + 0.0722 * f32::from(color.b))
+        / 255.0
+}
@@ -0,0 +1,188 @@
+//! Blurring, sharpening and pixelation.
+//!
+//! [`Detail`] changes how much fine detail the resized image keeps, before it is turned
+//! into pixels and its colors are adjusted, see
+//! [`LoadOptions::detail`](crate::LoadOptions::detail). A blur moves a background image
+//! out of the way of text drawn over it, an unsharp mask brings back edges that the
+//! downscale softened, and pixelation gives a deliberately chunky retro look at large
+//! terminal sizes.
+//!
+//! All three work on premultiplied alpha, so transparent pixels do not darken the edges of
+//! sprites.
+
+use crate::resample::{premultiply, unpremultiply};
+use image::{DynamicImage, Rgba32FImage};
+use std::borrow::Cow;
+
+/// Blur, sharpening and pixelation of an image. The default changes nothing.
+///
+/// The steps run in the order of the fields. Sizes are in pixels of the resized image.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::detail::Detail;
+/// use rael_img::{load_image_with, LoadOptions};
+///
+/// let options = LoadOptions {
+///     width: Some(120),
+///     detail: Detail {
+///         pixelate: 4,
+///         ..Detail::NONE
+///     },
+///     ..LoadOptions::default()
+/// };
+/// let retro = load_image_with("./assets/castle.png", &options).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Detail {
+    /// The standard deviation of a Gaussian blur. `0.0` does not blur.
+    pub blur: f32,
+    /// The strength of an unsharp mask: how much of the difference to a blurred copy is
+    /// added back. `0.0` does not sharpen, `1.0` doubles the local contrast of edges.
+    pub sharpen: f32,
+    /// The standard deviation of the blur the unsharp mask compares with, which sets how
+    /// wide the edges it sharpens are.
+    pub sharpen_radius: f32,
+    /// The side of the square blocks that are filled with their average color. `0` and `1`
+    /// do not pixelate.
+    pub pixelate: u32,
+}
+
+impl Default for Detail {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+impl Detail {
+    /// Settings that change nothing.
+    pub const NONE: Detail = Detail {
+        blur: 0.0,
+        sharpen: 0.0,
+        sharpen_radius: 1.0,
+        pixelate: 0,
+    };
+
+    /// Returns `true` if the settings change nothing.
+    pub fn is_none(&self) -> bool {
+        !self.blurs() && !self.sharpens() && !self.pixelates()
+    }
+
+    fn blurs(&self) -> bool {
+        self.blur > 0.0
+    }
+
+    fn sharpens(&self) -> bool {
+        self.sharpen != 0.0 && self.sharpen_radius > 0.0
+    }
+
+    fn pixelates(&self) -> bool {
+        self.pixelate >= 2
+    }
+
+    /// Applies the settings to an image, borrowing it if they change nothing.
+    ///
+    /// Images without an alpha channel are returned as RGB, the others as RGBA.
+    pub fn apply<'a>(&self, image: &'a DynamicImage) -> Cow<'a, DynamicImage> {
+        if self.is_none() {
+            return Cow::Borrowed(image);
+        }
+        let mut buffer = image.to_rgba32f();
+        premultiply(&mut buffer);
+        if self.blurs() {
+            buffer = gaussian_blur(&buffer, self.blur);
+        }
+        if self.sharpens() {
+            unsharp_mask(&mut buffer, self.sharpen, self.sharpen_radius);
+        }
+        if self.pixelates() {
+            pixelate(&mut buffer, self.pixelate);
+        }
+        unpremultiply(&mut buffer);
+        let result = DynamicImage::ImageRgba32F(buffer);
+        Cow::Owned(if image.color().has_alpha() {
+            result.into_rgba8().into()
+        } else {
+            result.into_rgb8().into()
+        })
+    }
+}
+
+/// Blurs a buffer with a separable Gaussian kernel, repeating the edge pixels.
+fn gaussian_blur(buffer: &Rgba32FImage, sigma: f32) -> Rgba32FImage {
+    let radius = (sigma * 3.0).ceil().max(1.0) as i64;
+    let weights: Vec<f32> = (-radius..=radius)
+        .map(|offset| (-((offset * offset) as f32) / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let total: f32 = weights.iter().sum();
+    let weights: Vec<f32> = weights.iter().map(|weight| weight / total).collect();
+
+    let (width, height) = buffer.dimensions();
+    let pass = |source: &Rgba32FImage, horizontal: bool| {
+        Rgba32FImage::from_fn(width, height, |x, y| {
+            let mut sum = [0.0f32; 4];
+            for (index, weight) in weights.iter().enumerate() {
+                let offset = index as i64 - radius;
+                let (sx, sy) = if horizontal {
+                    (
+                        (i64::from(x) + offset).clamp(0, i64::from(width) - 1),
+                        i64::from(y),
+                    )
+                } else {
+                    (
+                        i64::from(x),
+                        (i64::from(y) + offset).clamp(0, i64::from(height) - 1),
+                    )
+                };
+                let pixel = source.get_pixel(sx as u32, sy as u32);
+                for channel in 0..4 {
+                    sum[channel] += pixel[channel] * weight;
+                }
+            }
+            image::Rgba(sum)
+        })
+    };
+    pass(&pass(buffer, true), false)
+}
+
+/// Adds `amount` times the difference to a blurred copy back to every pixel.
+fn unsharp_mask(buffer: &mut Rgba32FImage, amount: f32, radius: f32) {
+    let blurred = gaussian_blur(buffer, radius);
+    for (pixel, soft) in buffer.pixels_mut().zip(blurred.pixels()) {
+        let alpha = pixel[3];
+        // Premultiplied channels stay between 0 and alpha.
+        for channel in 0..3 {
+            let sharp = pixel[channel] + amount * (pixel[channel] - soft[channel]);
+            pixel[channel] = sharp.clamp(0.0, alpha);
+        }
+    }
+}
+
+/// Fills every `size` by `size` block with its average color.
+fn pixelate(buffer: &mut Rgba32FImage, size: u32) {
+    let (width, height) = buffer.dimensions();
+    for top in (0..height).step_by(size as usize) {
+        for left in (0..width).step_by(size as usize) {
+            let (right, bottom) = ((left + size).min(width), (top + size).min(height));
+            let mut sum = [0.0f32; 4];
+            for y in top..bottom {
+                for x in left..right {
+                    let pixel = buffer.get_pixel(x, y);
+                    for channel in 0..4 {
+                        sum[channel] += pixel[channel];
+                    }
+                }
+            }
+            let count = ((right - left) * (bottom - top)) as f32;
+            let average = image::Rgba(sum.map(|channel| channel / count));
+            for y in top..bottom {
+                for x in left..right {
+                    buffer.put_pixel(x, y, average);
+                }
+            }
+        }
+    }
+}
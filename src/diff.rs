@@ -0,0 +1,54 @@
+//! Incremental frame updates.
+//!
+//! Redrawing a whole image every frame costs as much as the image is large, even when only
+//! a few pixels move. [`diff_frames`] returns just the pixels that differ from the previous
+//! frame, so a canvas that still shows the old frame can be brought up to date by drawing
+//! those. Pixels the old frame had and the new one lacks are reported by [`vacated`]; they
+//! have to be cleared to the background.
+//!
+//! Both functions take pixel lists, so they work with [`LoadedImage`](crate::loaded::LoadedImage)s
+//! as well as the frames of an [`Animation`](crate::animation::Animation).
+
+use rael::Color;
+use std::collections::{HashMap, HashSet};
+
+/// Returns the pixels of `new` that are missing from `old` or have another color there.
+///
+/// The pixels keep the order they have in `new`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael::{Canvas, Color};
+/// use rael_img::animation::Animation;
+/// use rael_img::canvas::CanvasImageExt;
+/// use rael_img::diff::diff_frames;
+/// use rael_img::LoadOptions;
+///
+/// let animation = Animation::load("./assets/fire.gif", &LoadOptions::default()).unwrap();
+/// let mut canvas = Canvas::new(80, 24, Color { r: 0, g: 0, b: 0 });
+/// let frames = animation.frames();
+/// canvas.draw_image(&frames[0], 1);
+/// for pair in frames.windows(2) {
+///     canvas.draw_image(&diff_frames(&pair[0], &pair[1]), 1);
+///     print!("{}", canvas.render());
+/// }
+/// ```
+pub fn diff_frames(old: &[(u32, u32, Color)], new: &[(u32, u32, Color)]) -> Vec<(u32, u32, Color)> {
+    let previous: HashMap<(u32, u32), Color> =
+        old.iter().map(|&(x, y, color)| ((x, y), color)).collect();
+    new.iter()
+        .filter(|&&(x, y, color)| previous.get(&(x, y)) != Some(&color))
+        .copied()
+        .collect()
+}
+
+/// Returns the coordinates that have a pixel in `old` but none in `new`, in the order of
+/// `old`.
+pub fn vacated(old: &[(u32, u32, Color)], new: &[(u32, u32, Color)]) -> Vec<(u32, u32)> {
+    let covered: HashSet<(u32, u32)> = new.iter().map(|&(x, y, _)| (x, y)).collect();
+    old.iter()
+        .map(|&(x, y, _)| (x, y))
+        .filter(|position| !covered.contains(position))
+        .collect()
+}
@@ -0,0 +1,155 @@
+//! Dithering to the palettes of limited-color terminals.
+//!
+//! Terminals with 256 or 16 colors map every truecolor pixel to the nearest color they
+//! have, which turns smooth gradients into bands. Dithering picks palette colors so that
+//! neighbouring pixels average out to the original color:
+//!
+//! - [`DitherMethod::FloydSteinberg`] diffuses the error of each pixel to its neighbours. It
+//!   gives the closest result, but a change to one pixel can ripple through the rest of the
+//!   image, which flickers in animations.
+//! - [`DitherMethod::Bayer4`] and [`DitherMethod::Bayer8`] add a fixed threshold pattern
+//!   before picking the nearest color. The result has a regular cross-hatched look and every
+//!   pixel only depends on itself, so it is stable from frame to frame.
+//!
+//! Setting [`LoadOptions::dither`](crate::LoadOptions::dither) dithers everything a load
+//! returns.
+
+use crate::quantize::{ANSI_16, WINDOWS_CONSOLE_16, XTERM_256, nearest_index, remap_dithered};
+use rael::Color;
+use std::collections::HashMap;
+
+/// A dithering algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum DitherMethod {
+    /// No dithering; every pixel takes its nearest palette color.
+    None,
+    /// Floyd–Steinberg error diffusion.
+    #[default]
+    FloydSteinberg,
+    /// Ordered dithering with a 4×4 Bayer matrix.
+    Bayer4,
+    /// Ordered dithering with an 8×8 Bayer matrix, which has more levels but a coarser
+    /// pattern.
+    Bayer8,
+}
+
+/// A terminal palette to dither to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum TerminalPalette {
+    /// The 16 ANSI colors, see [`ANSI_16`].
+    Ansi16,
+    /// The 16 colors of the classic Windows console, see [`WINDOWS_CONSOLE_16`].
+    WindowsConsole16,
+    /// The 256 xterm colors, see [`XTERM_256`].
+    #[default]
+    Xterm256,
+}
+
+impl TerminalPalette {
+    /// Returns the colors of the palette.
+    pub fn colors(self) -> &'static [Color] {
+        match self {
+            TerminalPalette::Ansi16 => &ANSI_16,
+            TerminalPalette::WindowsConsole16 => &WINDOWS_CONSOLE_16,
+            TerminalPalette::Xterm256 => &XTERM_256,
+        }
+    }
+}
+
+/// A palette and the algorithm to dither to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DitherOptions {
+    /// The palette the pixels are reduced to.
+    pub palette: TerminalPalette,
+    /// The dithering algorithm.
+    pub method: DitherMethod,
+}
+
+/// Reduces pixels to `palette`, dithering with `method`.
+///
+/// An empty palette leaves the pixels unchanged.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::dither::{dither, DitherMethod};
+/// use rael_img::load_image;
+/// use rael_img::quantize::ANSI_16;
+///
+/// let pixels = load_image("./assets/sunset.jpg", Some(60), None, (0, 0), false, 1.0).unwrap();
+/// let pixels = dither(&pixels, &ANSI_16, DitherMethod::Bayer4);
+/// ```
+pub fn dither(
+    pixels: &[(u32, u32, Color)],
+    palette: &[Color],
+    method: DitherMethod,
+) -> Vec<(u32, u32, Color)> {
+    match method {
+        DitherMethod::None => crate::quantize::remap(pixels, palette),
+        DitherMethod::FloydSteinberg => remap_dithered(pixels, palette),
+        DitherMethod::Bayer4 => ordered(pixels, palette, 4),
+        DitherMethod::Bayer8 => ordered(pixels, palette, 8),
+    }
+}
+
+/// Applies `options` to pixels; see [`dither`].
+pub fn dither_with(
+    pixels: &[(u32, u32, Color)],
+    options: &DitherOptions,
+) -> Vec<(u32, u32, Color)> {
+    dither(pixels, options.palette.colors(), options.method)
+}
+
+/// Ordered dithering with a `size` × `size` Bayer matrix, `size` a power of two.
+///
+/// The pattern is anchored at the canvas origin, so an image keeps its pattern wherever
+/// pixels with the same canvas coordinates have the same color.
+fn ordered(pixels: &[(u32, u32, Color)], palette: &[Color], size: u32) -> Vec<(u32, u32, Color)> {
+    if palette.is_empty() {
+        return pixels.to_vec();
+    }
+    // About the distance between neighbouring palette colors, assuming they are spread
+    // evenly over the color cube.
+    let spread = 255.0 / (palette.len() as f32).cbrt();
+    let levels = (size * size) as f32;
+    let mut cache: HashMap<(u8, u8, u8, u32), Color> = HashMap::new();
+    pixels
+        .iter()
+        .map(|&(x, y, color)| {
+            let rank = bayer(x % size, y % size, size);
+            let chosen = *cache
+                .entry((color.r, color.g, color.b, rank))
+                .or_insert_with(|| {
+                    let offset = ((rank as f32 + 0.5) / levels - 0.5) * spread;
+                    let shift =
+                        |channel: u8| (f32::from(channel) + offset).round().clamp(0.0, 255.0) as u8;
+                    let shifted = Color {
+                        r: shift(color.r),
+                        g: shift(color.g),
+                        b: shift(color.b),
+                    };
+                    palette[nearest_index(palette, shifted)]
+                });
+            (x, y, chosen)
+        })
+        .collect()
+}
+
+/// Returns the rank of `(x, y)` in the `size` × `size` Bayer matrix, from 0 to `size² - 1`.
+pub(crate) fn bayer(x: u32, y: u32, size: u32) -> u32 {
+    // M(2n) = 4 M(n) + M(2) of the quadrant, so the lowest bits weigh the most.
+    let mut rank = 0;
+    let mut bit = 1;
+    while bit < size {
+        let (bx, by) = (u32::from(x & bit != 0), u32::from(y & bit != 0));
+        rank = rank * 4 + 2 * (bx ^ by) + by;
+        bit *= 2;
+    }
+    rank
+}
@@ -0,0 +1,193 @@
+//! Color adjustments and color modes.
+//!
+//! Photos often come out too dark or washed out on a terminal, whose colors look different
+//! from a monitor calibrated for images. [`Adjustments`] tunes the result while loading,
+//! through [`LoadOptions::adjustments`](crate::LoadOptions::adjustments), or afterwards on
+//! any pixel list. The tone curve goes through a lookup table, so adjusting a loaded image
+//! every frame costs one table lookup per channel plus the saturation blend.
+//!
+//! [`ColorMode`] replaces the colors altogether, with grays, sepia tones or pure black and
+//! white, for status displays and terminals with poor color support. It is applied after
+//! the adjustments, see [`LoadOptions::color_mode`](crate::LoadOptions::color_mode).
+//!
+//! With the `rayon` feature, lists are converted in parallel; the order of the pixels stays.
+
+use crate::parallel;
+use rael::Color;
+
+/// Color adjustments. The default leaves every color unchanged.
+///
+/// The steps run in the order of the fields: saturation first, then brightness, contrast and
+/// gamma on each channel.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::effects::Adjustments;
+/// use rael_img::{load_image_with, LoadOptions};
+///
+/// let options = LoadOptions {
+///     width: Some(60),
+///     adjustments: Adjustments {
+///         brightness: 0.1,
+///         contrast: 1.2,
+///         ..Adjustments::NONE
+///     },
+///     ..LoadOptions::default()
+/// };
+/// let pixels = load_image_with("./assets/night.jpg", &options).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Adjustments {
+    /// The saturation factor. `0.0` gives grays, values above `1.0` more vivid colors.
+    pub saturation: f32,
+    /// The amount added to every channel, from `-1.0` (black) to `1.0` (white).
+    pub brightness: f32,
+    /// The contrast factor around middle gray. Values below `1.0` flatten the image.
+    pub contrast: f32,
+    /// The gamma. Values above `1.0` brighten the midtones, values below darken them; the
+    /// black and white points stay.
+    pub gamma: f32,
+}
+
+impl Adjustments {
+    /// Adjustments that leave every color unchanged.
+    pub const NONE: Adjustments = Adjustments {
+        saturation: 1.0,
+        brightness: 0.0,
+        contrast: 1.0,
+        gamma: 1.0,
+    };
+
+    /// Returns `true` if the adjustments leave every color unchanged.
+    pub fn is_none(&self) -> bool {
+        *self == Self::NONE
+    }
+
+    /// Adjusts a single color.
+    pub fn apply(&self, color: Color) -> Color {
+        self.apply_with(&self.tone_curve(), color)
+    }
+
+    /// Adjusts every pixel of a list, returning a new list.
+    pub fn apply_to(&self, pixels: &[(u32, u32, Color)]) -> Vec<(u32, u32, Color)> {
+        if self.is_none() {
+            return pixels.to_vec();
+        }
+        let curve = self.tone_curve();
+        parallel::map(pixels, |&(x, y, color)| {
+            (x, y, self.apply_with(&curve, color))
+        })
+    }
+
+    fn apply_with(&self, curve: &[u8; 256], color: Color) -> Color {
+        let color = if self.saturation == 1.0 {
+            color
+        } else {
+            saturate(color, self.saturation)
+        };
+        Color {
+            r: curve[color.r as usize],
+            g: curve[color.g as usize],
+            b: curve[color.b as usize],
+        }
+    }
+
+    /// Builds the lookup table for brightness, contrast and gamma.
+    fn tone_curve(&self) -> [u8; 256] {
+        let gamma = if self.gamma > 0.0 { self.gamma } else { 1.0 };
+        let mut curve = [0; 256];
+        for (value, entry) in curve.iter_mut().enumerate() {
+            let mut level = value as f32 / 255.0 + self.brightness;
+            level = (level - 0.5) * self.contrast + 0.5;
+            level = level.clamp(0.0, 1.0).powf(1.0 / gamma);
+            *entry = (level * 255.0).round() as u8;
+        }
+        curve
+    }
+}
+
+impl Default for Adjustments {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// How colors are shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum ColorMode {
+    /// The colors of the image.
+    #[default]
+    TrueColor,
+    /// Grays of the same luma.
+    Grayscale,
+    /// Brown tones, like an old photograph.
+    Sepia,
+    /// Black for pixels with a luma below the threshold, white for the others.
+    Monochrome(u8),
+}
+
+impl ColorMode {
+    /// Converts a single color.
+    pub fn apply(self, color: Color) -> Color {
+        match self {
+            ColorMode::TrueColor => color,
+            ColorMode::Grayscale => {
+                let gray = luma(color).round() as u8;
+                Color {
+                    r: gray,
+                    g: gray,
+                    b: gray,
+                }
+            }
+            ColorMode::Sepia => {
+                let [r, g, b] = [color.r, color.g, color.b].map(f32::from);
+                let channel = |value: f32| value.round().min(255.0) as u8;
+                Color {
+                    r: channel(0.393 * r + 0.769 * g + 0.189 * b),
+                    g: channel(0.349 * r + 0.686 * g + 0.168 * b),
+                    b: channel(0.272 * r + 0.534 * g + 0.131 * b),
+                }
+            }
+            ColorMode::Monochrome(threshold) => {
+                let level = if luma(color) >= f32::from(threshold) {
+                    255
+                } else {
+                    0
+                };
+                Color {
+                    r: level,
+                    g: level,
+                    b: level,
+                }
+            }
+        }
+    }
+
+    /// Converts every pixel of a list, returning a new list.
+    pub fn apply_to(self, pixels: &[(u32, u32, Color)]) -> Vec<(u32, u32, Color)> {
+        parallel::map(pixels, |&(x, y, color)| (x, y, self.apply(color)))
+    }
+}
+
+fn luma(color: Color) -> f32 {
+    0.2126 * f32::from(color.r) + 0.7152 * f32::from(color.g) + 0.0722 * f32::from(color.b)
+}
+
+fn saturate(color: Color, factor: f32) -> Color {
+    let gray = luma(color);
+    let channel = |value: u8| {
+        (gray + (f32::from(value) - gray) * factor)
+            .round()
+            .clamp(0.0, 255.0) as u8
+    };
+    Color {
+        r: channel(color.r),
+        g: channel(color.g),
+        b: channel(color.b),
+    }
+}
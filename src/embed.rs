@@ -0,0 +1,62 @@
+//! Images embedded in the binary at compile time.
+//!
+//! With the `macros` feature, [`include_image!`](crate::include_image) decodes and resizes
+//! an image while the crate is compiled and stores its pixels as an [`EmbeddedImage`]. A
+//! single-binary tool then carries its logo without an assets folder and without decoding
+//! it at startup.
+
+use crate::loaded::LoadedImage;
+use rael::Color;
+use std::ops::Deref;
+
+/// The color type the expansion of `include_image!` refers to.
+#[doc(hidden)]
+pub use rael::Color as __Color;
+
+/// Pixels stored in the binary, as produced by [`include_image!`](crate::include_image).
+///
+/// It derefs to the pixel slice, so it is drawn like any pixel list.
+///
+/// # Examples
+///
+/// ```ignore
+/// use rael::{Canvas, Color};
+/// use rael_img::canvas::CanvasImageExt;
+/// use rael_img::embed::EmbeddedImage;
+/// use rael_img::include_image;
+///
+/// static LOGO: EmbeddedImage = include_image!("assets/logo.png", width = 40);
+///
+/// let mut canvas = Canvas::new(80, 24, Color { r: 0, g: 0, b: 0 });
+/// canvas.draw_image(&LOGO, 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmbeddedImage {
+    /// The width of the image in pixels.
+    pub width: u32,
+    /// The height of the image in pixels.
+    pub height: u32,
+    /// The pixels, at the origin, in row-major order.
+    pub pixels: &'static [(u32, u32, Color)],
+}
+
+impl EmbeddedImage {
+    /// Copies the pixels into a [`LoadedImage`] of the same size, to move or compose them.
+    pub fn to_loaded(&self) -> LoadedImage {
+        LoadedImage::with_bounds(self.pixels.to_vec(), self.width, self.height, (0, 0))
+    }
+}
+
+impl Deref for EmbeddedImage {
+    type Target = [(u32, u32, Color)];
+
+    fn deref(&self) -> &Self::Target {
+        self.pixels
+    }
+}
+
+impl AsRef<[(u32, u32, Color)]> for EmbeddedImage {
+    fn as_ref(&self) -> &[(u32, u32, Color)] {
+        self.pixels
+    }
+}
@@ -0,0 +1,61 @@
+//! The error type of this crate.
+
+use image::ImageError;
+use std::fmt;
+use std::io;
+
+/// Everything that can go wrong while loading, processing or saving an image.
+///
+/// Errors of the `image` crate convert into this type, so custom
+/// [`Decoder`](crate::backend::Decoder)s can use `?` on them.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RaelImgError {
+    /// A file could not be read or written.
+    Io(io::Error),
+    /// The `image` crate could not decode or encode an image.
+    Image(ImageError),
+    /// A parameter is out of range or does not fit the data it applies to.
+    InvalidParameters(String),
+    /// The file or operation is not supported, such as an image format without a decoder.
+    Unsupported(String),
+    /// A download failed.
+    Network(io::Error),
+}
+
+impl fmt::Display for RaelImgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RaelImgError::Io(error) => write!(f, "I/O error: {error}"),
+            RaelImgError::Image(error) => write!(f, "{error}"),
+            RaelImgError::InvalidParameters(message) => write!(f, "invalid parameters: {message}"),
+            RaelImgError::Unsupported(message) => write!(f, "unsupported: {message}"),
+            RaelImgError::Network(error) => write!(f, "download failed: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for RaelImgError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RaelImgError::Io(error) | RaelImgError::Network(error) => Some(error),
+            RaelImgError::Image(error) => Some(error),
+            RaelImgError::InvalidParameters(_) | RaelImgError::Unsupported(_) => None,
+        }
+    }
+}
+
+impl From<ImageError> for RaelImgError {
+    fn from(error: ImageError) -> Self {
+        match error {
+            ImageError::IoError(error) => RaelImgError::Io(error),
+            error => RaelImgError::Image(error),
+        }
+    }
+}
+
+impl From<io::Error> for RaelImgError {
+    fn from(error: io::Error) -> Self {
+        RaelImgError::Io(error)
+    }
+}
@@ -0,0 +1,198 @@
+//! Exporting rendered frames as animated GIF files.
+//!
+//! Games keep the state of their canvas as pixel lists or color grids anyway, so a replay or
+//! demo can be recorded by handing the same data to a [`GifWriter`] every frame. Each
+//! half-block pixel becomes a `scale` × `scale` block, since terminal resolutions are tiny
+//! next to the screens the GIF will be watched on.
+
+use crate::animation::{Animation, Repeat};
+use crate::error::RaelImgError;
+use image::codecs::gif::{self, GifEncoder};
+use image::{Delay, Frame, Rgba, RgbaImage};
+use rael::Color;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::time::Duration;
+
+/// Options for [`GifWriter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GifOptions {
+    /// The size in GIF pixels of every source pixel, at least 1.
+    pub scale: u32,
+    /// The color of areas that a pixel list leaves empty, usually the canvas clear color.
+    pub background: Color,
+    /// How often viewers play the GIF.
+    pub repeat: Repeat,
+}
+
+impl Default for GifOptions {
+    fn default() -> Self {
+        Self {
+            scale: 1,
+            background: Color { r: 0, g: 0, b: 0 },
+            repeat: Repeat::Infinite,
+        }
+    }
+}
+
+/// Writes frames to an animated GIF as they are produced.
+///
+/// The GIF is complete once the writer is dropped.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::export::{GifOptions, GifWriter};
+/// use std::time::Duration;
+///
+/// let options = GifOptions { scale: 4, ..GifOptions::default() };
+/// // A canvas of 80 × 24 cells has 80 × 48 pixels.
+/// let mut gif = GifWriter::create("./replay.gif", 80, 48, &options).unwrap();
+/// # let frames: Vec<Vec<(u32, u32, rael::Color)>> = Vec::new();
+/// for pixels in &frames {
+///     gif.add_frame(pixels, Duration::from_millis(50)).unwrap();
+/// }
+/// ```
+pub struct GifWriter<W: Write> {
+    encoder: GifEncoder<W>,
+    width: u32,
+    height: u32,
+    options: GifOptions,
+}
+
+impl GifWriter<BufWriter<File>> {
+    /// Creates the file at `path` and writes a GIF of `width` by `height` source pixels to
+    /// it.
+    pub fn create(
+        path: &str,
+        width: u32,
+        height: u32,
+        options: &GifOptions,
+    ) -> Result<Self, RaelImgError> {
+        Self::new(BufWriter::new(File::create(path)?), width, height, options)
+    }
+}
+
+impl<W: Write> GifWriter<W> {
+    /// Starts a GIF of `width` by `height` source pixels on `writer`.
+    pub fn new(
+        writer: W,
+        width: u32,
+        height: u32,
+        options: &GifOptions,
+    ) -> Result<Self, RaelImgError> {
+        let mut encoder = GifEncoder::new(writer);
+        match options.repeat {
+            Repeat::Infinite => encoder.set_repeat(gif::Repeat::Infinite)?,
+            // The loop count stored in a GIF counts repetitions after the first play.
+            Repeat::Times(times) if times > 1 => {
+                let repetitions = u16::try_from(times - 1).unwrap_or(u16::MAX);
+                encoder.set_repeat(gif::Repeat::Finite(repetitions))?;
+            }
+            Repeat::Times(_) => {}
+        }
+        Ok(Self {
+            encoder,
+            width,
+            height,
+            options: GifOptions {
+                scale: options.scale.max(1),
+                ..*options
+            },
+        })
+    }
+
+    /// Adds a frame from a pixel list, shown for `delay`.
+    ///
+    /// Pixels outside the GIF's area are ignored and missing ones take the background color.
+    pub fn add_frame(
+        &mut self,
+        pixels: &[(u32, u32, Color)],
+        delay: Duration,
+    ) -> Result<(), RaelImgError> {
+        let mut grid = vec![self.options.background; self.width as usize * self.height as usize];
+        for &(x, y, color) in pixels {
+            if x < self.width && y < self.height {
+                grid[y as usize * self.width as usize + x as usize] = color;
+            }
+        }
+        self.add_grid(&grid, delay)
+    }
+
+    /// Adds a frame from a row-major grid of exactly `width` × `height` colors, shown for
+    /// `delay`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is:
+    /// - `Ok(())`: If the frame was written.
+    /// - `Err(RaelImgError)`: `RaelImgError::InvalidParameters` if the grid has the wrong
+    ///   size, or the error of the underlying writer.
+    pub fn add_grid(&mut self, colors: &[Color], delay: Duration) -> Result<(), RaelImgError> {
+        if colors.len() != self.width as usize * self.height as usize {
+            return Err(RaelImgError::InvalidParameters(format!(
+                "expected {} colors for a {}x{} frame, got {}",
+                self.width as usize * self.height as usize,
+                self.width,
+                self.height,
+                colors.len()
+            )));
+        }
+        let scale = self.options.scale;
+        let image = RgbaImage::from_fn(self.width * scale, self.height * scale, |x, y| {
+            let color = colors[(y / scale) as usize * self.width as usize + (x / scale) as usize];
+            Rgba([color.r, color.g, color.b, 255])
+        });
+        let delay = Delay::from_saturating_duration(delay);
+        self.encoder
+            .encode_frame(Frame::from_parts(image, 0, 0, delay))?;
+        Ok(())
+    }
+}
+
+/// Writes an animation as an animated GIF, using its playback order and repeat count.
+///
+/// The GIF covers the bounding box of all frames' pixels. `options.repeat` is ignored in
+/// favor of [`Animation::repeat`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::animation::Animation;
+/// use rael_img::export::{save_gif, GifOptions};
+/// use rael_img::LoadOptions;
+///
+/// let walk = Animation::load("./assets/walk.gif", &LoadOptions::default()).unwrap();
+/// save_gif("./walk_reversed.gif", &walk.reversed(), &GifOptions::default()).unwrap();
+/// ```
+pub fn save_gif(
+    path: &str,
+    animation: &Animation,
+    options: &GifOptions,
+) -> Result<(), RaelImgError> {
+    let all: Vec<(u32, u32, ())> = animation
+        .frames()
+        .iter()
+        .flatten()
+        .map(|&(x, y, _)| (x, y, ()))
+        .collect();
+    let Some((min_x, min_y, max_x, max_y)) = crate::grid::bounds(&all) else {
+        return Err(RaelImgError::InvalidParameters(
+            "the animation has no pixels".to_string(),
+        ));
+    };
+
+    let options = GifOptions {
+        repeat: animation.repeat(),
+        ..*options
+    };
+    let mut writer = GifWriter::create(path, max_x - min_x + 1, max_y - min_y + 1, &options)?;
+    for &index in animation.sequence() {
+        let pixels: Vec<_> = animation.frames()[index]
+            .iter()
+            .map(|&(x, y, color)| (x - min_x, y - min_y, color))
+            .collect();
+        writer.add_frame(&pixels, animation.delays()[index])?;
+    }
+    Ok(())
+}
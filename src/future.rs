@@ -0,0 +1,187 @@
+//! Loading without blocking an async runtime (feature `async`).
+//!
+//! Decoding a large photo takes long enough to stall an event loop. The functions here run
+//! the whole load on a small pool of worker threads owned by this crate and return a
+//! [`LoadFuture`] that completes when the pixels are ready. The future only needs a waker,
+//! so it works with every executor: tokio, async-std, smol or a hand-written one.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), rael_img::error::RaelImgError> {
+//! use rael_img::future::load_image_async;
+//! use rael_img::LoadOptions;
+//!
+//! let options = LoadOptions {
+//!     width: Some(60),
+//!     ..LoadOptions::default()
+//! };
+//! let pixels = load_image_async("./assets/photo.jpg", &options).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::RaelImgError;
+use crate::{LoadOptions, load_image_from_bytes, load_image_with};
+use image::ImageError;
+use image::error::{DecodingError, ImageFormatHint};
+use rael::Color;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{Context, Poll, Waker};
+
+/// The largest number of worker threads, so a burst of loads does not start one thread each.
+const MAX_WORKERS: usize = 4;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A load running on the worker pool.
+///
+/// Dropping the future does not cancel the work, it only discards the result.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless awaited"]
+pub struct LoadFuture<T> {
+    shared: Arc<Mutex<Shared<T>>>,
+}
+
+#[derive(Debug)]
+struct Shared<T> {
+    result: Option<Result<T, RaelImgError>>,
+    waker: Option<Waker>,
+}
+
+impl<T> Future for LoadFuture<T> {
+    type Output = Result<T, RaelImgError>;
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut shared = self
+            .shared
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        match shared.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                shared.waker = Some(context.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Runs `work` on the worker pool and returns a future for its result.
+///
+/// This is how the other functions here are built; use it to run any blocking load, such as
+/// [`Animation::load`](crate::animation::Animation::load), off the event loop. A panic in
+/// `work` becomes a decoding error.
+pub fn run_blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T, RaelImgError> + Send + 'static,
+) -> LoadFuture<T> {
+    let shared = Arc::new(Mutex::new(Shared {
+        result: None,
+        waker: None,
+    }));
+    let future = LoadFuture {
+        shared: Arc::clone(&shared),
+    };
+    submit(Box::new(move || {
+        let result = panic::catch_unwind(AssertUnwindSafe(work)).unwrap_or_else(|_| {
+            Err(RaelImgError::Image(ImageError::Decoding(
+                DecodingError::new(ImageFormatHint::Unknown, "the decoder panicked"),
+            )))
+        });
+        let mut shared = shared.lock().unwrap_or_else(|error| error.into_inner());
+        shared.result = Some(result);
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    }));
+    future
+}
+
+/// Loads an image like [`load_image_with`] without blocking the caller.
+///
+/// # Returns
+///
+/// A future which resolves to:
+/// - `Ok(Vec<(u32, u32, Color)>)`: A vector of pixel tuples `(x, y, Color)`.
+/// - `Err(RaelImgError)`: If there was an error loading or processing the image.
+pub fn load_image_async(
+    path: impl Into<String>,
+    options: &LoadOptions,
+) -> LoadFuture<Vec<(u32, u32, Color)>> {
+    let (path, options) = (path.into(), *options);
+    run_blocking(move || load_image_with(&path, &options))
+}
+
+/// Loads an image from encoded bytes like [`load_image_from_bytes`] without blocking the
+/// caller.
+pub fn load_image_from_bytes_async(
+    bytes: impl Into<Vec<u8>>,
+    options: &LoadOptions,
+) -> LoadFuture<Vec<(u32, u32, Color)>> {
+    let (bytes, options) = (bytes.into(), *options);
+    run_blocking(move || load_image_from_bytes(&bytes, &options))
+}
+
+/// Downloads and loads an image like
+/// [`load_image_from_url`](crate::http::load_image_from_url) without blocking the caller.
+#[cfg(feature = "http")]
+pub fn load_image_from_url_async(
+    url: impl Into<String>,
+    options: &LoadOptions,
+) -> LoadFuture<Vec<(u32, u32, Color)>> {
+    let (url, options) = (url.into(), *options);
+    run_blocking(move || crate::http::load_image_from_url(&url, &options))
+}
+
+/// Hands a job to the pool, starting another worker while there are fewer than
+/// [`MAX_WORKERS`].
+fn submit(job: Job) {
+    static POOL: OnceLock<Mutex<Pool>> = OnceLock::new();
+    let pool = POOL.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel();
+        Mutex::new(Pool {
+            sender,
+            receiver: Arc::new(Mutex::new(receiver)),
+            workers: 0,
+        })
+    });
+    let mut pool = pool.lock().unwrap_or_else(|error| error.into_inner());
+    let workers = std::thread::available_parallelism().map_or(1, usize::from);
+    if pool.workers < workers.min(MAX_WORKERS) {
+        let receiver = Arc::clone(&pool.receiver);
+        let spawned = std::thread::Builder::new()
+            .name("rael_img-load".to_string())
+            .spawn(move || work(&receiver));
+        if spawned.is_ok() {
+            pool.workers += 1;
+        }
+    }
+    if pool.workers == 0 {
+        // Not even one thread could be started; block rather than never finishing.
+        job();
+        return;
+    }
+    // The workers never exit, so the receiver is alive.
+    let _ = pool.sender.send(job);
+}
+
+struct Pool {
+    sender: Sender<Job>,
+    receiver: Arc<Mutex<Receiver<Job>>>,
+    workers: usize,
+}
+
+fn work(receiver: &Mutex<Receiver<Job>>) {
+    loop {
+        let job = {
+            let receiver = receiver.lock().unwrap_or_else(|error| error.into_inner());
+            match receiver.recv() {
+                Ok(job) => job,
+                Err(_) => return,
+            }
+        };
+        job();
+    }
+}
@@ -0,0 +1,87 @@
+//! Generated images: solid fills and gradients.
+//!
+//! Backdrops, panels and fades often do not need a file. The functions here produce a
+//! [`LoadedImage`] at the origin, the same as a loaded image, so generated and loaded
+//! content are drawn, composed and moved the same way.
+//!
+//! Colors are interpolated per channel in sRGB, as CSS gradients are.
+
+use crate::loaded::LoadedImage;
+use rael::Color;
+
+/// Creates an image of `width` by `height` pixels, all of one color.
+pub fn solid(width: u32, height: u32, color: Color) -> LoadedImage {
+    generate(width, height, |_, _| color)
+}
+
+/// Creates an image that fades from `from` to `to` along a direction.
+///
+/// `angle` is in degrees, clockwise from pointing right: `0.0` fades from the left edge to
+/// the right one, `90.0` from top to bottom, `45.0` from the top-left corner to the
+/// bottom-right one.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael::Color;
+/// use rael_img::canvas::CanvasImageExt;
+/// use rael_img::generate::linear_gradient;
+///
+/// let dusk = linear_gradient(
+///     80,
+///     48,
+///     Color { r: 20, g: 24, b: 82 },
+///     Color { r: 250, g: 120, b: 60 },
+///     90.0,
+/// );
+/// let mut canvas = rael::Canvas::new(80, 24, Color { r: 0, g: 0, b: 0 });
+/// canvas.draw_image(&dusk, 0);
+/// ```
+pub fn linear_gradient(width: u32, height: u32, from: Color, to: Color, angle: f32) -> LoadedImage {
+    let (sin, cos) = f64::from(angle).to_radians().sin_cos();
+    // Project every pixel center onto the direction; the corners give the extent.
+    let project = |x: f64, y: f64| x * cos + y * sin;
+    let (right, bottom) = (f64::from(width), f64::from(height));
+    let corners = [
+        project(0.0, 0.0),
+        project(right, 0.0),
+        project(0.0, bottom),
+        project(right, bottom),
+    ];
+    let start = corners.iter().copied().fold(f64::INFINITY, f64::min);
+    let end = corners.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let length = (end - start).max(f64::EPSILON);
+    generate(width, height, |x, y| {
+        let t = (project(f64::from(x) + 0.5, f64::from(y) + 0.5) - start) / length;
+        mix(from, to, t)
+    })
+}
+
+/// Creates an image that fades from `inner` at its center to `outer` at its corners.
+pub fn radial_gradient(width: u32, height: u32, inner: Color, outer: Color) -> LoadedImage {
+    let (center_x, center_y) = (f64::from(width) / 2.0, f64::from(height) / 2.0);
+    let radius = center_x.hypot(center_y).max(f64::EPSILON);
+    generate(width, height, |x, y| {
+        let distance = (f64::from(x) + 0.5 - center_x).hypot(f64::from(y) + 0.5 - center_y);
+        mix(inner, outer, distance / radius)
+    })
+}
+
+fn generate(width: u32, height: u32, color: impl Fn(u32, u32) -> Color) -> LoadedImage {
+    let pixels = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| (x, y, color(x, y)))
+        .collect();
+    LoadedImage::from_pixels(pixels)
+}
+
+/// Mixes two colors, `t` from `0.0` (`from`) to `1.0` (`to`).
+fn mix(from: Color, to: Color, t: f64) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    let channel = |a: u8, b: u8| (f64::from(a) + (f64::from(b) - f64::from(a)) * t).round() as u8;
+    Color {
+        r: channel(from.r, to.r),
+        g: channel(from.g, to.g),
+        b: channel(from.b, to.b),
+    }
+}
@@ -0,0 +1,127 @@
+//! A dense, row-major view over a list of `(x, y, color)` pixel tuples.
+//!
+//! Most helpers in this crate accept the pixel lists produced by [`crate::load_image`].
+//! Those lists carry their own coordinates, so algorithms that need random access
+//! (wrapping, neighbourhood lookups, compositing) first rebuild a grid from them.
+
+use rael::Color;
+
+/// A rectangular grid of optional colors rebuilt from a pixel list.
+///
+/// The grid covers the bounding box of the source pixels. Cells that had no pixel
+/// in the source list are `None`.
+#[derive(Debug, Clone)]
+pub(crate) struct PixelGrid {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) cells: Vec<Option<Color>>,
+}
+
+impl PixelGrid {
+    /// Creates an empty grid of the given size.
+    pub(crate) fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![None; width as usize * height as usize],
+        }
+    }
+
+    /// Builds a grid from a pixel list. Returns an empty grid for an empty list.
+    /// When the list contains the same coordinate twice, the last entry wins.
+    pub(crate) fn from_pixels(pixels: &[(u32, u32, Color)]) -> Self {
+        let Some((min_x, min_y, max_x, max_y)) = bounds(pixels) else {
+            return Self {
+                width: 0,
+                height: 0,
+                cells: Vec::new(),
+            };
+        };
+
+        let width = max_x - min_x + 1;
+        let height = max_y - min_y + 1;
+        let mut cells = vec![None; width as usize * height as usize];
+        for &(x, y, color) in pixels {
+            let index = (y - min_y) as usize * width as usize + (x - min_x) as usize;
+            cells[index] = Some(color);
+        }
+
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Builds a grid of the given size whose top-left cell is `origin`. Pixels outside
+    /// the box are left out, and cells with no pixel stay empty.
+    pub(crate) fn from_region(
+        pixels: &[(u32, u32, Color)],
+        origin: (u32, u32),
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let mut grid = Self::new(width, height);
+        for &(x, y, color) in pixels {
+            grid.set(
+                i64::from(x) - i64::from(origin.0),
+                i64::from(y) - i64::from(origin.1),
+                color,
+            );
+        }
+        grid
+    }
+
+    /// Returns the color at local grid coordinates, or `None` if the cell is empty
+    /// or out of range.
+    pub(crate) fn get(&self, x: u32, y: u32) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.cells[y as usize * self.width as usize + x as usize]
+    }
+
+    /// Sets the color at local grid coordinates. Out-of-range coordinates are ignored.
+    pub(crate) fn set(&mut self, x: i64, y: i64, color: Color) {
+        if x < 0 || y < 0 || x >= i64::from(self.width) || y >= i64::from(self.height) {
+            return;
+        }
+        self.cells[y as usize * self.width as usize + x as usize] = Some(color);
+    }
+
+    /// Converts the grid back into a row-major pixel list, skipping empty cells.
+    pub(crate) fn to_pixels(&self, position: (u32, u32)) -> Vec<(u32, u32, Color)> {
+        let width = self.width as usize;
+        self.cells
+            .iter()
+            .enumerate()
+            .filter_map(|(index, color)| {
+                color.map(|color| {
+                    (
+                        (index % width) as u32 + position.0,
+                        (index / width) as u32 + position.1,
+                        color,
+                    )
+                })
+            })
+            .collect()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+}
+
+/// Returns the inclusive bounding box `(min_x, min_y, max_x, max_y)` of a pixel list.
+pub(crate) fn bounds<T>(pixels: &[(u32, u32, T)]) -> Option<(u32, u32, u32, u32)> {
+    let mut iter = pixels.iter();
+    let &(x, y, _) = iter.next()?;
+    let mut result = (x, y, x, y);
+    for &(x, y, _) in iter {
+        result.0 = result.0.min(x);
+        result.1 = result.1.min(y);
+        result.2 = result.2.max(x);
+        result.3 = result.3.max(y);
+    }
+    Some(result)
+}
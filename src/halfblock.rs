@@ -0,0 +1,211 @@
+//! Half-block rendering with correct proportions.
+//!
+//! Terminal cells are about twice as tall as they are wide, so an image drawn one pixel per
+//! cell comes out stretched vertically. [`render_half_blocks`] maps two vertically adjacent
+//! pixels to one cell instead: the upper pixel becomes the foreground of `▀` and the lower one
+//! its background, which makes every pixel roughly square. This is the layout the `rael`
+//! canvas uses too, so pixel lists from [`load_image`](crate::load_image) keep the same
+//! proportions whether they are drawn on a canvas or printed directly.
+//!
+//! For renderers that do show one pixel per cell, [`aspect_corrected`] returns load options
+//! that halve the image height up front.
+//!
+//! Programs that only print an image once, without a canvas, can use [`to_ansi_string`] to
+//! get it as lines of text ready for `println!`.
+
+use crate::LoadOptions;
+use crate::loaded::LoadedImage;
+use crate::mosaic::GlyphCell;
+use crate::quantize::{XTERM_256, nearest_index};
+use rael::Color;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Options for [`render_half_blocks`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HalfBlockOptions {
+    /// The color shown in the half of a cell that has no pixel, usually the terminal or
+    /// canvas background.
+    pub background: Color,
+}
+
+impl Default for HalfBlockOptions {
+    fn default() -> Self {
+        Self {
+            background: Color { r: 0, g: 0, b: 0 },
+        }
+    }
+}
+
+/// Renders a pixel list with two pixels per cell.
+///
+/// The pixel at `(x, y)` lands in column `x` and row `y / 2`, in the upper half for even
+/// `y`. Cells without any pixel are skipped.
+///
+/// # Returns
+///
+/// A vector of [`GlyphCell`]s in row-major order, ready for
+/// [`cells_to_ansi`](crate::mosaic::cells_to_ansi).
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::halfblock::{render_half_blocks, HalfBlockOptions};
+/// use rael_img::load_image;
+/// use rael_img::mosaic::cells_to_ansi;
+///
+/// // 40 columns by 20 rows.
+/// let pixels = load_image("./assets/photo.jpg", Some(40), Some(40), (0, 0), false, 1.0).unwrap();
+/// let cells = render_half_blocks(&pixels, &HalfBlockOptions::default());
+/// print!("{}", cells_to_ansi(&cells));
+/// ```
+pub fn render_half_blocks(
+    pixels: &[(u32, u32, Color)],
+    options: &HalfBlockOptions,
+) -> Vec<GlyphCell> {
+    let mut halves: BTreeMap<(u32, u32), [Option<Color>; 2]> = BTreeMap::new();
+    for &(x, y, color) in pixels {
+        halves.entry((y / 2, x)).or_default()[(y % 2) as usize] = Some(color);
+    }
+
+    halves
+        .into_iter()
+        .map(|((row, column), [upper, lower])| {
+            let (glyph, fg, bg) = match (upper, lower) {
+                (Some(upper), lower) => ('▀', upper, lower.unwrap_or(options.background)),
+                (None, lower) => ('▄', lower.unwrap_or(options.background), options.background),
+            };
+            GlyphCell {
+                column,
+                row,
+                glyph,
+                fg,
+                bg,
+            }
+        })
+        .collect()
+}
+
+/// Returns options that load an image of `image_size` at the size `options` would give it,
+/// but half as tall, for renderers that show one pixel per cell.
+///
+/// The image size is needed because the height `options` lead to may depend on the aspect
+/// ratio; [`image::image_dimensions`] reads it from the file header.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::halfblock::aspect_corrected;
+/// use rael_img::{load_image_with, LoadOptions};
+///
+/// let options = LoadOptions {
+///     width: Some(60),
+///     ..LoadOptions::default()
+/// };
+/// let size = image::image_dimensions("./assets/photo.jpg").unwrap();
+/// let pixels = load_image_with("./assets/photo.jpg", &aspect_corrected(&options, size)).unwrap();
+/// ```
+pub fn aspect_corrected(options: &LoadOptions, image_size: (u32, u32)) -> LoadOptions {
+    let (width, height) = crate::pipeline::target_size(image_size.0, image_size.1, options);
+    LoadOptions {
+        width: Some(width),
+        height: Some(height.div_ceil(2).max(1)),
+        stretch: true,
+        ..*options
+    }
+}
+
+/// Renders an image as lines of half-block characters with truecolor escape sequences.
+///
+/// Unlike [`cells_to_ansi`](crate::mosaic::cells_to_ansi), the string does not move the
+/// cursor: it starts wherever the cursor is, the lines are separated by newlines, and every
+/// line ends with a reset. Halves without a pixel show the terminal background.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::halfblock::to_ansi_string;
+/// use rael_img::loaded::LoadedImage;
+/// use rael_img::LoadOptions;
+///
+/// let options = LoadOptions {
+///     width: Some(40),
+///     alpha_threshold: 1,
+///     ..LoadOptions::default()
+/// };
+/// let logo = LoadedImage::load("./assets/logo.png", &options).unwrap();
+/// println!("{}", to_ansi_string(&logo));
+/// ```
+pub fn to_ansi_string(image: &LoadedImage) -> String {
+    ansi_lines(image, |output, layer, color| {
+        let _ = write!(
+            output,
+            "\u{1b}[{layer}8;2;{};{};{}m",
+            color.r, color.g, color.b
+        );
+    })
+}
+
+/// Renders an image like [`to_ansi_string`], with xterm 256-color escape sequences for
+/// terminals without truecolor support.
+///
+/// Colors are mapped like [`cells_to_ansi_256`](crate::mosaic::cells_to_ansi_256) does.
+pub fn to_ansi_string_256(image: &LoadedImage) -> String {
+    let mut cache: BTreeMap<(u8, u8, u8), usize> = BTreeMap::new();
+    ansi_lines(image, |output, layer, color| {
+        let index = *cache
+            .entry((color.r, color.g, color.b))
+            .or_insert_with(|| 16 + nearest_index(&XTERM_256[16..], color));
+        let _ = write!(output, "\u{1b}[{layer}8;5;{index}m");
+    })
+}
+
+/// Writes the half-block lines of an image, calling `color` to set the foreground (layer
+/// `3`) or background (layer `4`) when it changes.
+fn ansi_lines(image: &LoadedImage, mut color: impl FnMut(&mut String, u8, Color)) -> String {
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    let (left, top) = image.position();
+    let mut grid: Vec<Option<Color>> = vec![None; width * height];
+    for &(x, y, pixel) in image.pixels() {
+        if let (Some(column), Some(row)) = (x.checked_sub(left), y.checked_sub(top))
+            && (column as usize) < width
+            && (row as usize) < height
+        {
+            grid[row as usize * width + column as usize] = Some(pixel);
+        }
+    }
+
+    let mut output = String::new();
+    for row in (0..height).step_by(2) {
+        if row > 0 {
+            output.push('\n');
+        }
+        // `None` is the terminal default; the line starts from it.
+        let (mut fg, mut bg): (Option<Color>, Option<Color>) = (None, None);
+        for column in 0..width {
+            let upper = grid[row * width + column];
+            let lower = grid.get((row + 1) * width + column).copied().flatten();
+            let (glyph, want_fg, want_bg) = match (upper, lower) {
+                (Some(upper), lower) => ('▀', Some(upper), lower),
+                (None, Some(lower)) => ('▄', Some(lower), None),
+                (None, None) => (' ', fg, None),
+            };
+            if want_bg != bg {
+                match want_bg {
+                    Some(want) => color(&mut output, 4, want),
+                    None => output.push_str("\u{1b}[49m"),
+                }
+                bg = want_bg;
+            }
+            if let Some(want) = want_fg
+                && want_fg != fg
+            {
+                color(&mut output, 3, want);
+                fg = want_fg;
+            }
+            output.push(glyph);
+        }
+        output.push_str("\u{1b}[0m");
+    }
+    output
+}
@@ -0,0 +1,166 @@
+//! Rendering numeric grids as colored heatmaps.
+
+use crate::error::RaelImgError;
+use rael::Color;
+
+const VIRIDIS: [(u8, u8, u8); 9] = [
+    (68, 1, 84),
+    (71, 44, 122),
+    (59, 81, 139),
+    (44, 113, 142),
+    (33, 144, 141),
+    (39, 173, 129),
+    (92, 200, 99),
+    (170, 220, 50),
+    (253, 231, 37),
+];
+
+const MAGMA: [(u8, u8, u8); 9] = [
+    (0, 0, 4),
+    (28, 16, 68),
+    (79, 18, 123),
+    (129, 37, 129),
+    (181, 54, 122),
+    (229, 80, 100),
+    (251, 135, 97),
+    (254, 194, 135),
+    (252, 253, 191),
+];
+
+/// Maps normalised values in `0.0..=1.0` to colors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Colormap {
+    /// The perceptually uniform blue-green-yellow map popularised by matplotlib.
+    Viridis,
+    /// The perceptually uniform black-purple-orange-white map.
+    Magma,
+    /// Black to white.
+    Grayscale,
+    /// Evenly spaced color stops, interpolated linearly. An empty list renders black.
+    Custom(Vec<Color>),
+}
+
+impl Colormap {
+    /// Returns the color for a normalised value. Values outside `0.0..=1.0` are clamped.
+    pub fn sample(&self, t: f32) -> Color {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        match self {
+            Colormap::Viridis => interpolate_stops(&VIRIDIS.map(tuple_to_color), t),
+            Colormap::Magma => interpolate_stops(&MAGMA.map(tuple_to_color), t),
+            Colormap::Grayscale => {
+                let v = (t * 255.0).round() as u8;
+                Color { r: v, g: v, b: v }
+            }
+            Colormap::Custom(stops) => interpolate_stops(stops, t),
+        }
+    }
+}
+
+fn tuple_to_color((r, g, b): (u8, u8, u8)) -> Color {
+    Color { r, g, b }
+}
+
+fn interpolate_stops(stops: &[Color], t: f32) -> Color {
+    match stops {
+        [] => Color { r: 0, g: 0, b: 0 },
+        [only] => *only,
+        _ => {
+            let scaled = t * (stops.len() - 1) as f32;
+            let index = (scaled.floor() as usize).min(stops.len() - 2);
+            let fraction = scaled - index as f32;
+            let (a, b) = (stops[index], stops[index + 1]);
+            let mix = |a: u8, b: u8| {
+                (f32::from(a) + (f32::from(b) - f32::from(a)) * fraction).round() as u8
+            };
+            Color {
+                r: mix(a.r, b.r),
+                g: mix(a.g, b.g),
+                b: mix(a.b, b.b),
+            }
+        }
+    }
+}
+
+/// Options controlling how values are mapped and where the heatmap is placed.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct HeatmapOptions {
+    /// The value mapped to the start of the colormap. Defaults to the smallest finite value.
+    pub min: Option<f32>,
+    /// The value mapped to the end of the colormap. Defaults to the largest finite value.
+    pub max: Option<f32>,
+    /// The `(x, y)` offset applied to each pixel.
+    pub position: (u32, u32),
+    /// The color used for NaN and infinite values. If `None`, such cells are left out.
+    pub missing_color: Option<Color>,
+}
+
+/// Renders a row-major grid of values as a heatmap.
+///
+/// # Arguments
+///
+/// * `data` - The values, `width * height` of them, in row-major order.
+/// * `width` - The number of columns in the grid.
+/// * `height` - The number of rows in the grid.
+/// * `colormap` - The colormap that values are mapped through.
+/// * `options` - Value range, placement and handling of non-finite values.
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(Vec<(u32, u32, rael::Color)>)`: One pixel per grid cell (minus skipped
+///   non-finite cells), offset by `options.position`.
+/// - `Err(RaelImgError)`: If `data.len()` does not equal `width * height`.
+///
+/// # Examples
+///
+/// ```
+/// use rael_img::heatmap::{heatmap, Colormap, HeatmapOptions};
+///
+/// let load: Vec<f32> = (0..64).map(|i| (i as f32 / 8.0).sin()).collect();
+/// let pixels = heatmap(&load, 16, 4, &Colormap::Viridis, &HeatmapOptions::default()).unwrap();
+/// assert_eq!(pixels.len(), 64);
+/// ```
+pub fn heatmap(
+    data: &[f32],
+    width: u32,
+    height: u32,
+    colormap: &Colormap,
+    options: &HeatmapOptions,
+) -> Result<Vec<(u32, u32, Color)>, RaelImgError> {
+    if data.len() != width as usize * height as usize {
+        return Err(RaelImgError::InvalidParameters(format!(
+            "expected {} values for a {width}x{height} heatmap, got {}",
+            width as usize * height as usize,
+            data.len()
+        )));
+    }
+
+    let finite = || data.iter().copied().filter(|value| value.is_finite());
+    let min = options
+        .min
+        .unwrap_or_else(|| finite().fold(f32::INFINITY, f32::min));
+    let max = options
+        .max
+        .unwrap_or_else(|| finite().fold(f32::NEG_INFINITY, f32::max));
+    let range = max - min;
+
+    let mut pixels = Vec::with_capacity(data.len());
+    for (index, &value) in data.iter().enumerate() {
+        let x = (index % width as usize) as u32 + options.position.0;
+        let y = (index / width as usize) as u32 + options.position.1;
+        if !value.is_finite() {
+            if let Some(color) = options.missing_color {
+                pixels.push((x, y, color));
+            }
+            continue;
+        }
+        let t = if range > 0.0 {
+            (value - min) / range
+        } else {
+            0.5
+        };
+        pixels.push((x, y, colormap.sample(t)));
+    }
+
+    Ok(pixels)
+}
@@ -0,0 +1,123 @@
+//! Loading images from URLs (feature `http`).
+//!
+//! [`load_image_from_url`] downloads an image and runs it through the same pipeline as a
+//! file. The format is taken from the bytes when they identify it and from the
+//! `Content-Type` header otherwise, so formats without a signature, like TGA, load when the
+//! server names them.
+//!
+//! Downloads go through [`ureq`], so `http://` and `https://` URLs both work and redirects
+//! between them are followed. Applications that already use another HTTP client can
+//! download the bytes with it and pass them to [`load_image_from_download`].
+
+use crate::LoadOptions;
+use crate::decode::decode_as;
+use crate::error::RaelImgError;
+use crate::pipeline::process_bytes_with;
+use image::ImageFormat;
+use rael::Color;
+use std::io;
+use std::time::Duration;
+
+/// How many redirects are followed before giving up.
+const MAX_REDIRECTS: u32 = 5;
+
+/// The largest response body accepted, so a wrong URL cannot exhaust memory.
+const MAX_BODY: u64 = 64 * 1024 * 1024;
+
+/// How long connecting to the server may take.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a whole download may take, redirects included.
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A downloaded file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Download {
+    /// The response body.
+    pub bytes: Vec<u8>,
+    /// The media type from the `Content-Type` header, without parameters.
+    pub content_type: Option<String>,
+}
+
+impl Download {
+    /// Returns the image format the `Content-Type` header names.
+    pub fn format(&self) -> Option<ImageFormat> {
+        ImageFormat::from_mime_type(self.content_type.as_deref()?)
+    }
+}
+
+/// Downloads an image and processes it like [`load_image_with`](crate::load_image_with).
+///
+/// Redirects are followed, also between `http://` and `https://`.
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(Vec<(u32, u32, Color)>)`: A vector of pixel tuples `(x, y, Color)`.
+/// - `Err(RaelImgError)`: If the download failed or the image could not be decoded.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::http::load_image_from_url;
+/// use rael_img::LoadOptions;
+///
+/// let options = LoadOptions {
+///     width: Some(16),
+///     height: Some(16),
+///     ..LoadOptions::default()
+/// };
+/// let avatar = load_image_from_url("https://example.com/avatars/42.png", &options).unwrap();
+/// ```
+pub fn load_image_from_url(
+    url: &str,
+    options: &LoadOptions,
+) -> Result<Vec<(u32, u32, Color)>, RaelImgError> {
+    let download = download(url).map_err(RaelImgError::Network)?;
+    load_image_from_download(&download, options)
+}
+
+/// Processes a downloaded image like [`load_image_from_bytes`](crate::load_image_from_bytes),
+/// using its `Content-Type` when the bytes do not identify the format.
+pub fn load_image_from_download(
+    download: &Download,
+    options: &LoadOptions,
+) -> Result<Vec<(u32, u32, Color)>, RaelImgError> {
+    let fallback = download.format();
+    let decoder = move |bytes: &[u8], policy| decode_as(bytes, fallback, policy);
+    process_bytes_with(&download.bytes, options, &decoder).map(|loaded| loaded.pixels)
+}
+
+/// Downloads a file over HTTP or HTTPS, following redirects.
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(Download)`: The body and content type of the response.
+/// - `Err(io::Error)`: If the request failed or timed out, the server answered with an
+///   error status or the body is larger than 64 MiB.
+pub fn download(url: &str) -> io::Result<Download> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_connect(Some(CONNECT_TIMEOUT))
+        .timeout_global(Some(TIMEOUT))
+        .max_redirects(MAX_REDIRECTS)
+        .user_agent("rael_img")
+        .build()
+        .into();
+    let mut response = agent
+        .get(url)
+        .header("Accept", "image/*")
+        .call()
+        .map_err(ureq::Error::into_io)?;
+    let content_type = response.body().mime_type().map(str::to_ascii_lowercase);
+    let bytes = response
+        .body_mut()
+        .with_config()
+        .limit(MAX_BODY)
+        .read_to_vec()
+        .map_err(ureq::Error::into_io)?;
+    Ok(Download {
+        bytes,
+        content_type,
+    })
+}
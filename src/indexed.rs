@@ -0,0 +1,215 @@
+//! Palette-indexed images and palette-cycling animation.
+//!
+//! An [`IndexedImage`] stores each pixel as an index into a palette of at most 256 colors.
+//! Changing the palette recolors the whole image without touching the pixel data, which
+//! is what classic palette-cycling effects (water, fire, waterfalls) are built on.
+
+use rael::Color;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// An image whose pixels reference a shared palette.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(into = "IndexedData", try_from = "IndexedData")
+)]
+pub struct IndexedImage {
+    pixels: Vec<(u32, u32, u8)>,
+    palette: Vec<Color>,
+}
+
+/// The serialized form of an [`IndexedImage`], checked with [`IndexedImage::new`] when read.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct IndexedData {
+    pixels: Vec<(u32, u32, u8)>,
+    #[serde(with = "crate::serde_color::list")]
+    palette: Vec<Color>,
+}
+
+#[cfg(feature = "serde")]
+impl From<IndexedImage> for IndexedData {
+    fn from(image: IndexedImage) -> Self {
+        Self {
+            pixels: image.pixels,
+            palette: image.palette,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<IndexedData> for IndexedImage {
+    type Error = String;
+
+    fn try_from(data: IndexedData) -> Result<Self, Self::Error> {
+        let colors = data.palette.len();
+        Self::new(data.pixels, data.palette).ok_or_else(|| {
+            format!("an indexed image needs at most 256 colors and indices below {colors}")
+        })
+    }
+}
+
+impl IndexedImage {
+    /// Creates an indexed image from palette indices and a palette.
+    ///
+    /// Returns `None` if the palette has more than 256 entries or any index is out of range.
+    pub fn new(pixels: Vec<(u32, u32, u8)>, palette: Vec<Color>) -> Option<Self> {
+        if palette.len() > 256 {
+            return None;
+        }
+        if pixels
+            .iter()
+            .any(|&(_, _, index)| usize::from(index) >= palette.len())
+        {
+            return None;
+        }
+        Some(Self { pixels, palette })
+    }
+
+    /// Builds an indexed image from a pixel list by assigning one palette entry per distinct
+    /// color, in order of first appearance.
+    ///
+    /// Returns `None` if the pixels use more than 256 distinct colors; such images have to be
+    /// quantized first.
+    pub fn from_pixels(pixels: &[(u32, u32, Color)]) -> Option<Self> {
+        let mut lookup: HashMap<(u8, u8, u8), u8> = HashMap::new();
+        let mut palette = Vec::new();
+        let mut indexed = Vec::with_capacity(pixels.len());
+
+        for &(x, y, color) in pixels {
+            let key = (color.r, color.g, color.b);
+            let index = match lookup.get(&key) {
+                Some(&index) => index,
+                None => {
+                    if palette.len() == 256 {
+                        return None;
+                    }
+                    let index = palette.len() as u8;
+                    palette.push(color);
+                    lookup.insert(key, index);
+                    index
+                }
+            };
+            indexed.push((x, y, index));
+        }
+
+        Some(Self {
+            pixels: indexed,
+            palette,
+        })
+    }
+
+    /// Returns the palette.
+    pub fn palette(&self) -> &[Color] {
+        &self.palette
+    }
+
+    /// Returns the palette for in-place editing.
+    pub fn palette_mut(&mut self) -> &mut [Color] {
+        &mut self.palette
+    }
+
+    /// Returns the pixels as `(x, y, palette_index)` tuples.
+    pub fn indices(&self) -> &[(u32, u32, u8)] {
+        &self.pixels
+    }
+
+    /// Resolves every pixel through the image's own palette.
+    pub fn to_pixels(&self) -> Vec<(u32, u32, Color)> {
+        self.to_pixels_with(&self.palette)
+    }
+
+    /// Resolves every pixel through a different palette of the same length, such as one
+    /// returned by [`cycle_palette`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `palette` is shorter than the image's palette.
+    pub fn to_pixels_with(&self, palette: &[Color]) -> Vec<(u32, u32, Color)> {
+        self.pixels
+            .iter()
+            .map(|&(x, y, index)| (x, y, palette[usize::from(index)]))
+            .collect()
+    }
+
+    /// Renders the image with the given palette cycles applied at `elapsed`.
+    pub fn render_cycled(
+        &self,
+        cycles: &[PaletteCycle],
+        elapsed: Duration,
+    ) -> Vec<(u32, u32, Color)> {
+        self.to_pixels_with(&cycle_palette(&self.palette, cycles, elapsed))
+    }
+}
+
+/// A contiguous palette range that rotates over time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaletteCycle {
+    /// The first palette index of the range.
+    pub start: u8,
+    /// The last palette index of the range (inclusive).
+    pub end: u8,
+    /// How long each rotation step lasts.
+    pub step: Duration,
+    /// If `true`, colors move towards lower indices instead of higher ones.
+    pub reverse: bool,
+}
+
+impl PaletteCycle {
+    /// Creates a forward cycle over `start..=end`, advancing one entry per `step`.
+    pub fn new(start: u8, end: u8, step: Duration) -> Self {
+        Self {
+            start,
+            end,
+            step,
+            reverse: false,
+        }
+    }
+}
+
+/// Returns a copy of `palette` with every cycle rotated to its position at `elapsed`.
+///
+/// Ranges that fall outside the palette are clamped to it; ranges with a zero step or a
+/// single entry are left unchanged. Cycles are applied in order, so overlapping ranges
+/// compose.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::indexed::{IndexedImage, PaletteCycle};
+/// use rael_img::load_image;
+/// use std::time::{Duration, Instant};
+///
+/// let pixels = load_image("./assets/waterfall.png", None, None, (0, 0), false, 1.0).unwrap();
+/// let image = IndexedImage::from_pixels(&pixels).expect("at most 256 colors");
+/// let water = PaletteCycle::new(16, 31, Duration::from_millis(80));
+///
+/// let start = Instant::now();
+/// // In the game loop:
+/// let frame = image.render_cycled(&[water], start.elapsed());
+/// ```
+pub fn cycle_palette(palette: &[Color], cycles: &[PaletteCycle], elapsed: Duration) -> Vec<Color> {
+    let mut result = palette.to_vec();
+    for cycle in cycles {
+        if palette.is_empty() || cycle.step.is_zero() {
+            continue;
+        }
+        let start = usize::from(cycle.start.min(cycle.end));
+        let end = usize::from(cycle.start.max(cycle.end)).min(result.len() - 1);
+        if start >= end {
+            continue;
+        }
+
+        let len = end - start + 1;
+        let steps = (elapsed.as_nanos() / cycle.step.as_nanos() % len as u128) as usize;
+        let range = &mut result[start..=end];
+        if cycle.reverse {
+            range.rotate_left(steps);
+        } else {
+            range.rotate_right(steps);
+        }
+    }
+    result
+}
@@ -2,7 +2,86 @@
 //! It provides a function to load an image from a file, optionally resize it, and convert its pixels
 //! into a format suitable for `rael`'s `Canvas`.
 
-use image::{DynamicImage, GenericImageView};
+#![allow(clippy::doc_overindented_list_items)]
+
+pub mod adaptive;
+pub mod animation;
+pub mod assets;
+pub mod backend;
+pub mod background;
+pub mod baked;
+pub mod batch;
+pub mod braille;
+pub mod buffer;
+pub mod cache;
+pub mod canvas;
+pub mod checkerboard;
+pub mod chroma;
+pub mod compose;
+pub mod console;
+pub mod debug;
+pub mod decode;
+pub mod density;
+pub mod detail;
+pub mod diff;
+pub mod dither;
+pub mod effects;
+pub mod embed;
+pub mod enhance;
+pub mod error;
+pub mod export;
+#[cfg(feature = "async")]
+pub mod future;
+pub mod generate;
+mod grid;
+pub mod halfblock;
+pub mod heatmap;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod indexed;
+pub mod loaded;
+pub mod loader;
+pub mod mosaic;
+pub mod ninepatch;
+pub mod outline;
+pub mod palette;
+mod parallel;
+pub mod pipeline;
+pub mod plot;
+#[cfg(feature = "presets")]
+pub mod preset;
+pub mod protocol;
+pub mod quantize;
+#[cfg(feature = "record")]
+pub mod record;
+pub mod resample;
+pub mod scene;
+#[cfg(feature = "serde")]
+mod serde_color;
+pub mod shadow;
+pub mod size;
+pub mod sprite;
+#[cfg(any(feature = "record", feature = "video"))]
+mod stderr;
+pub mod stream;
+pub mod subpixel;
+pub mod terminal;
+pub mod testing;
+pub mod tile;
+pub mod tint;
+pub mod transform;
+#[cfg(feature = "video")]
+pub mod video;
+#[cfg(feature = "watch")]
+pub mod watch;
+pub mod width;
+
+pub use pipeline::{from_raw_rgb, from_raw_rgba, process_image};
+#[cfg(feature = "macros")]
+pub use rael_img_macros::include_image;
+
+use crate::error::RaelImgError;
+use image::{DynamicImage, GenericImageView, ImageDecoder};
 use rael::Color;
 
 /// Loads an image from the given path, processes it according to the specified parameters,
@@ -10,7 +89,12 @@ use rael::Color;
 /// processed image, ready to be drawn onto a `rael::Canvas`.
 ///
 /// This function handles image loading, optional resizing (with or without stretching),
-/// and provides the pixel data with an applied position offset.
+/// and provides the pixel data with an applied position offset. The image is first turned
+/// upright according to its EXIF orientation; [`load_image_with`] can disable that with
+/// [`LoadOptions::auto_orient`].
+///
+/// Resizing uses the [`Triangle`](resample::ResizeFilter::Triangle) filter, which blurs pixel
+/// art; [`load_image_filtered`] takes the filter as a parameter.
 ///
 /// # Arguments
 ///
@@ -20,8 +104,9 @@ use rael::Color;
 ///             the original image's width (scaled by `scale`) is used.
 /// * `height` - An `Option<u32>` specifying the target height for the image. If `None`,
 ///              the original image's height (scaled by `scale`) is used.
-/// * `position` - A tuple `(u32, u32)` representing the `(x, y)` offset to apply to each
+/// * `position` - A tuple `(i32, i32)` representing the `(x, y)` offset to apply to each
 ///                pixel's coordinates. This effectively positions the image on the canvas.
+///                Pixels that land left of or above the canvas are left out.
 /// * `stretch` - A boolean. If `true` and both `width` and `height` are provided, the image
 ///               will be stretched to exactly match the `target_width` and `target_height`.
 ///               If `false`, the image will be resized while maintaining its aspect ratio,
@@ -35,7 +120,7 @@ use rael::Color;
 /// A `Result` which is:
 /// - `Ok(Vec<(u32, u32, rael::Color)>)`: A vector of tuples, where each tuple contains
 ///   the `(x, y)` coordinate (offset by `position`) and the `rael::Color` of a pixel.
-/// - `Err(image::ImageError)`: If there was an error loading or processing the image.
+/// - `Err(RaelImgError)`: If there was an error loading or processing the image.
 ///
 /// # Examples
 ///
@@ -61,15 +146,28 @@ pub fn load_image(
     path: &str,
     width: Option<u32>,
     height: Option<u32>,
-    position: (u32, u32),
+    position: (i32, i32),
     stretch: bool,
     scale: f32,
-) -> Result<Vec<(u32, u32, Color)>, image::ImageError> {
-    let img = image::open(path)?;
+) -> Result<Vec<(u32, u32, Color)>, RaelImgError> {
+    let options = LoadOptions {
+        width,
+        height,
+        scale,
+        ..LoadOptions::default()
+    };
+    options.validate()?;
+    let mut decoder = image::ImageReader::open(path)?
+        .with_guessed_format()?
+        .into_decoder()?;
+    let orientation = decoder.orientation()?;
+    let mut img = DynamicImage::from_decoder(decoder)?;
+    img.apply_orientation(orientation);
     let (img_width, img_height) = img.dimensions();
 
-    let target_width = width.unwrap_or((img_width as f32 * scale) as u32);
-    let target_height = height.unwrap_or((img_height as f32 * scale) as u32);
+    let scaled = |length: u32| ((length as f32 * scale).round() as u32).max(1);
+    let target_width = width.unwrap_or_else(|| scaled(img_width));
+    let target_height = height.unwrap_or_else(|| scaled(img_height));
 
     let final_img: DynamicImage;
 
@@ -96,8 +194,342 @@ pub fn load_image(
             g: pixel[1],
             b: pixel[2],
         };
-        pixels.push((x + position.0, y + position.1, color));
+        let shift = |value: u32, offset: i32| u32::try_from(i64::from(value) + i64::from(offset));
+        if let (Ok(x), Ok(y)) = (shift(x, position.0), shift(y, position.1)) {
+            pixels.push((x, y, color));
+        }
     }
 
     Ok(pixels)
-}
\ No newline at end of file
+}
+
+/// Loads an image like [`load_image`], resizing it with `filter`.
+///
+/// [`ResizeFilter::Nearest`](resample::ResizeFilter::Nearest) keeps pixel-art sprites
+/// crisp; [`ResizeFilter::CatmullRom`](resample::ResizeFilter::CatmullRom) and
+/// [`ResizeFilter::Lanczos3`](resample::ResizeFilter::Lanczos3) keep photos sharp. For
+/// per-axis filters and the other resampling settings use [`load_image_with`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::load_image_filtered;
+/// use rael_img::resample::ResizeFilter;
+///
+/// let sprite = load_image_filtered(
+///     "./assets/hero.png",
+///     Some(64),
+///     None,
+///     (0, 0),
+///     false,
+///     1.0,
+///     ResizeFilter::Nearest,
+/// )
+/// .unwrap();
+/// ```
+pub fn load_image_filtered(
+    path: &str,
+    width: Option<u32>,
+    height: Option<u32>,
+    position: (i32, i32),
+    stretch: bool,
+    scale: f32,
+    filter: resample::ResizeFilter,
+) -> Result<Vec<(u32, u32, Color)>, RaelImgError> {
+    let options = LoadOptions {
+        width,
+        height,
+        position,
+        stretch,
+        scale,
+        filters: resample::AxisFilters::uniform(filter),
+        ..LoadOptions::default()
+    };
+    load_image_with(path, &options)
+}
+
+/// The parameters of [`load_image`] as one value, so a processing setup can be stored,
+/// shared and reused.
+///
+/// With the `serde` feature this type can be serialized; missing fields take their default
+/// values. See the `preset` module (feature `presets`) for named presets in TOML or JSON.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct LoadOptions {
+    /// The `(x, y, width, height)` region of the source image to keep, cut out before
+    /// resizing so the rest is never resampled. The region is clamped to the image.
+    pub crop: Option<(u32, u32, u32, u32)>,
+    /// Whether to turn the decoded image upright according to the orientation stored in
+    /// its EXIF metadata, as phone cameras do, before it is cropped and resized. Disable
+    /// this to get the pixels as they are stored.
+    pub auto_orient: bool,
+    /// The rotation and mirroring applied after resizing. The target size is that of the
+    /// turned image. See [`transform::Orientation`].
+    pub orientation: transform::Orientation,
+    /// The target width. See [`load_image`].
+    pub width: Option<u32>,
+    /// The target height. See [`load_image`].
+    pub height: Option<u32>,
+    /// The `(x, y)` offset applied to every pixel. Pixels that land left of or above the
+    /// canvas origin are left out, so an image can scroll partly off the top-left edge.
+    pub position: (i32, i32),
+    /// If set, only pixels inside this `(x, y, width, height)` rectangle of the canvas are
+    /// kept, such as `(0, 0, canvas.width, canvas.height * 2)` for a whole
+    /// [`rael::Canvas`].
+    pub clip: Option<(u32, u32, u32, u32)>,
+    /// Whether to stretch to exactly `width` by `height`.
+    pub stretch: bool,
+    /// The scaling factor used for missing dimensions.
+    pub scale: f32,
+    /// How damaged files are treated. See [`decode::DecodePolicy`].
+    pub policy: decode::DecodePolicy,
+    /// The resampling filters. See [`resample::AxisFilters`].
+    pub filters: resample::AxisFilters,
+    /// The supersampling factor from 1 (off) to 4. See [`resample::resize_supersampled`].
+    pub supersample: u32,
+    /// Whether to resample in linear light. See [`resample::Resampling::linear_light`].
+    pub linear_light: bool,
+    /// Blur, sharpening and pixelation of the resized image, applied before its colors are
+    /// converted and adjusted. See [`detail::Detail`].
+    pub detail: detail::Detail,
+    /// Brightness, contrast, saturation and gamma adjustments of the result. See
+    /// [`effects::Adjustments`].
+    pub adjustments: effects::Adjustments,
+    /// How colors are shown, applied after the adjustments. See [`effects::ColorMode`].
+    pub color_mode: effects::ColorMode,
+    /// Whether to tune the result for readability. See [`enhance::optimize_for_terminal`].
+    pub optimize: bool,
+    /// If set, the result is reduced to a terminal palette with dithering. See
+    /// [`dither::dither`].
+    pub dither: Option<dither::DitherOptions>,
+    /// If set, the result is repeated to cover a `(width, height)` region from its
+    /// top-left corner, such as a whole canvas for a background texture. See
+    /// [`tile::TiledBackground`] to scroll the repeated image.
+    pub tile: Option<(u32, u32)>,
+    /// Whether to draw a grid, the bounding box and the origin over the result, to check
+    /// where an image lands. See [`debug::debug_overlay`].
+    pub debug: bool,
+    /// If set, pixels of this color become transparent and are left out, for sprites that
+    /// mark transparency with a key color instead of alpha. See [`chroma::ColorKey`].
+    pub transparent_color: Option<chroma::ColorKey>,
+    /// If set, transparent images get an outline of this color around their opaque part,
+    /// and their fully transparent pixels are left out. See [`outline::Outline`].
+    pub outline: Option<outline::Outline>,
+    /// If set, transparent images cast this shadow beneath their opaque part, outline
+    /// included, and their fully transparent pixels are left out. See [`shadow::Shadow`].
+    pub shadow: Option<shadow::Shadow>,
+    /// If set, transparent images are composited over this pattern instead of showing the
+    /// color of their transparent pixels.
+    pub checkerboard: Option<checkerboard::Checkerboard>,
+    /// If set, semi-transparent pixels are blended over this color, which should match the
+    /// canvas background. Ignored when `checkerboard` is set.
+    #[cfg_attr(feature = "serde", serde(with = "serde_color::option"))]
+    pub background: Option<Color>,
+    /// Pixels with a lower alpha value are left out, so transparent areas of sprites do not
+    /// draw over the canvas. `0` keeps every pixel, `1` leaves out fully transparent ones.
+    /// Ignored when `checkerboard` is set.
+    pub alpha_threshold: u8,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self {
+            crop: None,
+            auto_orient: true,
+            orientation: transform::Orientation::IDENTITY,
+            width: None,
+            height: None,
+            position: (0, 0),
+            clip: None,
+            stretch: false,
+            scale: 1.0,
+            policy: decode::DecodePolicy::Strict,
+            filters: resample::AxisFilters::default(),
+            supersample: 1,
+            linear_light: false,
+            detail: detail::Detail::NONE,
+            adjustments: effects::Adjustments::NONE,
+            color_mode: effects::ColorMode::TrueColor,
+            optimize: false,
+            dither: None,
+            tile: None,
+            debug: false,
+            transparent_color: None,
+            outline: None,
+            shadow: None,
+            checkerboard: None,
+            background: None,
+            alpha_threshold: 0,
+        }
+    }
+}
+
+impl LoadOptions {
+    /// The resampling settings in these options.
+    pub(crate) fn resampling(&self) -> resample::Resampling {
+        resample::Resampling {
+            filters: self.filters,
+            supersample: self.supersample,
+            linear_light: self.linear_light,
+        }
+    }
+
+    /// Checks the sizes in these options.
+    ///
+    /// Every function that loads an image with options checks them first. The functions
+    /// that cannot fail, such as [`pipeline::process_image`], resize images to at least 1 by
+    /// 1 pixel instead.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is:
+    /// - `Ok(())`: If the options can be used.
+    /// - `Err(RaelImgError)`: `RaelImgError::InvalidParameters` with the offending value if
+    ///   `scale` is not a positive finite number or `width` or `height` is `Some(0)`.
+    pub fn validate(&self) -> Result<(), RaelImgError> {
+        if !(self.scale.is_finite() && self.scale > 0.0) {
+            return Err(RaelImgError::InvalidParameters(format!(
+                "the scale must be a positive number, got {}",
+                self.scale
+            )));
+        }
+        for (name, size) in [("width", self.width), ("height", self.height)] {
+            if size == Some(0) {
+                return Err(RaelImgError::InvalidParameters(format!(
+                    "the {name} must be at least 1 pixel, got 0"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The alpha below which pixels are left out: the threshold, and at least 1 with a
+    /// color key, an outline or a shadow so keyed pixels and the space around the outline and
+    /// the shadow are dropped.
+    pub(crate) fn alpha_cutoff(&self) -> u8 {
+        if self.transparent_color.is_some() || self.outline.is_some() || self.shadow.is_some() {
+            self.alpha_threshold.max(1)
+        } else {
+            self.alpha_threshold
+        }
+    }
+}
+
+/// Loads an image like [`load_image`], with the parameters taken from `options`.
+///
+/// Unlike [`load_image`], the decoding policy in `options` applies: with
+/// [`DecodePolicy::Lenient`](decode::DecodePolicy::Lenient) a truncated PNG yields the rows
+/// that decoded instead of an error. The resampling filters can also be chosen per axis, and
+/// downscaling can be supersampled or done in linear light.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::{load_image_with, LoadOptions};
+///
+/// let options = LoadOptions {
+///     width: Some(50),
+///     position: (10, 5),
+///     ..LoadOptions::default()
+/// };
+/// let image_pixels = load_image_with("./assets/my_image.png", &options).unwrap();
+/// ```
+pub fn load_image_with(
+    path: &str,
+    options: &LoadOptions,
+) -> Result<Vec<(u32, u32, Color)>, RaelImgError> {
+    decode::load_with_policy(path, options, options.policy).map(|loaded| loaded.pixels)
+}
+
+/// Loads an image like [`load_image_with`], but returns the alpha value of every pixel so
+/// callers can blend sprites over the canvas themselves.
+///
+/// Pixels with an alpha below [`LoadOptions::alpha_threshold`] are left out. Images without
+/// an alpha channel have an alpha of 255 everywhere. The checkerboard, background, readability
+/// tuning and debug overlay of `options` are not applied.
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(Vec<(u32, u32, rael::Color, u8)>)`: The `(x, y)` coordinate (offset by `position`),
+///   color and alpha of every pixel that was kept.
+/// - `Err(RaelImgError)`: If there was an error loading or processing the image.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::{load_image_rgba, LoadOptions};
+///
+/// let options = LoadOptions {
+///     width: Some(16),
+///     alpha_threshold: 1,
+///     ..LoadOptions::default()
+/// };
+/// for (x, y, color, alpha) in load_image_rgba("./assets/sprite.png", &options).unwrap() {
+///     // Blend `color` over the canvas pixel at (x, y) by `alpha`.
+/// }
+/// ```
+pub fn load_image_rgba(
+    path: &str,
+    options: &LoadOptions,
+) -> Result<Vec<(u32, u32, Color, u8)>, RaelImgError> {
+    let decoded =
+        pipeline::decode_using(&std::fs::read(path)?, options, &decode::file_decoder(path))?;
+    Ok(pipeline::rgba_pixels(
+        &pipeline::resized(&decoded, options),
+        options,
+    ))
+}
+
+/// Loads an image from encoded bytes, such as an asset embedded with `include_bytes!`,
+/// processing it like [`load_image_with`].
+///
+/// The format is detected from the content.
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(Vec<(u32, u32, rael::Color)>)`: The processed pixels, offset by `position`.
+/// - `Err(RaelImgError)`: If the bytes are not an image in a supported format.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::{load_image_from_bytes, LoadOptions};
+///
+/// # macro_rules! include_bytes { ($path:expr) => { &[] } }
+/// static LOGO: &[u8] = include_bytes!("../assets/logo.png");
+///
+/// let options = LoadOptions {
+///     width: Some(40),
+///     ..LoadOptions::default()
+/// };
+/// let pixels = load_image_from_bytes(LOGO, &options).unwrap();
+/// ```
+pub fn load_image_from_bytes(
+    bytes: &[u8],
+    options: &LoadOptions,
+) -> Result<Vec<(u32, u32, Color)>, RaelImgError> {
+    pipeline::process_bytes(bytes, options).map(|loaded| loaded.pixels)
+}
+
+/// Loads an image from a reader, such as a network stream or an archive entry, processing it
+/// like [`load_image_with`].
+///
+/// The whole input is read before decoding starts.
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(Vec<(u32, u32, rael::Color)>)`: The processed pixels, offset by `position`.
+/// - `Err(RaelImgError)`: If reading failed or the data is not an image in a supported
+///   format.
+pub fn load_image_from_reader(
+    mut reader: impl std::io::Read,
+    options: &LoadOptions,
+) -> Result<Vec<(u32, u32, Color)>, RaelImgError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    load_image_from_bytes(&bytes, options)
+}
@@ -0,0 +1,427 @@
+//! Loaded images that keep their size and origin.
+//!
+//! The loading functions return bare pixel lists, which lose how large the image is and
+//! where it came from. [`LoadedImage`] keeps both next to the pixels. It derefs to the
+//! pixel slice, so it works wherever a pixel list is expected, and
+//! [`into_pixels`](LoadedImage::into_pixels) returns the plain list.
+//!
+//! [`load_images`] and [`load_dir`] load many images with the same options at once, such
+//! as the sprites of a game at startup.
+
+use crate::canvas::DEFAULT_LAYER;
+use crate::decode::{DecodeWarning, PartialImage, file_decoder};
+use crate::error::RaelImgError;
+use crate::{LoadOptions, parallel, pipeline};
+use image::{DynamicImage, GenericImageView, ImageFormat, Rgba, RgbaImage};
+use rael::Color;
+use std::collections::BTreeMap;
+use std::ops::Deref;
+use std::path::Path;
+
+/// A processed image with its dimensions and metadata.
+///
+/// With the `serde` feature this type can be serialized, so that processed images can be
+/// cached on disk and loaded without decoding and resizing them again.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::loaded::LoadedImage;
+/// use rael_img::LoadOptions;
+///
+/// let options = LoadOptions {
+///     width: Some(40),
+///     ..LoadOptions::default()
+/// };
+/// let logo = LoadedImage::load("./assets/logo.png", &options).unwrap();
+/// println!("{}x{} pixels from a {:?} file", logo.width(), logo.height(), logo.format());
+///
+/// // Center it on an 80 pixel wide canvas.
+/// let offset = (80 - i64::from(logo.width())) / 2;
+/// let logo = logo.translate(offset, 0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LoadedImage {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_color::pixels"))]
+    pixels: Vec<(u32, u32, Color)>,
+    width: u32,
+    height: u32,
+    position: (u32, u32),
+    source_size: Option<(u32, u32)>,
+    path: Option<String>,
+    format: Option<ImageFormat>,
+    warning: Option<DecodeWarning>,
+    layer: usize,
+}
+
+impl LoadedImage {
+    /// Loads an image like [`load_image_with`](crate::load_image_with) and keeps its
+    /// metadata.
+    ///
+    /// The decoding policy of `options` applies; an incomplete image carries a
+    /// [`warning`](LoadedImage::warning).
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is:
+    /// - `Ok(LoadedImage)`: The processed image.
+    /// - `Err(RaelImgError)`: If there was an error loading or processing the image.
+    pub fn load(path: &str, options: &LoadOptions) -> Result<Self, RaelImgError> {
+        let bytes = std::fs::read(path)?;
+        let decoded = pipeline::decode_using(&bytes, options, &file_decoder(path))?;
+        let mut image = Self::decoded(decoded, options);
+        image.path = Some(path.to_string());
+        image.format = image::guess_format(&bytes)
+            .ok()
+            .or_else(|| ImageFormat::from_path(path).ok());
+        Ok(image)
+    }
+
+    /// Loads an image from encoded bytes like
+    /// [`load_image_from_bytes`](crate::load_image_from_bytes) and keeps its metadata.
+    pub fn from_bytes(bytes: &[u8], options: &LoadOptions) -> Result<Self, RaelImgError> {
+        let mut image = Self::decoded(pipeline::decode(bytes, options)?, options);
+        image.format = image::guess_format(bytes).ok();
+        Ok(image)
+    }
+
+    fn decoded(decoded: PartialImage, options: &LoadOptions) -> Self {
+        let mut image = Self::processed(
+            &pipeline::resized(&decoded, options),
+            options,
+            decoded.image.dimensions(),
+        );
+        image.warning = decoded.warning;
+        image
+    }
+
+    /// Processes a decoded image like [`process_image`](pipeline::process_image) and keeps
+    /// its size.
+    pub fn from_image(image: &DynamicImage, options: &LoadOptions) -> Self {
+        Self::processed(
+            &pipeline::resized_image(image, options),
+            options,
+            image.dimensions(),
+        )
+    }
+
+    fn processed(resized: &DynamicImage, options: &LoadOptions, source_size: (u32, u32)) -> Self {
+        Self {
+            source_size: Some(source_size),
+            ..pipeline::finish_loaded(resized, options)
+        }
+    }
+
+    /// Wraps a pixel list that covers part of a `width` by `height` box at `position`.
+    pub(crate) fn with_bounds(
+        pixels: Vec<(u32, u32, Color)>,
+        width: u32,
+        height: u32,
+        position: (u32, u32),
+    ) -> Self {
+        Self {
+            width,
+            height,
+            position,
+            ..Self::from_pixels(pixels)
+        }
+    }
+
+    /// Wraps a pixel list. The size and position are those of its bounding box.
+    pub fn from_pixels(pixels: Vec<(u32, u32, Color)>) -> Self {
+        let (position, width, height) = match crate::grid::bounds(&pixels) {
+            Some((min_x, min_y, max_x, max_y)) => {
+                ((min_x, min_y), max_x - min_x + 1, max_y - min_y + 1)
+            }
+            None => ((0, 0), 0, 0),
+        };
+        Self {
+            pixels,
+            width,
+            height,
+            position,
+            source_size: None,
+            path: None,
+            format: None,
+            warning: None,
+            layer: DEFAULT_LAYER,
+        }
+    }
+
+    /// Returns the width of the processed image in pixels, including its outline and shadow.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of the processed image in pixels, including its outline and shadow.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the canvas coordinate of the image's top-left corner. An outline, or a shadow
+    /// cast up or left, moves it up and left by the room it takes. A position left of or
+    /// above the canvas is reported as 0.
+    pub fn position(&self) -> (u32, u32) {
+        self.position
+    }
+
+    /// Returns the size of the image before resizing, unless it was made from pixels.
+    pub fn source_size(&self) -> Option<(u32, u32)> {
+        self.source_size
+    }
+
+    /// Returns the path the image was loaded from.
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// Returns the format of the encoded image.
+    pub fn format(&self) -> Option<ImageFormat> {
+        self.format
+    }
+
+    /// Returns what was missing if the image is incomplete.
+    pub fn warning(&self) -> Option<&DecodeWarning> {
+        self.warning.as_ref()
+    }
+
+    /// Returns the canvas layer the image is drawn on by
+    /// [`draw_loaded`](crate::canvas::CanvasImageExt::draw_loaded), [`DEFAULT_LAYER`] unless
+    /// set with [`with_layer`](LoadedImage::with_layer).
+    pub fn layer(&self) -> usize {
+        self.layer
+    }
+
+    /// Sets the canvas layer the image is drawn on, such as 0 for a background and 2 for
+    /// sprites in front of it.
+    pub fn with_layer(mut self, layer: usize) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    /// Returns the pixels, offset by the position.
+    pub fn pixels(&self) -> &[(u32, u32, Color)] {
+        &self.pixels
+    }
+
+    /// Iterates over the pixels.
+    pub fn iter_pixels(&self) -> impl Iterator<Item = (u32, u32, Color)> + '_ {
+        self.pixels.iter().copied()
+    }
+
+    /// Moves the image by `(dx, dy)` pixels.
+    ///
+    /// Canvas coordinates cannot be negative: pixels moved past the left or top edge are
+    /// dropped, and the position stops at 0.
+    pub fn translate(mut self, dx: i64, dy: i64) -> Self {
+        let shift = |value: u32, delta: i64| u32::try_from(i64::from(value) + delta).ok();
+        self.pixels = self
+            .pixels
+            .into_iter()
+            .filter_map(|(x, y, color)| Some((shift(x, dx)?, shift(y, dy)?, color)))
+            .collect();
+        self.position = (
+            shift(self.position.0, dx).unwrap_or(0),
+            shift(self.position.1, dy).unwrap_or(0),
+        );
+        self
+    }
+
+    /// Draws the pixels into an image of the same size, with the top-left corner at
+    /// [`position`](LoadedImage::position). Places without a pixel are transparent.
+    pub fn to_rgba_image(&self) -> RgbaImage {
+        let mut image = RgbaImage::new(self.width, self.height);
+        let (left, top) = self.position;
+        for &(x, y, color) in &self.pixels {
+            if let (Some(x), Some(y)) = (x.checked_sub(left), y.checked_sub(top))
+                && x < self.width
+                && y < self.height
+            {
+                image.put_pixel(x, y, Rgba([color.r, color.g, color.b, 255]));
+            }
+        }
+        image
+    }
+
+    /// Writes the pixels to an image file, to check what dithering or quantization did or
+    /// to keep a preview of what the terminal shows.
+    ///
+    /// Places without a pixel are transparent, or black in formats without alpha such as
+    /// JPEG.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to write.
+    /// * `format` - The format to encode, such as `ImageFormat::Png`, `ImageFormat::Jpeg`
+    ///              or `ImageFormat::WebP`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is:
+    /// - `Ok(())`: If the file was written.
+    /// - `Err(RaelImgError)`: If the format cannot be encoded or the file could not be
+    ///   written.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use image::ImageFormat;
+    /// use rael_img::dither::{DitherOptions, TerminalPalette};
+    /// use rael_img::loaded::LoadedImage;
+    /// use rael_img::LoadOptions;
+    ///
+    /// let options = LoadOptions {
+    ///     width: Some(80),
+    ///     dither: Some(DitherOptions {
+    ///         palette: TerminalPalette::Ansi16,
+    ///         ..DitherOptions::default()
+    ///     }),
+    ///     ..LoadOptions::default()
+    /// };
+    /// let preview = LoadedImage::load("./assets/photo.jpg", &options).unwrap();
+    /// preview.save("./preview.png", ImageFormat::Png).unwrap();
+    /// ```
+    pub fn save(&self, path: impl AsRef<Path>, format: ImageFormat) -> Result<(), RaelImgError> {
+        let image = DynamicImage::ImageRgba8(self.to_rgba_image());
+        let image = match format {
+            ImageFormat::Jpeg | ImageFormat::Pnm | ImageFormat::Farbfeld => {
+                DynamicImage::ImageRgb8(image.to_rgb8())
+            }
+            _ => image,
+        };
+        image.save_with_format(path, format)?;
+        Ok(())
+    }
+
+    /// Returns the plain pixel list, as the loading functions do.
+    pub fn into_pixels(self) -> Vec<(u32, u32, Color)> {
+        self.pixels
+    }
+}
+
+impl Deref for LoadedImage {
+    type Target = [(u32, u32, Color)];
+
+    fn deref(&self) -> &Self::Target {
+        &self.pixels
+    }
+}
+
+impl AsRef<[(u32, u32, Color)]> for LoadedImage {
+    fn as_ref(&self) -> &[(u32, u32, Color)] {
+        &self.pixels
+    }
+}
+
+impl From<&DynamicImage> for LoadedImage {
+    /// Converts a decoded image at its own size, like [`LoadedImage::from_image`] with the
+    /// default options.
+    fn from(image: &DynamicImage) -> Self {
+        Self::from_image(image, &LoadOptions::default())
+    }
+}
+
+impl From<DynamicImage> for LoadedImage {
+    fn from(image: DynamicImage) -> Self {
+        Self::from(&image)
+    }
+}
+
+impl From<LoadedImage> for Vec<(u32, u32, Color)> {
+    fn from(image: LoadedImage) -> Self {
+        image.pixels
+    }
+}
+
+impl IntoIterator for LoadedImage {
+    type Item = (u32, u32, Color);
+    type IntoIter = std::vec::IntoIter<(u32, u32, Color)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.pixels.into_iter()
+    }
+}
+
+/// Loads several images with the same options, in parallel with the `rayon` feature.
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(Vec<LoadedImage>)`: The images, in the order of `paths`.
+/// - `Err(RaelImgError)`: The error of the first path, in the order of `paths`, that could
+///   not be loaded.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::loaded::load_images;
+/// use rael_img::LoadOptions;
+///
+/// let options = LoadOptions {
+///     width: Some(16),
+///     height: Some(16),
+///     ..LoadOptions::default()
+/// };
+/// let sprites = load_images(&["player.png", "enemy.png", "coin.png"], &options).unwrap();
+/// let player = &sprites[0];
+/// ```
+pub fn load_images(
+    paths: &[&str],
+    options: &LoadOptions,
+) -> Result<Vec<LoadedImage>, RaelImgError> {
+    parallel::map(paths, |path| LoadedImage::load(path, options))
+        .into_iter()
+        .collect()
+}
+
+/// Loads every image in a directory with the same options, keyed by file stem, so
+/// `sprites/player.png` becomes `"player"`.
+///
+/// Files whose extension is not that of an image format are skipped, and so are
+/// subdirectories. The images are loaded in parallel with the `rayon` feature.
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(BTreeMap<String, LoadedImage>)`: The images by name.
+/// - `Err(RaelImgError)`: If the directory could not be read, two images share a stem
+///   (`InvalidParameters`), or an image could not be loaded.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::loaded::load_dir;
+/// use rael_img::LoadOptions;
+///
+/// let sprites = load_dir("./assets/sprites", &LoadOptions::default()).unwrap();
+/// let player = &sprites["player"];
+/// ```
+pub fn load_dir(
+    dir: impl AsRef<Path>,
+    options: &LoadOptions,
+) -> Result<BTreeMap<String, LoadedImage>, RaelImgError> {
+    let mut files = BTreeMap::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_file() || ImageFormat::from_path(&path).is_err() {
+            continue;
+        }
+        let Some(stem) = path.file_stem() else {
+            continue;
+        };
+        let stem = stem.to_string_lossy().into_owned();
+        let path = path.to_string_lossy().into_owned();
+        if let Some(other) = files.insert(stem.clone(), path.clone()) {
+            return Err(RaelImgError::InvalidParameters(format!(
+                "`{other}` and `{path}` are both named `{stem}`"
+            )));
+        }
+    }
+    let (names, paths): (Vec<String>, Vec<String>) = files.into_iter().unzip();
+    let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+    Ok(names
+        .into_iter()
+        .zip(load_images(&paths, options)?)
+        .collect())
+}
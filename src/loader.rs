@@ -0,0 +1,398 @@
+//! A builder for loading images.
+//!
+//! [`ImageLoader`] sets the fields of [`LoadOptions`] one call at a time, so loads read
+//! like a sentence and new options can be added without breaking existing calls:
+//!
+//! ```no_run
+//! use rael_img::loader::ImageLoader;
+//!
+//! let pixels = ImageLoader::new("./assets/my_image.png")
+//!     .width(50)
+//!     .position(10, 5)
+//!     .stretch(false)
+//!     .scale(1.0)
+//!     .load()
+//!     .unwrap();
+//! ```
+
+use crate::animation::Animation;
+use crate::cache::ImageCache;
+use crate::canvas::DEFAULT_LAYER;
+use crate::checkerboard::Checkerboard;
+use crate::chroma::ColorKey;
+use crate::decode::{DecodePolicy, PartialPixels, load_with_policy};
+use crate::detail::Detail;
+use crate::dither::DitherOptions;
+use crate::effects::{Adjustments, ColorMode};
+use crate::error::RaelImgError;
+use crate::loaded::LoadedImage;
+use crate::outline::Outline;
+use crate::resample::{AxisFilters, ResizeFilter};
+use crate::shadow::Shadow;
+use crate::size::Size;
+use crate::transform::{Orientation, Rotation};
+use crate::{LoadOptions, load_image_rgba, load_image_with};
+use rael::{Canvas, Color};
+
+/// Loads an image with options set by builder methods.
+///
+/// Every option starts at its [`LoadOptions::default`] value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageLoader {
+    path: String,
+    options: LoadOptions,
+    layer: usize,
+}
+
+impl ImageLoader {
+    /// Starts a load of the image at `path`.
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            options: LoadOptions::default(),
+            layer: DEFAULT_LAYER,
+        }
+    }
+
+    /// Replaces all options at once, for example with a preset. Later builder calls change
+    /// individual fields.
+    pub fn options(mut self, options: LoadOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Keeps only the `width` by `height` region at `(x, y)` of the source image.
+    pub fn crop(mut self, x: u32, y: u32, width: u32, height: u32) -> Self {
+        self.options.crop = Some((x, y, width, height));
+        self
+    }
+
+    /// Sets whether the image is turned upright according to its EXIF orientation.
+    pub fn auto_orient(mut self, auto_orient: bool) -> Self {
+        self.options.auto_orient = auto_orient;
+        self
+    }
+
+    /// Sets the rotation and mirroring.
+    pub fn orientation(mut self, orientation: Orientation) -> Self {
+        self.options.orientation = orientation;
+        self
+    }
+
+    /// Rotates the image a quarter turn clockwise.
+    pub fn rotate90(mut self) -> Self {
+        self.options.orientation.rotation = Rotation::Rotate90;
+        self
+    }
+
+    /// Rotates the image a half turn.
+    pub fn rotate180(mut self) -> Self {
+        self.options.orientation.rotation = Rotation::Rotate180;
+        self
+    }
+
+    /// Rotates the image a quarter turn counterclockwise.
+    pub fn rotate270(mut self) -> Self {
+        self.options.orientation.rotation = Rotation::Rotate270;
+        self
+    }
+
+    /// Mirrors the image left to right.
+    pub fn flip_horizontal(mut self) -> Self {
+        self.options.orientation.flip_horizontal = true;
+        self
+    }
+
+    /// Mirrors the image top to bottom.
+    pub fn flip_vertical(mut self) -> Self {
+        self.options.orientation.flip_vertical = true;
+        self
+    }
+
+    /// Sets the target width.
+    pub fn width(mut self, width: u32) -> Self {
+        self.options.width = Some(width);
+        self
+    }
+
+    /// Sets the target height.
+    pub fn height(mut self, height: u32) -> Self {
+        self.options.height = Some(height);
+        self
+    }
+
+    /// Sets the target width and height relative to the terminal, resolved against its
+    /// current size, like [`LoadOptions::with_size`].
+    pub fn size(mut self, width: Size, height: Size) -> Self {
+        self.options = self.options.with_size(width, height);
+        self
+    }
+
+    /// Sets the `(x, y)` offset applied to every pixel. Negative offsets move the image
+    /// partly off the left or top edge.
+    pub fn position(mut self, x: i32, y: i32) -> Self {
+        self.options.position = (x, y);
+        self
+    }
+
+    /// Keeps only the pixels inside the `(x, y, width, height)` rectangle of the canvas.
+    pub fn clip(mut self, x: u32, y: u32, width: u32, height: u32) -> Self {
+        self.options.clip = Some((x, y, width, height));
+        self
+    }
+
+    /// Keeps only the pixels that fall on `canvas`.
+    pub fn clip_to_canvas(self, canvas: &Canvas) -> Self {
+        let size = |value: usize| u32::try_from(value).unwrap_or(u32::MAX);
+        self.clip(
+            0,
+            0,
+            size(canvas.width),
+            size(canvas.height).saturating_mul(2),
+        )
+    }
+
+    /// If `true` and both width and height are set, the image is stretched to exactly that
+    /// size instead of keeping its aspect ratio.
+    pub fn stretch(mut self, stretch: bool) -> Self {
+        self.options.stretch = stretch;
+        self
+    }
+
+    /// Sets the scaling factor used for dimensions that are not set.
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.options.scale = scale;
+        self
+    }
+
+    /// Sets how damaged files are treated.
+    pub fn policy(mut self, policy: DecodePolicy) -> Self {
+        self.options.policy = policy;
+        self
+    }
+
+    /// Uses `filter` for resampling along both axes.
+    pub fn filter(mut self, filter: ResizeFilter) -> Self {
+        self.options.filters = AxisFilters::uniform(filter);
+        self
+    }
+
+    /// Sets the resampling filter of each axis.
+    pub fn filters(mut self, filters: AxisFilters) -> Self {
+        self.options.filters = filters;
+        self
+    }
+
+    /// Sets the supersampling factor, from 1 (off) to 4.
+    pub fn supersample(mut self, factor: u32) -> Self {
+        self.options.supersample = factor;
+        self
+    }
+
+    /// Sets whether to resample in linear light.
+    pub fn linear_light(mut self, linear_light: bool) -> Self {
+        self.options.linear_light = linear_light;
+        self
+    }
+
+    /// Sets the blur, sharpening and pixelation at once.
+    pub fn detail(mut self, detail: Detail) -> Self {
+        self.options.detail = detail;
+        self
+    }
+
+    /// Blurs the image with a Gaussian of this standard deviation, in pixels.
+    pub fn blur(mut self, sigma: f32) -> Self {
+        self.options.detail.blur = sigma;
+        self
+    }
+
+    /// Sharpens the image with an unsharp mask of this strength; `0.0` does not sharpen.
+    pub fn sharpen(mut self, amount: f32) -> Self {
+        self.options.detail.sharpen = amount;
+        self
+    }
+
+    /// Fills blocks of `size` by `size` pixels with their average color.
+    pub fn pixelate(mut self, size: u32) -> Self {
+        self.options.detail.pixelate = size;
+        self
+    }
+
+    /// Sets all color adjustments at once.
+    pub fn adjustments(mut self, adjustments: Adjustments) -> Self {
+        self.options.adjustments = adjustments;
+        self
+    }
+
+    /// Adds `amount` to every channel, from `-1.0` to `1.0`.
+    pub fn brightness(mut self, amount: f32) -> Self {
+        self.options.adjustments.brightness = amount;
+        self
+    }
+
+    /// Sets the contrast factor; `1.0` keeps the contrast.
+    pub fn contrast(mut self, factor: f32) -> Self {
+        self.options.adjustments.contrast = factor;
+        self
+    }
+
+    /// Sets the saturation factor; `0.0` gives grays, `1.0` keeps the colors.
+    pub fn saturation(mut self, factor: f32) -> Self {
+        self.options.adjustments.saturation = factor;
+        self
+    }
+
+    /// Sets the gamma; values above `1.0` brighten the midtones.
+    pub fn gamma(mut self, gamma: f32) -> Self {
+        self.options.adjustments.gamma = gamma;
+        self
+    }
+
+    /// Sets how colors are shown.
+    pub fn color_mode(mut self, mode: ColorMode) -> Self {
+        self.options.color_mode = mode;
+        self
+    }
+
+    /// Sets whether to tune the result for readability at terminal resolution.
+    pub fn optimize(mut self, optimize: bool) -> Self {
+        self.options.optimize = optimize;
+        self
+    }
+
+    /// Reduces the result to a terminal palette with dithering.
+    pub fn dither(mut self, dither: DitherOptions) -> Self {
+        self.options.dither = Some(dither);
+        self
+    }
+
+    /// Repeats the result to cover a `width` by `height` region.
+    pub fn tile(mut self, width: u32, height: u32) -> Self {
+        self.options.tile = Some((width, height));
+        self
+    }
+
+    /// Sets whether to draw the debug overlay over the result.
+    pub fn debug(mut self, debug: bool) -> Self {
+        self.options.debug = debug;
+        self
+    }
+
+    /// Blends semi-transparent pixels over `background`.
+    pub fn background(mut self, background: Color) -> Self {
+        self.options.background = Some(background);
+        self
+    }
+
+    /// Composites transparent images over `checkerboard`.
+    pub fn checkerboard(mut self, checkerboard: Checkerboard) -> Self {
+        self.options.checkerboard = Some(checkerboard);
+        self
+    }
+
+    /// Leaves out pixels with an alpha below `threshold`.
+    pub fn alpha_threshold(mut self, threshold: u8) -> Self {
+        self.options.alpha_threshold = threshold;
+        self
+    }
+
+    /// Makes pixels within `tolerance` of `color` in every channel transparent and leaves
+    /// them out.
+    pub fn transparent_color(mut self, color: Color, tolerance: u8) -> Self {
+        self.options.transparent_color = Some(ColorKey::new(color, tolerance));
+        self
+    }
+
+    /// Draws a 1-pixel outline in `color` around the opaque part of transparent images.
+    pub fn outline(mut self, color: Color) -> Self {
+        self.options.outline = Some(Outline::new(color));
+        self
+    }
+
+    /// Casts a shadow of the opaque part of transparent images, `offset` pixels away, in
+    /// `color` with the given opacity.
+    pub fn shadow(mut self, offset: (i32, i32), color: Color, opacity: f32) -> Self {
+        self.options.shadow = Some(Shadow::new(offset, color, opacity));
+        self
+    }
+
+    /// Sets the canvas layer of the images loaded with
+    /// [`load_image`](ImageLoader::load_image). See [`LoadedImage::layer`].
+    pub fn layer(mut self, layer: usize) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    /// Returns the options set so far.
+    pub fn load_options(&self) -> &LoadOptions {
+        &self.options
+    }
+
+    /// Loads the image, like [`load_image_with`].
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is:
+    /// - `Ok(Vec<(u32, u32, rael::Color)>)`: The processed pixels, offset by the position.
+    /// - `Err(RaelImgError)`: If there was an error loading or processing the image.
+    pub fn load(&self) -> Result<Vec<(u32, u32, Color)>, RaelImgError> {
+        load_image_with(&self.path, &self.options)
+    }
+
+    /// Loads the image on a worker thread, like
+    /// [`load_image_async`](crate::future::load_image_async).
+    #[cfg(feature = "async")]
+    pub fn load_async(&self) -> crate::future::LoadFuture<Vec<(u32, u32, Color)>> {
+        crate::future::load_image_async(self.path.clone(), &self.options)
+    }
+
+    /// Loads the image as an iterator over its pixels, like
+    /// [`load_image_iter`](crate::stream::load_image_iter).
+    pub fn load_iter(&self) -> Result<crate::stream::PixelStream<'static>, RaelImgError> {
+        crate::stream::load_image_iter(&self.path, &self.options)
+    }
+
+    /// Loads the image with its size and metadata, like [`LoadedImage::load`].
+    pub fn load_image(&self) -> Result<LoadedImage, RaelImgError> {
+        LoadedImage::load(&self.path, &self.options).map(|image| image.with_layer(self.layer))
+    }
+
+    /// Loads the image on a background thread and again whenever the file changes, like
+    /// [`ImageWatcher::new`](crate::watch::ImageWatcher::new).
+    #[cfg(feature = "watch")]
+    pub fn watch(&self) -> crate::watch::ImageWatcher {
+        crate::watch::ImageWatcher::new(self.path.clone(), &self.options)
+    }
+
+    /// Loads the image with its size and metadata through `cache`, like
+    /// [`ImageCache::load`].
+    pub fn load_cached<'a>(
+        &self,
+        cache: &'a mut ImageCache,
+    ) -> Result<&'a LoadedImage, RaelImgError> {
+        cache.load(&self.path, &self.options)
+    }
+
+    /// Loads the image with the alpha value of every pixel, like [`load_image_rgba`].
+    pub fn load_rgba(&self) -> Result<Vec<(u32, u32, Color, u8)>, RaelImgError> {
+        load_image_rgba(&self.path, &self.options)
+    }
+
+    /// Loads every frame of an animation, like [`Animation::load`].
+    pub fn load_animation(&self) -> Result<Animation, RaelImgError> {
+        Animation::load(&self.path, &self.options)
+    }
+
+    /// Opens the file as a video, like [`Video::open`](crate::video::Video::open).
+    #[cfg(feature = "video")]
+    pub fn load_video(&self) -> Result<crate::video::Video, RaelImgError> {
+        crate::video::Video::open(&self.path, &self.options)
+    }
+
+    /// Loads the image under the decoding policy that is set, with a warning if it is
+    /// incomplete, like [`load_image_partial`](crate::decode::load_image_partial) does.
+    pub fn load_partial(&self) -> Result<PartialPixels, RaelImgError> {
+        load_with_policy(&self.path, &self.options, self.options.policy)
+    }
+}
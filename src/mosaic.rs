@@ -0,0 +1,420 @@
+//! Per-cell glyph selection for character-based rendering.
+//!
+//! Instead of showing one or two pixels per terminal cell, the mosaic renderer looks at a
+//! small block of source pixels for every cell and picks the glyph and foreground/background
+//! colors that reproduce that block with the least error. Block, quadrant, sextant and
+//! braille glyphs can be combined; the more glyphs are allowed, the more detail survives.
+
+use crate::grid::PixelGrid;
+use crate::quantize::{XTERM_256, nearest_index};
+use crate::width::{AmbiguousWidth, is_single_cell};
+use rael::Color;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// One terminal cell produced by a character-based renderer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlyphCell {
+    /// The terminal column.
+    pub column: u32,
+    /// The terminal row.
+    pub row: u32,
+    /// The character to print.
+    pub glyph: char,
+    /// The foreground (ink) color.
+    pub fg: Color,
+    /// The background color.
+    pub bg: Color,
+}
+
+/// The families of glyphs the mosaic renderer may choose from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GlyphSet {
+    /// Space, full block and the four half blocks (`▀ ▄ ▌ ▐`).
+    pub blocks: bool,
+    /// The sixteen 2x2 quadrant glyphs (`▘ ▚ ▙ …`).
+    pub quadrants: bool,
+    /// The 2x3 sextant glyphs from Unicode 13 "Symbols for Legacy Computing".
+    pub sextants: bool,
+    /// The 2x4 braille patterns.
+    pub braille: bool,
+}
+
+impl GlyphSet {
+    /// Half blocks only; works with virtually every font.
+    pub const BLOCKS: GlyphSet = GlyphSet {
+        blocks: true,
+        quadrants: false,
+        sextants: false,
+        braille: false,
+    };
+
+    /// Blocks and quadrants, supported by most modern monospace fonts.
+    pub const QUADRANTS: GlyphSet = GlyphSet {
+        blocks: true,
+        quadrants: true,
+        sextants: false,
+        braille: false,
+    };
+
+    /// Every supported glyph family.
+    pub const ALL: GlyphSet = GlyphSet {
+        blocks: true,
+        quadrants: true,
+        sextants: true,
+        braille: true,
+    };
+}
+
+impl Default for GlyphSet {
+    fn default() -> Self {
+        Self::QUADRANTS
+    }
+}
+
+/// Options for [`render_mosaic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct MosaicOptions {
+    /// The glyph families to choose from.
+    pub glyphs: GlyphSet,
+    /// How many source pixels map onto one cell, as `(width, height)`. The source image
+    /// should be `columns * width` by `rows * height` pixels. `(2, 4)` resolves every glyph
+    /// family exactly except sextants, which need `(2, 3)` or `(2, 6)`.
+    pub cell_pixels: (u32, u32),
+    /// The `(column, row)` offset applied to every cell.
+    pub position: (u32, u32),
+    /// How the terminal draws ambiguous-width glyphs. With [`AmbiguousWidth::Wide`], glyphs
+    /// that would cover two cells are never chosen, so the image is not sheared.
+    pub ambiguous_width: AmbiguousWidth,
+}
+
+impl Default for MosaicOptions {
+    fn default() -> Self {
+        Self {
+            glyphs: GlyphSet::default(),
+            cell_pixels: (2, 4),
+            position: (0, 0),
+            ambiguous_width: AmbiguousWidth::Narrow,
+        }
+    }
+}
+
+/// A glyph described as a grid of set (foreground) and unset (background) sub-cells.
+#[derive(Debug, Clone, Copy)]
+struct Glyph {
+    ch: char,
+    columns: u32,
+    rows: u32,
+    bits: u8,
+}
+
+impl Glyph {
+    fn is_set(&self, column: u32, row: u32) -> bool {
+        self.bits & (1 << (row * self.columns + column)) != 0
+    }
+}
+
+const QUADRANTS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+
+/// Braille dot bits, indexed by `row * 2 + column`.
+const BRAILLE_DOTS: [u8; 8] = [0x01, 0x08, 0x02, 0x10, 0x04, 0x20, 0x40, 0x80];
+
+fn glyphs(set: GlyphSet) -> Vec<Glyph> {
+    let mut glyphs = Vec::new();
+    if set.blocks {
+        // Bits are row-major over a 2x2 grid, so the halves are simple combinations.
+        for (ch, bits) in [
+            (' ', 0),
+            ('█', 15),
+            ('▀', 3),
+            ('▄', 12),
+            ('▌', 5),
+            ('▐', 10),
+        ] {
+            glyphs.push(Glyph {
+                ch,
+                columns: 2,
+                rows: 2,
+                bits,
+            });
+        }
+    }
+    if set.quadrants {
+        for (bits, &ch) in QUADRANTS.iter().enumerate() {
+            glyphs.push(Glyph {
+                ch,
+                columns: 2,
+                rows: 2,
+                bits: bits as u8,
+            });
+        }
+    }
+    if set.sextants {
+        for bits in 1..63u32 {
+            // Patterns that equal a half block have no sextant code point.
+            if bits == 21 || bits == 42 {
+                continue;
+            }
+            let offset = bits - 1 - u32::from(bits > 21) - u32::from(bits > 42);
+            if let Some(ch) = char::from_u32(0x1FB00 + offset) {
+                glyphs.push(Glyph {
+                    ch,
+                    columns: 2,
+                    rows: 3,
+                    bits: bits as u8,
+                });
+            }
+        }
+    }
+    if set.braille {
+        for bits in 0..=255u8 {
+            let mut code = 0u32;
+            for (position, dot) in BRAILLE_DOTS.iter().enumerate() {
+                if bits & (1 << position) != 0 {
+                    code |= u32::from(*dot);
+                }
+            }
+            if let Some(ch) = char::from_u32(0x2800 + code) {
+                glyphs.push(Glyph {
+                    ch,
+                    columns: 2,
+                    rows: 4,
+                    bits,
+                });
+            }
+        }
+    }
+    glyphs
+}
+
+/// Returns the glyphs of `set` that cover a single cell, falling back to braille when none of
+/// them do.
+fn single_cell_glyphs(set: GlyphSet, ambiguous: AmbiguousWidth) -> Vec<Glyph> {
+    let mut candidates = glyphs(set);
+    candidates.retain(|glyph| is_single_cell(glyph.ch, ambiguous));
+    if candidates.is_empty() {
+        // Braille patterns and spaces have neutral width in every locale.
+        let braille = GlyphSet {
+            blocks: false,
+            quadrants: false,
+            sextants: false,
+            braille: true,
+        };
+        candidates = glyphs(braille);
+    }
+    candidates
+}
+
+/// Renders a pixel list with the best-matching glyph for every cell.
+///
+/// Cells whose pixel block is entirely missing are skipped. Only glyphs that cover exactly
+/// one cell under `options.ambiguous_width` are considered.
+///
+/// # Returns
+///
+/// A vector of [`GlyphCell`]s in row-major order.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::load_image;
+/// use rael_img::mosaic::{render_mosaic, GlyphSet, MosaicOptions};
+///
+/// // 40x20 cells at 2x4 pixels each.
+/// let pixels = load_image("./assets/logo.png", Some(80), Some(80), (0, 0), true, 1.0).unwrap();
+/// let options = MosaicOptions {
+///     glyphs: GlyphSet::ALL,
+///     ..MosaicOptions::default()
+/// };
+/// let cells = render_mosaic(&pixels, &options);
+/// ```
+pub fn render_mosaic(pixels: &[(u32, u32, Color)], options: &MosaicOptions) -> Vec<GlyphCell> {
+    let grid = PixelGrid::from_pixels(pixels);
+    let (cell_width, cell_height) = (options.cell_pixels.0.max(1), options.cell_pixels.1.max(1));
+    let mut set = options.glyphs;
+    if glyphs(set).is_empty() {
+        set = GlyphSet::BLOCKS;
+    }
+    let candidates = single_cell_glyphs(set, options.ambiguous_width);
+
+    let columns = grid.width.div_ceil(cell_width);
+    let rows = grid.height.div_ceil(cell_height);
+    let mut cells = Vec::with_capacity(columns as usize * rows as usize);
+    let mut block = Vec::with_capacity((cell_width * cell_height) as usize);
+
+    for row in 0..rows {
+        for column in 0..columns {
+            block.clear();
+            for y in 0..cell_height {
+                for x in 0..cell_width {
+                    if let Some(color) = grid.get(column * cell_width + x, row * cell_height + y) {
+                        block.push((x, y, color));
+                    }
+                }
+            }
+            if block.is_empty() {
+                continue;
+            }
+
+            let (glyph, fg, bg) = best_glyph(&candidates, &block, cell_width, cell_height);
+            cells.push(GlyphCell {
+                column: column + options.position.0,
+                row: row + options.position.1,
+                glyph,
+                fg,
+                bg,
+            });
+        }
+    }
+
+    cells
+}
+
+/// Finds the glyph with the smallest squared error, with the mean colors of its set and
+/// unset regions as foreground and background.
+fn best_glyph(
+    candidates: &[Glyph],
+    block: &[(u32, u32, Color)],
+    cell_width: u32,
+    cell_height: u32,
+) -> (char, Color, Color) {
+    let mut best: Option<(u64, char, Color, Color)> = None;
+    for glyph in candidates {
+        let mut sums = [[0u64; 4]; 2];
+        let mut squares = [0u64; 2];
+        for &(x, y, color) in block {
+            let set = glyph.is_set(x * glyph.columns / cell_width, y * glyph.rows / cell_height);
+            let side = &mut sums[usize::from(set)];
+            side[0] += u64::from(color.r);
+            side[1] += u64::from(color.g);
+            side[2] += u64::from(color.b);
+            side[3] += 1;
+            squares[usize::from(set)] +=
+                u64::from(color.r).pow(2) + u64::from(color.g).pow(2) + u64::from(color.b).pow(2);
+        }
+
+        // Sum of squared deviations from the mean, per side: sum(x^2) - sum(x)^2 / n.
+        let mut error = 0u64;
+        for side in 0..2 {
+            let squared_sum: u64 = sums[side][..3].iter().map(|s| s * s).sum();
+            if let Some(mean_part) = squared_sum.checked_div(sums[side][3]) {
+                error += squares[side] - mean_part;
+            }
+        }
+
+        if best.is_none_or(|(best_error, ..)| error < best_error) {
+            let mean = |side: &[u64; 4]| match side[3] {
+                0 => None,
+                n => Some(Color {
+                    r: ((side[0] + n / 2) / n) as u8,
+                    g: ((side[1] + n / 2) / n) as u8,
+                    b: ((side[2] + n / 2) / n) as u8,
+                }),
+            };
+            let bg = mean(&sums[0]);
+            let fg = mean(&sums[1]);
+            let fg_color = fg.or(bg).unwrap_or(Color { r: 0, g: 0, b: 0 });
+            let bg_color = bg.unwrap_or(fg_color);
+            best = Some((error, glyph.ch, fg_color, bg_color));
+        }
+    }
+
+    let (_, glyph, fg, bg) = best.expect("at least one candidate glyph");
+    (glyph, fg, bg)
+}
+
+/// Converts glyph cells into a string of truecolor ANSI escape sequences.
+///
+/// Every cell is positioned explicitly, so the cells do not need to be contiguous. Color
+/// escapes are only emitted when the color changes, and the string ends with a reset. The
+/// cursor is repositioned after any glyph that is not one cell wide, so such a glyph cannot
+/// shift the rest of its row.
+pub fn cells_to_ansi(cells: &[GlyphCell]) -> String {
+    write_cells(cells, |output, fg, bg| {
+        let _ = write!(
+            output,
+            "\u{1b}[38;2;{};{};{};48;2;{};{};{}m",
+            fg.r, fg.g, fg.b, bg.r, bg.g, bg.b
+        );
+    })
+}
+
+/// Converts glyph cells into a string of xterm 256-color escape sequences, for terminals
+/// without truecolor support.
+///
+/// Every color is mapped to the nearest entry of the 6×6×6 color cube or the gray ramp of
+/// [`XTERM_256`]. The first 16 entries are left out because terminals let users redefine
+/// them. Otherwise this works like [`cells_to_ansi`].
+pub fn cells_to_ansi_256(cells: &[GlyphCell]) -> String {
+    let mut cache: HashMap<(u8, u8, u8), usize> = HashMap::new();
+    let mut index = |color: Color| {
+        *cache
+            .entry((color.r, color.g, color.b))
+            .or_insert_with(|| 16 + nearest_index(&XTERM_256[16..], color))
+    };
+    // Map to palette colors first, so cells that only differ before mapping share escapes.
+    let mapped: Vec<GlyphCell> = cells
+        .iter()
+        .map(|&cell| GlyphCell {
+            fg: XTERM_256[index(cell.fg)],
+            bg: XTERM_256[index(cell.bg)],
+            ..cell
+        })
+        .collect();
+    write_cells(&mapped, |output, fg, bg| {
+        let (fg, bg) = (index(fg), index(bg));
+        let _ = write!(output, "\u{1b}[38;5;{fg};48;5;{bg}m");
+    })
+}
+
+/// Converts `(column, row, char, Color)` cells, such as those of
+/// [`render_braille`](crate::braille::render_braille), into a string of truecolor ANSI
+/// escape sequences that only set the foreground color.
+///
+/// The cells are positioned like [`cells_to_ansi`] does, and whatever is behind them keeps
+/// its background.
+pub fn char_cells_to_ansi(cells: &[(u32, u32, char, Color)]) -> String {
+    let cells: Vec<GlyphCell> = cells
+        .iter()
+        .map(|&(column, row, glyph, color)| GlyphCell {
+            column,
+            row,
+            glyph,
+            fg: color,
+            bg: color,
+        })
+        .collect();
+    write_cells(&cells, |output, fg, _| {
+        let _ = write!(output, "\u{1b}[38;2;{};{};{}m", fg.r, fg.g, fg.b);
+    })
+}
+
+/// Writes cells with explicit cursor positioning, calling `colors` whenever they change.
+fn write_cells(cells: &[GlyphCell], mut colors: impl FnMut(&mut String, Color, Color)) -> String {
+    let mut output = String::new();
+    let mut cursor: Option<(u32, u32)> = None;
+    let mut current: Option<(Color, Color)> = None;
+
+    for cell in cells {
+        if cursor != Some((cell.column, cell.row)) {
+            let _ = write!(output, "\u{1b}[{};{}H", cell.row + 1, cell.column + 1);
+        }
+        if current != Some((cell.fg, cell.bg)) {
+            colors(&mut output, cell.fg, cell.bg);
+            current = Some((cell.fg, cell.bg));
+        }
+        output.push(cell.glyph);
+        cursor = is_single_cell(cell.glyph, AmbiguousWidth::Narrow)
+            .then_some((cell.column + 1, cell.row));
+    }
+
+    if !cells.is_empty() {
+        output.push_str("\u{1b}[0m");
+    }
+    output
+}
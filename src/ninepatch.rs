@@ -0,0 +1,238 @@
+//! Nine-patch scaling for resizable frames.
+//!
+//! A nine-patch image is a small frame texture cut into a 3 by 3 grid by four margins. When
+//! it is drawn at another size, the four corners keep their size, the four edges stretch
+//! along the frame, and the center stretches or repeats to fill the rest. One texture then
+//! draws dialog boxes, panels and buttons of any size without blurring their borders.
+//!
+//! Scaling picks the nearest source pixel, so pixel-art frames stay crisp.
+
+use crate::LoadOptions;
+use crate::decode::file_decoder;
+use crate::error::RaelImgError;
+use crate::loaded::LoadedImage;
+use image::{DynamicImage, GenericImageView, RgbaImage};
+
+/// The widths of the fixed borders of a nine-patch image, in pixels of the source image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Margins {
+    /// The width of the left column.
+    pub left: u32,
+    /// The height of the top row.
+    pub top: u32,
+    /// The width of the right column.
+    pub right: u32,
+    /// The height of the bottom row.
+    pub bottom: u32,
+}
+
+impl Margins {
+    /// Creates margins with the given sizes.
+    pub const fn new(left: u32, top: u32, right: u32, bottom: u32) -> Self {
+        Self {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    /// Creates margins of the same size on every side.
+    pub const fn uniform(size: u32) -> Self {
+        Self::new(size, size, size, size)
+    }
+}
+
+/// How the center of a nine-patch image fills its area.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CenterFill {
+    /// Stretch the center to the area.
+    #[default]
+    Stretch,
+    /// Repeat the center from its top-left corner, for patterned backgrounds.
+    Tile,
+}
+
+/// A frame texture with the margins that divide it.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael::{Canvas, Color};
+/// use rael_img::canvas::CanvasImageExt;
+/// use rael_img::ninepatch::{Margins, NinePatch};
+///
+/// let frame = NinePatch::load("./assets/dialog.png", Margins::uniform(3)).unwrap();
+/// let mut canvas = Canvas::new(80, 24, Color { r: 0, g: 0, b: 0 });
+/// // Draw the dialog again whenever the terminal is resized.
+/// let dialog = frame.render(60, 30).translate(10, 8);
+/// canvas.draw_image(&dialog, 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NinePatch {
+    image: RgbaImage,
+    margins: Margins,
+    center: CenterFill,
+}
+
+impl NinePatch {
+    /// Creates a nine-patch from a decoded image.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is:
+    /// - `Ok(NinePatch)`: The nine-patch, with a stretched center.
+    /// - `Err(RaelImgError)`: If the margins of a side together are wider or taller than
+    ///   the image.
+    pub fn new(image: &DynamicImage, margins: Margins) -> Result<Self, RaelImgError> {
+        let (width, height) = image.dimensions();
+        if u64::from(margins.left) + u64::from(margins.right) > u64::from(width)
+            || u64::from(margins.top) + u64::from(margins.bottom) > u64::from(height)
+        {
+            return Err(RaelImgError::InvalidParameters(format!(
+                "nine-patch margins {margins:?} do not fit into a {width}x{height} image"
+            )));
+        }
+        Ok(Self {
+            image: image.to_rgba8(),
+            margins,
+            center: CenterFill::Stretch,
+        })
+    }
+
+    /// Loads and decodes a nine-patch image.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is:
+    /// - `Ok(NinePatch)`: The nine-patch, with a stretched center.
+    /// - `Err(RaelImgError)`: If the file could not be read or decoded, or the margins do
+    ///   not fit into it.
+    pub fn load(path: &str, margins: Margins) -> Result<Self, RaelImgError> {
+        let decoded = crate::pipeline::decode_using(
+            &std::fs::read(path)?,
+            &LoadOptions::default(),
+            &file_decoder(path),
+        )?;
+        Self::new(&decoded.image, margins)
+    }
+
+    /// Sets how the center fills its area.
+    pub fn with_center(mut self, center: CenterFill) -> Self {
+        self.center = center;
+        self
+    }
+
+    /// Returns the margins.
+    pub fn margins(&self) -> Margins {
+        self.margins
+    }
+
+    /// Draws the frame at `width` by `height` pixels.
+    ///
+    /// When the target is smaller than the margins of a side together, the corners shrink
+    /// in proportion and the edges and center disappear.
+    pub fn render_image(&self, width: u32, height: u32) -> RgbaImage {
+        let (source_width, source_height) = self.image.dimensions();
+        let margins = self.margins;
+        let tile = self.center == CenterFill::Tile;
+        let columns = Axis::new(source_width, margins.left, margins.right, width);
+        let rows = Axis::new(source_height, margins.top, margins.bottom, height);
+        RgbaImage::from_fn(width, height, |x, y| {
+            let center = columns.is_middle(x) && rows.is_middle(y);
+            let (sx, sy) = (
+                columns.source(x, tile && center),
+                rows.source(y, tile && center),
+            );
+            match (sx, sy) {
+                (Some(sx), Some(sy)) => *self.image.get_pixel(sx, sy),
+                _ => image::Rgba([0, 0, 0, 0]),
+            }
+        })
+    }
+
+    /// Draws the frame at `width` by `height` pixels, at the origin. Fully transparent
+    /// pixels, such as those around rounded corners, are left out.
+    pub fn render(&self, width: u32, height: u32) -> LoadedImage {
+        let options = LoadOptions {
+            alpha_threshold: 1,
+            ..LoadOptions::default()
+        };
+        LoadedImage::from_image(&self.render_image(width, height).into(), &options)
+    }
+}
+
+/// How one axis of the target maps onto the source.
+struct Axis {
+    /// The source length of the leading margin, the middle and the trailing margin.
+    source: (u32, u32, u32),
+    /// The target length of the same three parts.
+    target: (u32, u32, u32),
+}
+
+impl Axis {
+    fn new(length: u32, leading: u32, trailing: u32, target: u32) -> Self {
+        let margins = u64::from(leading) + u64::from(trailing);
+        let (lead, trail) = if margins > u64::from(target) {
+            // Shrink the margins in proportion so they fill the target between them.
+            let lead = (u64::from(leading) * u64::from(target) / margins.max(1)) as u32;
+            (lead, target - lead)
+        } else {
+            (leading, trailing)
+        };
+        Self {
+            source: (leading, length - leading - trailing, trailing),
+            target: (lead, target - lead - trail, trail),
+        }
+    }
+
+    fn is_middle(&self, at: u32) -> bool {
+        at >= self.target.0 && at - self.target.0 < self.target.1
+    }
+
+    /// Returns the source coordinate for target coordinate `at`, or `None` if the source
+    /// has nothing there.
+    fn source(&self, at: u32, tile: bool) -> Option<u32> {
+        let (lead, middle, trail) = self.target;
+        let scale = |offset: u32, target: u32, source: u32| {
+            (source > 0).then(|| (u64::from(offset) * u64::from(source) / u64::from(target)) as u32)
+        };
+        if at < lead {
+            scale(at, lead, self.source.0)
+        } else if at - lead < middle {
+            let offset = at - lead;
+            let source = self.source.1;
+            let inner = if tile {
+                (source > 0).then(|| offset % source.max(1))
+            } else {
+                scale(offset, middle, source)
+            };
+            inner.map(|inner| self.source.0 + inner)
+        } else {
+            let offset = at - lead - middle;
+            scale(offset, trail, self.source.2).map(|inner| self.source.0 + self.source.1 + inner)
+        }
+    }
+}
+
+/// Loads a nine-patch image and draws it at `width` by `height` pixels, at the origin.
+///
+/// Call [`NinePatch::load`] and [`NinePatch::render`] instead to draw the same frame at
+/// several sizes without decoding it again.
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(LoadedImage)`: The frame, with fully transparent pixels left out.
+/// - `Err(RaelImgError)`: If the file could not be read or decoded, or the margins do not
+///   fit into it.
+pub fn load_nine_patch(
+    path: &str,
+    margins: Margins,
+    width: u32,
+    height: u32,
+) -> Result<LoadedImage, RaelImgError> {
+    Ok(NinePatch::load(path, margins)?.render(width, height))
+}
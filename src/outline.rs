@@ -0,0 +1,106 @@
+//! Outlines around transparent sprites.
+//!
+//! A sprite drawn over a busy background can disappear into it. [`LoadOptions::outline`]
+//! traces the silhouette of a transparent image, its pixels at or above the
+//! [alpha cutoff](crate::LoadOptions::alpha_threshold), and surrounds it with a ring of
+//! opaque pixels in one color, so the sprite stays readable without editing the asset.
+//!
+//! The ring is drawn around the resized image and may extend past its edges, so the result
+//! is larger than the image by the thickness of the outline on every side, and starts that
+//! far above and left of the position. Images without an alpha channel have no silhouette
+//! and are left as they are.
+//!
+//! [`LoadOptions::outline`]: crate::LoadOptions::outline
+
+use image::{DynamicImage, Rgba, RgbaImage};
+use rael::Color;
+
+/// An outline around the opaque part of an image.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael::Color;
+/// use rael_img::outline::Outline;
+/// use rael_img::{load_image_with, LoadOptions};
+///
+/// let options = LoadOptions {
+///     outline: Some(Outline::new(Color { r: 0, g: 0, b: 0 })),
+///     ..LoadOptions::default()
+/// };
+/// let hero = load_image_with("./assets/hero.png", &options).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outline {
+    /// The color of the outline.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_color"))]
+    pub color: Color,
+    /// How many pixels the outline reaches out from the silhouette.
+    #[cfg_attr(feature = "serde", serde(default = "default_thickness"))]
+    pub thickness: u32,
+}
+
+#[cfg(feature = "serde")]
+fn default_thickness() -> u32 {
+    1
+}
+
+impl Outline {
+    /// Creates a 1-pixel outline in `color`.
+    pub const fn new(color: Color) -> Self {
+        Self {
+            color,
+            thickness: 1,
+        }
+    }
+
+    /// Returns the image with the outline drawn around every pixel with an alpha of at
+    /// least `threshold`, or at least 1 if `threshold` is 0.
+    ///
+    /// The result is `2 * thickness` pixels wider and taller than `image`; its pixel at
+    /// `(thickness, thickness)` is the top-left pixel of `image`. Pixels the outline does
+    /// not cover keep their color and alpha.
+    pub fn apply(&self, image: &DynamicImage, threshold: u8) -> DynamicImage {
+        let threshold = threshold.max(1);
+        let source = image.to_rgba8();
+        let pad = self.thickness;
+        let (width, height) = (source.width() + 2 * pad, source.height() + 2 * pad);
+        let mut result = RgbaImage::new(width, height);
+        image::imageops::replace(&mut result, &source, i64::from(pad), i64::from(pad));
+
+        // Grow the silhouette one pixel at a time into its eight neighbours.
+        let mut mask: Vec<bool> = result.pixels().map(|pixel| pixel[3] >= threshold).collect();
+        let silhouette = mask.clone();
+        for _ in 0..self.thickness {
+            mask = dilate(&mask, width, height);
+        }
+
+        let color = Rgba([self.color.r, self.color.g, self.color.b, 255]);
+        for ((pixel, grown), inside) in result.pixels_mut().zip(mask).zip(silhouette) {
+            if grown && !inside {
+                *pixel = color;
+            }
+        }
+        DynamicImage::ImageRgba8(result)
+    }
+}
+
+/// Marks every cell of a `width` by `height` mask that is set or next to a set cell.
+fn dilate(mask: &[bool], width: u32, height: u32) -> Vec<bool> {
+    let (width, height) = (width as usize, height as usize);
+    let mut grown = mask.to_vec();
+    for y in 0..height {
+        for x in 0..width {
+            if !mask[y * width + x] {
+                continue;
+            }
+            for ny in y.saturating_sub(1)..(y + 2).min(height) {
+                for nx in x.saturating_sub(1)..(x + 2).min(width) {
+                    grown[ny * width + nx] = true;
+                }
+            }
+        }
+    }
+    grown
+}
@@ -0,0 +1,114 @@
+//! Dominant colors and palettes of images.
+//!
+//! Applications that theme their interface after the image they show, such as a music
+//! player matching its borders to the album art, need a handful of representative colors
+//! rather than every pixel. The colors here come from the
+//! [median-cut](crate::quantize::median_cut) quantizer and are ordered by how much of the
+//! image they cover.
+//!
+//! Every function takes a pixel list, so a [`LoadedImage`](crate::loaded::LoadedImage) can
+//! be passed directly. Small images work as well as large ones and are much faster; load the
+//! image at a reduced size first when only its colors are needed.
+
+use crate::quantize::{median_cut, nearest_index};
+use rael::Color;
+
+/// How many colors [`dominant_color`] splits the image into before picking the largest.
+const DOMINANT_CLUSTERS: usize = 5;
+
+/// A palette color and how much of the image it stands for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Swatch {
+    /// The color.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_color"))]
+    pub color: Color,
+    /// The number of pixels closest to this color.
+    pub population: usize,
+    /// The share of the image closest to this color, from `0.0` to `1.0`.
+    pub share: f32,
+}
+
+/// Splits an image into at most `count` colors and returns them with the number of pixels
+/// each stands for, the largest first.
+///
+/// Swatches that tie keep the order of their colors in RGB. An empty image gives no swatches.
+pub fn swatches(pixels: &[(u32, u32, Color)], count: usize) -> Vec<Swatch> {
+    let palette = median_cut(pixels, count);
+    if palette.is_empty() {
+        return Vec::new();
+    }
+    let mut populations = vec![0usize; palette.len()];
+    for &(_, _, color) in pixels {
+        populations[nearest_index(&palette, color)] += 1;
+    }
+    let total = pixels.len().max(1) as f32;
+    let mut swatches: Vec<Swatch> = palette
+        .into_iter()
+        .zip(populations)
+        .filter(|&(_, population)| population > 0)
+        .map(|(color, population)| Swatch {
+            color,
+            population,
+            share: population as f32 / total,
+        })
+        .collect();
+    swatches.sort_by_key(|swatch| {
+        let color = swatch.color;
+        (
+            std::cmp::Reverse(swatch.population),
+            color.r,
+            color.g,
+            color.b,
+        )
+    });
+    swatches
+}
+
+/// Returns at most `count` colors that represent the image, the one covering most of it
+/// first.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::loaded::LoadedImage;
+/// use rael_img::palette::extract_palette;
+/// use rael_img::LoadOptions;
+///
+/// let options = LoadOptions {
+///     width: Some(64),
+///     ..LoadOptions::default()
+/// };
+/// let cover = LoadedImage::load("./covers/album.jpg", &options).unwrap();
+/// let theme = extract_palette(&cover, 4);
+/// ```
+pub fn extract_palette(pixels: &[(u32, u32, Color)], count: usize) -> Vec<Color> {
+    swatches(pixels, count)
+        .into_iter()
+        .map(|swatch| swatch.color)
+        .collect()
+}
+
+/// Returns the color that covers the largest part of the image, or `None` if it has no
+/// pixels.
+///
+/// The image is split into a few broad colors first, so a photo with many slightly
+/// different shades of blue gives a blue even if some other single color occurs more often.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::loaded::LoadedImage;
+/// use rael_img::palette::dominant_color;
+/// use rael_img::LoadOptions;
+///
+/// let cover = LoadedImage::load("./covers/album.jpg", &LoadOptions::default()).unwrap();
+/// if let Some(color) = dominant_color(&cover) {
+///     println!("border color: #{:02x}{:02x}{:02x}", color.r, color.g, color.b);
+/// }
+/// ```
+pub fn dominant_color(pixels: &[(u32, u32, Color)]) -> Option<Color> {
+    swatches(pixels, DOMINANT_CLUSTERS)
+        .first()
+        .map(|swatch| swatch.color)
+}
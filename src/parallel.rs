@@ -0,0 +1,46 @@
+//! Data-parallel loops that use rayon with the `rayon` feature and run sequentially
+//! without it.
+//!
+//! Every helper hands each element or row to a closure that only sees its own input and
+//! output and collects the results in order, so the output is the same either way.
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+/// Maps every item of a slice, keeping the order.
+pub(crate) fn map<T: Sync, U: Send>(items: &[T], function: impl Fn(&T) -> U + Sync) -> Vec<U> {
+    #[cfg(feature = "rayon")]
+    return items.par_iter().map(&function).collect();
+    #[cfg(not(feature = "rayon"))]
+    return items.iter().map(function).collect();
+}
+
+/// Maps every row index below `rows` to a list of items and concatenates the lists in row
+/// order.
+pub(crate) fn flat_map_rows<U: Send>(rows: u32, function: impl Fn(u32) -> Vec<U> + Sync) -> Vec<U> {
+    #[cfg(feature = "rayon")]
+    return (0..rows).into_par_iter().flat_map_iter(&function).collect();
+    #[cfg(not(feature = "rayon"))]
+    return (0..rows).flat_map(function).collect();
+}
+
+/// Calls `function` with the index and contents of every `row_length` long row of `buffer`.
+pub(crate) fn for_each_row<T: Send>(
+    buffer: &mut [T],
+    row_length: usize,
+    function: impl Fn(usize, &mut [T]) + Sync,
+) {
+    if row_length == 0 {
+        return;
+    }
+    #[cfg(feature = "rayon")]
+    buffer
+        .par_chunks_mut(row_length)
+        .enumerate()
+        .for_each(|(index, row)| function(index, row));
+    #[cfg(not(feature = "rayon"))]
+    buffer
+        .chunks_mut(row_length)
+        .enumerate()
+        .for_each(|(index, row)| function(index, row));
+}
@@ -0,0 +1,663 @@
+//! The processing pipeline on in-memory data.
+//!
+//! Everything here works on bytes and decoded images only: no paths, no file system. The
+//! path-based functions of this crate read the file and then call into this module, so
+//! sandboxes, fuzz harnesses and environments that bring their own I/O get exactly the same
+//! results by reading the bytes themselves.
+//!
+//! The pipeline has three stages:
+//!
+//! 1. [`decode`] turns encoded bytes into an image, under the
+//!    [`DecodePolicy`](crate::decode::DecodePolicy) in the options. Another
+//!    [`Decoder`](crate::backend::Decoder) can take its place.
+//! 2. [`target_size`] and [`resample`](crate::resample::resample) bring the image to the
+//!    size the options ask for, after cropping it and before turning it.
+//! 3. The finishing steps (blur, sharpening and pixelation, checkerboard, color
+//!    adjustments, color mode, readability tuning, dithering, tiling, debug overlay) run on
+//!    the result, which is converted to pixel tuples offset by the position. Pixels that
+//!    land above or left of the canvas origin, or outside the clip rectangle, are left out
+//!    last.
+//!
+//! [`process_image`] runs stages 2 and 3, [`process_bytes`] runs all three.
+
+use crate::backend::{Decoder, ImageCrate};
+use crate::decode::{PartialImage, PartialPixels, orient};
+use crate::effects::ColorMode;
+use crate::error::RaelImgError;
+use crate::loaded::LoadedImage;
+use crate::parallel;
+use crate::resample::resample;
+use crate::tile::TiledBackground;
+use crate::{LoadOptions, checkerboard, debug, dither, enhance};
+use image::{DynamicImage, GenericImageView};
+use rael::Color;
+use std::borrow::Cow;
+
+/// Decodes encoded image bytes under the policy in `options`.
+///
+/// This is [`decode_with_policy`](crate::decode::decode_with_policy) with
+/// `options.policy`, with the image turned upright according to its EXIF orientation
+/// unless [`LoadOptions::auto_orient`] is disabled.
+///
+/// The options are [checked](LoadOptions::validate) first, so the functions that load
+/// images through this one report invalid sizes before decoding.
+pub fn decode(bytes: &[u8], options: &LoadOptions) -> Result<PartialImage, RaelImgError> {
+    decode_using(bytes, options, &ImageCrate)
+}
+
+/// Decodes encoded image bytes with `decoder` and turns the result upright like [`decode`].
+pub(crate) fn decode_using(
+    bytes: &[u8],
+    options: &LoadOptions,
+    decoder: &dyn Decoder,
+) -> Result<PartialImage, RaelImgError> {
+    options.validate()?;
+    let mut decoded = decoder.decode(bytes, options.policy)?;
+    orient(&mut decoded.image, bytes, options);
+    Ok(decoded)
+}
+
+/// Decodes and processes encoded image bytes.
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(PartialPixels)`: The processed pixels. With a lenient policy an incomplete image
+///   yields the rows that decoded, scaled as if the image were complete, and a warning.
+/// - `Err(RaelImgError)`: If the bytes are not an image the policy accepts.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::pipeline::process_bytes;
+/// use rael_img::LoadOptions;
+///
+/// # let bytes: Vec<u8> = Vec::new();
+/// // `bytes` came from the network, an archive or a fuzzer.
+/// let loaded = process_bytes(&bytes, &LoadOptions::default()).unwrap();
+/// ```
+pub fn process_bytes(bytes: &[u8], options: &LoadOptions) -> Result<PartialPixels, RaelImgError> {
+    process_bytes_with(bytes, options, &ImageCrate)
+}
+
+/// Decodes encoded image bytes with `decoder` and processes them like [`process_bytes`].
+///
+/// See [`backend`](crate::backend) for alternative decoders.
+pub fn process_bytes_with(
+    bytes: &[u8],
+    options: &LoadOptions,
+    decoder: &dyn Decoder,
+) -> Result<PartialPixels, RaelImgError> {
+    let decoded = decode_using(bytes, options, decoder)?;
+    let pixels = finish_pixels(&resized(&decoded, options), options);
+    Ok(PartialPixels {
+        pixels,
+        warning: decoded.warning,
+    })
+}
+
+/// Resizes and finishes a decoded image and returns its pixels.
+///
+/// # Examples
+///
+/// ```
+/// use rael_img::pipeline::process_image;
+/// use rael_img::LoadOptions;
+///
+/// let image = image::DynamicImage::new_rgb8(40, 20);
+/// let options = LoadOptions {
+///     width: Some(10),
+///     ..LoadOptions::default()
+/// };
+/// assert_eq!(process_image(&image, &options).len(), 10 * 5);
+/// ```
+pub fn process_image(image: &DynamicImage, options: &LoadOptions) -> Vec<(u32, u32, Color)> {
+    finish_pixels(&resized_image(image, options), options)
+}
+
+/// Wraps raw RGB data, 3 bytes per pixel row by row, as an image to process.
+///
+/// Procedurally generated frames, such as plots or fractals, go through the pipeline this
+/// way without being encoded first.
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(DynamicImage)`: The image, to pass to [`process_image`].
+/// - `Err(RaelImgError)`: `RaelImgError::InvalidParameters` if `data` is not exactly
+///   `width * height * 3` bytes long.
+///
+/// # Examples
+///
+/// ```
+/// use rael_img::pipeline::{from_raw_rgb, process_image};
+/// use rael_img::LoadOptions;
+///
+/// // A 2x1 image: one red pixel, one blue pixel.
+/// let image = from_raw_rgb(2, 1, &[255, 0, 0, 0, 0, 255]).unwrap();
+/// let pixels = process_image(&image, &LoadOptions::default());
+/// assert_eq!(pixels.len(), 2);
+/// ```
+pub fn from_raw_rgb(width: u32, height: u32, data: &[u8]) -> Result<DynamicImage, RaelImgError> {
+    check_raw_length(width, height, data, 3, "RGB")?;
+    let image = image::RgbImage::from_raw(width, height, data.to_vec());
+    Ok(DynamicImage::ImageRgb8(
+        image.expect("the length was checked"),
+    ))
+}
+
+/// Wraps raw RGBA data, 4 bytes per pixel row by row, as an image to process, like
+/// [`from_raw_rgb`].
+pub fn from_raw_rgba(width: u32, height: u32, data: &[u8]) -> Result<DynamicImage, RaelImgError> {
+    check_raw_length(width, height, data, 4, "RGBA")?;
+    let image = image::RgbaImage::from_raw(width, height, data.to_vec());
+    Ok(DynamicImage::ImageRgba8(
+        image.expect("the length was checked"),
+    ))
+}
+
+fn check_raw_length(
+    width: u32,
+    height: u32,
+    data: &[u8],
+    channels: u64,
+    layout: &str,
+) -> Result<(), RaelImgError> {
+    let expected = u64::from(width) * u64::from(height) * channels;
+    if data.len() as u64 != expected {
+        return Err(RaelImgError::InvalidParameters(format!(
+            "a {width}x{height} {layout} image needs {expected} bytes, got {}",
+            data.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Resizes a decoded image and returns its pixels with their alpha values.
+///
+/// Pixels with an alpha below [`LoadOptions::alpha_threshold`] are left out. The finishing
+/// steps of `options` (checkerboard, readability tuning, debug overlay) are not applied,
+/// since they work on opaque colors.
+///
+/// # Examples
+///
+/// ```
+/// use rael_img::pipeline::process_image_rgba;
+/// use rael_img::LoadOptions;
+///
+/// // A fully transparent image.
+/// let image = image::DynamicImage::new_rgba8(4, 4);
+/// let options = LoadOptions {
+///     alpha_threshold: 1,
+///     ..LoadOptions::default()
+/// };
+/// assert!(process_image_rgba(&image, &options).is_empty());
+/// ```
+pub fn process_image_rgba(
+    image: &DynamicImage,
+    options: &LoadOptions,
+) -> Vec<(u32, u32, Color, u8)> {
+    rgba_pixels(&resized_image(image, options), options)
+}
+
+/// Brings a decoded image to the size in `options`. An incomplete image keeps the rows that
+/// decoded, scaled as if it were complete.
+pub(crate) fn resized<'a>(
+    decoded: &'a PartialImage,
+    options: &LoadOptions,
+) -> Cow<'a, DynamicImage> {
+    let Some(warning) = &decoded.warning else {
+        return resized_image(&decoded.image, options);
+    };
+    let image = prepared(&decoded.image, options);
+    let (width, height) = image.dimensions();
+    let complete_rows = match options.crop {
+        Some((_, y, _, _)) => warning.complete_rows.saturating_sub(y).min(height),
+        None => warning.complete_rows,
+    };
+    let (target_width, target_height) = unturned_size(width, height, options);
+    if width == 0 || complete_rows == 0 {
+        return Cow::Owned(DynamicImage::new_rgba8(0, 0));
+    }
+    let rows =
+        ((u64::from(target_height) * u64::from(complete_rows)) / u64::from(height.max(1))) as u32;
+    let complete = image.crop_imm(0, 0, width, complete_rows);
+    let resized = resample(&complete, target_width, rows.max(1), &options.resampling());
+    Cow::Owned(options.orientation.apply(&resized).into_owned())
+}
+
+/// Brings a complete image to the size in `options`, borrowing it if it already has it.
+pub(crate) fn resized_image<'a>(
+    image: &'a DynamicImage,
+    options: &LoadOptions,
+) -> Cow<'a, DynamicImage> {
+    let image = prepared(image, options);
+    let (width, height) = unturned_size(image.width(), image.height(), options);
+    let resized = if (width, height) == image.dimensions() {
+        image
+    } else {
+        Cow::Owned(resample(&image, width, height, &options.resampling()))
+    };
+    match options.orientation.apply(&resized) {
+        Cow::Borrowed(_) => resized,
+        Cow::Owned(turned) => Cow::Owned(turned),
+    }
+}
+
+/// Crops an image and applies the color key, the steps that come before resizing.
+pub(crate) fn prepared<'a>(
+    image: &'a DynamicImage,
+    options: &LoadOptions,
+) -> Cow<'a, DynamicImage> {
+    let image = cropped(image, options.crop);
+    let Some(key) = options.transparent_color else {
+        return image;
+    };
+    match key.apply(&image) {
+        Cow::Borrowed(_) => image,
+        Cow::Owned(keyed) => Cow::Owned(keyed),
+    }
+}
+
+/// Cuts the `(x, y, width, height)` region out of an image, clamped to its bounds.
+pub(crate) fn cropped(
+    image: &DynamicImage,
+    crop: Option<(u32, u32, u32, u32)>,
+) -> Cow<'_, DynamicImage> {
+    match crop {
+        None => Cow::Borrowed(image),
+        Some((x, y, width, height)) => Cow::Owned(image.crop_imm(x, y, width, height)),
+    }
+}
+
+/// Returns the size to resample an already cropped image to, before it is turned to
+/// [`LoadOptions::orientation`].
+fn unturned_size(width: u32, height: u32, options: &LoadOptions) -> (u32, u32) {
+    let uncropped = LoadOptions {
+        crop: None,
+        ..*options
+    };
+    let (width, height) = target_size(width, height, &uncropped);
+    options.orientation.size(width, height)
+}
+
+/// Computes the size [`load_image`](crate::load_image) resizes a `width` by `height` image
+/// to under `options`, including [`LoadOptions::crop`] and
+/// [`LoadOptions::orientation`].
+///
+/// Reading only an image's header is enough to know how large its pixels will be.
+pub fn target_size(width: u32, height: u32, options: &LoadOptions) -> (u32, u32) {
+    let (width, height) = match options.crop {
+        Some((x, y, crop_width, crop_height)) => (
+            crop_width.min(width.saturating_sub(x)),
+            crop_height.min(height.saturating_sub(y)),
+        ),
+        None => (width, height),
+    };
+    let (width, height) = options.orientation.size(width, height);
+    if width == 0 || height == 0 {
+        return (0, 0);
+    }
+    // Invalid sizes are rejected by `LoadOptions::validate`; here they become 1 pixel.
+    let scaled = |length: u32| ((length as f32 * options.scale).round() as u32).max(1);
+    let target_width = options
+        .width
+        .map_or_else(|| scaled(width), |width| width.max(1));
+    let target_height = options
+        .height
+        .map_or_else(|| scaled(height), |height| height.max(1));
+
+    if target_width == width && target_height == height {
+        (width, height)
+    } else if options.stretch && options.width.is_some() && options.height.is_some() {
+        (target_width, target_height)
+    } else {
+        // The same fit-inside computation `DynamicImage::resize` uses.
+        let ratio = f64::min(
+            f64::from(target_width) / f64::from(width),
+            f64::from(target_height) / f64::from(height),
+        );
+        (
+            ((f64::from(width) * ratio).round() as u32).max(1),
+            ((f64::from(height) * ratio).round() as u32).max(1),
+        )
+    }
+}
+
+/// Converts a resized image into pixels and applies the finishing steps in `options`.
+pub(crate) fn finish_pixels(image: &DynamicImage, options: &LoadOptions) -> Vec<(u32, u32, Color)> {
+    finish_loaded(image, options).into_pixels()
+}
+
+/// Like [`finish_pixels`], keeping the size and position of the box the pixels cover,
+/// grown by the outline and the shadow in `options`. A shadow cast up or left also moves
+/// the position up or left.
+pub(crate) fn finish_loaded(image: &DynamicImage, options: &LoadOptions) -> LoadedImage {
+    let detailed = options.detail.apply(image);
+    let (image, placement) = decorated(&detailed, options);
+    let image = &*image;
+    let dimensions = image.dimensions();
+    let size = options.tile.unwrap_or(dimensions);
+    let mut pixels = match options.checkerboard {
+        Some(board) if image.color().has_alpha() => {
+            board.composite(&image.to_rgba8(), placement.offset)
+        }
+        _ if (options.background.is_some() || options.alpha_cutoff() > 0)
+            && image.color().has_alpha() =>
+        {
+            image_to_rgba_pixels(image, placement.offset, options.alpha_cutoff())
+                .into_iter()
+                .map(|(x, y, color, alpha)| match options.background {
+                    Some(background) => (x, y, checkerboard::blend(color, alpha, background)),
+                    None => (x, y, color),
+                })
+                .collect()
+        }
+        _ => image_to_pixels(image, placement.offset),
+    };
+    if !options.adjustments.is_none() {
+        pixels = options.adjustments.apply_to(&pixels);
+    }
+    if options.color_mode != ColorMode::TrueColor {
+        pixels = options.color_mode.apply_to(&pixels);
+    }
+    if options.optimize {
+        pixels = enhance::optimize_for_terminal(&pixels);
+    }
+    if let Some(dither) = &options.dither {
+        pixels = dither::dither_with(&pixels, dither);
+    }
+    if let Some(region) = options.tile {
+        pixels = TiledBackground::from_region(&pixels, placement.offset, dimensions).view(
+            (0, 0),
+            region,
+            placement.offset,
+        );
+    }
+    if options.debug {
+        pixels = debug::debug_overlay(&pixels, &debug::DebugOverlay::default());
+    }
+    LoadedImage::with_bounds(placement.apply(pixels), size.0, size.1, placement.offset)
+}
+
+/// Converts a resized image into pixel tuples with alpha, placed as `options` asks.
+pub(crate) fn rgba_pixels(
+    image: &DynamicImage,
+    options: &LoadOptions,
+) -> Vec<(u32, u32, Color, u8)> {
+    let detailed = options.detail.apply(image);
+    let (image, placement) = decorated(&detailed, options);
+    let mut pixels = image_to_rgba_pixels(&image, placement.offset, options.alpha_cutoff());
+    if !placement.is_exact() {
+        pixels.retain_mut(|(x, y, _, _)| placement.place(x, y));
+    }
+    pixels
+}
+
+/// Draws the outline and the shadow in `options` around a resized image and returns it with
+/// where it lands, moved up and left by what they add on those sides.
+fn decorated<'a>(
+    image: &'a DynamicImage,
+    options: &LoadOptions,
+) -> (Cow<'a, DynamicImage>, Placement) {
+    let mut image = Cow::Borrowed(image);
+    if !image.color().has_alpha() {
+        return (image, Placement::new(options));
+    }
+    let (mut x, mut y) = options.position;
+    let mut move_by = |(left, top): (u32, u32)| {
+        x = x.saturating_sub(i32::try_from(left).unwrap_or(i32::MAX));
+        y = y.saturating_sub(i32::try_from(top).unwrap_or(i32::MAX));
+    };
+    if let Some(outline) = options.outline {
+        image = Cow::Owned(outline.apply(&image, options.alpha_cutoff()));
+        move_by((outline.thickness, outline.thickness));
+    }
+    if let Some(shadow) = options.shadow {
+        image = Cow::Owned(shadow.apply(&image, options.alpha_cutoff()));
+        move_by(shadow.origin());
+    }
+    let moved = LoadOptions {
+        position: (x, y),
+        ..*options
+    };
+    (image, Placement::new(&moved))
+}
+
+/// Where the pixels of an image land on the canvas, from
+/// [`LoadOptions::position`] and [`LoadOptions::clip`].
+///
+/// Pixel coordinates cannot be negative, so the non-negative part of the position is added
+/// while converting and the negative part is taken off afterwards, dropping the pixels it
+/// moves past the origin.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Placement {
+    /// The offset added while converting.
+    pub(crate) offset: (u32, u32),
+    /// The amount taken off afterwards.
+    shift: (u32, u32),
+    clip: Option<(u32, u32, u32, u32)>,
+}
+
+impl Placement {
+    pub(crate) fn new(options: &LoadOptions) -> Self {
+        let (x, y) = options.position;
+        Self {
+            offset: (x.max(0).unsigned_abs(), y.max(0).unsigned_abs()),
+            shift: (x.min(0).unsigned_abs(), y.min(0).unsigned_abs()),
+            clip: options.clip,
+        }
+    }
+
+    /// Returns `true` if converting with [`offset`](Placement::offset) is all there is to do.
+    pub(crate) fn is_exact(&self) -> bool {
+        self.shift == (0, 0) && self.clip.is_none()
+    }
+
+    /// Moves a converted pixel to its final coordinate and returns whether it is kept.
+    pub(crate) fn place(&self, x: &mut u32, y: &mut u32) -> bool {
+        if *x < self.shift.0 || *y < self.shift.1 {
+            return false;
+        }
+        *x -= self.shift.0;
+        *y -= self.shift.1;
+        match self.clip {
+            Some((left, top, width, height)) => {
+                *x >= left && *y >= top && *x - left < width && *y - top < height
+            }
+            None => true,
+        }
+    }
+
+    /// Places every pixel of a converted list.
+    pub(crate) fn apply(&self, mut pixels: Vec<(u32, u32, Color)>) -> Vec<(u32, u32, Color)> {
+        if !self.is_exact() {
+            pixels.retain_mut(|(x, y, _)| self.place(x, y));
+        }
+        pixels
+    }
+}
+
+/// Converts an image into pixel tuples offset by `position`.
+pub(crate) fn image_to_pixels(
+    image: &DynamicImage,
+    position: (u32, u32),
+) -> Vec<(u32, u32, Color)> {
+    parallel::flat_map_rows(image.height(), |y| {
+        (0..image.width())
+            .map(|x| {
+                let pixel = image.get_pixel(x, y);
+                let color = Color {
+                    r: pixel[0],
+                    g: pixel[1],
+                    b: pixel[2],
+                };
+                (x + position.0, y + position.1, color)
+            })
+            .collect()
+    })
+}
+
+/// Converts an image into pixel tuples with alpha offset by `position`, leaving out pixels
+/// with an alpha below `threshold`.
+pub(crate) fn image_to_rgba_pixels(
+    image: &DynamicImage,
+    position: (u32, u32),
+    threshold: u8,
+) -> Vec<(u32, u32, Color, u8)> {
+    parallel::flat_map_rows(image.height(), |y| {
+        (0..image.width())
+            .map(|x| (x, image.get_pixel(x, y)))
+            .filter(|(_, pixel)| pixel[3] >= threshold)
+            .map(|(x, pixel)| {
+                let color = Color {
+                    r: pixel[0],
+                    g: pixel[1],
+                    b: pixel[2],
+                };
+                (x + position.0, y + position.1, color, pixel[3])
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::outline::Outline;
+    use crate::transform::{Orientation, Rotation};
+    use image::{Rgba, RgbaImage};
+
+    /// A `width` × `height` image whose first column is transparent and whose other pixels
+    /// are opaque red.
+    fn margined(width: u32, height: u32) -> DynamicImage {
+        DynamicImage::ImageRgba8(RgbaImage::from_fn(width, height, |x, _| match x {
+            0 => Rgba([0, 0, 0, 0]),
+            _ => Rgba([255, 0, 0, 255]),
+        }))
+    }
+
+    fn row(pixels: &[(u32, u32, Color)], y: u32) -> Vec<u32> {
+        let mut xs: Vec<u32> = pixels.iter().filter(|p| p.1 == y).map(|p| p.0).collect();
+        xs.sort_unstable();
+        xs
+    }
+
+    #[test]
+    fn tile_repeats_the_image_box_with_its_transparent_margin() {
+        let options = LoadOptions {
+            alpha_threshold: 1,
+            tile: Some((8, 2)),
+            ..LoadOptions::default()
+        };
+        let loaded = finish_loaded(&margined(4, 2), &options);
+        assert_eq!((loaded.width(), loaded.height()), (8, 2));
+        assert_eq!(row(loaded.pixels(), 0), [1, 2, 3, 5, 6, 7]);
+        assert_eq!(row(loaded.pixels(), 1), [1, 2, 3, 5, 6, 7]);
+    }
+
+    #[test]
+    fn target_size_fits_inside_unless_stretched() {
+        let width = |width| LoadOptions {
+            width: Some(width),
+            ..LoadOptions::default()
+        };
+        assert_eq!(target_size(40, 20, &width(10)), (10, 5));
+        let both = LoadOptions {
+            height: Some(10),
+            ..width(10)
+        };
+        assert_eq!(target_size(40, 20, &both), (10, 5));
+        let stretched = LoadOptions {
+            stretch: true,
+            ..both
+        };
+        assert_eq!(target_size(40, 20, &stretched), (10, 10));
+        let half = LoadOptions {
+            scale: 0.5,
+            ..LoadOptions::default()
+        };
+        assert_eq!(target_size(40, 20, &half), (20, 10));
+    }
+
+    #[test]
+    fn target_size_crops_before_turning() {
+        let options = LoadOptions {
+            crop: Some((30, 0, 50, 8)),
+            orientation: Orientation {
+                rotation: Rotation::Rotate90,
+                ..Orientation::IDENTITY
+            },
+            ..LoadOptions::default()
+        };
+        // The crop is clamped to 10 × 8, then turned.
+        assert_eq!(target_size(40, 20, &options), (8, 10));
+    }
+
+    #[test]
+    fn alpha_threshold_leaves_out_transparent_pixels() {
+        let options = LoadOptions {
+            alpha_threshold: 1,
+            ..LoadOptions::default()
+        };
+        let loaded = finish_loaded(&margined(4, 2), &options);
+        assert_eq!(row(loaded.pixels(), 0), [1, 2, 3]);
+        assert_eq!((loaded.width(), loaded.height()), (4, 2));
+    }
+
+    #[test]
+    fn outline_grows_the_box_and_moves_it_up_and_left() {
+        let options = LoadOptions {
+            position: (5, 5),
+            outline: Some(Outline::new(Color { r: 0, g: 0, b: 0 })),
+            ..LoadOptions::default()
+        };
+        let loaded = finish_loaded(&margined(4, 2), &options);
+        assert_eq!((loaded.width(), loaded.height()), (6, 4));
+        assert_eq!(loaded.position(), (4, 4));
+        // The outline reaches one pixel around the opaque columns 1 to 3.
+        assert_eq!(row(loaded.pixels(), 4), [5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn negative_positions_and_clips_drop_pixels() {
+        let image = DynamicImage::new_rgb8(4, 2);
+        let shifted = LoadOptions {
+            position: (-1, -1),
+            ..LoadOptions::default()
+        };
+        let pixels = process_image(&image, &shifted);
+        assert_eq!(row(&pixels, 0), [0, 1, 2]);
+        assert!(pixels.iter().all(|pixel| pixel.1 == 0));
+
+        let clipped = LoadOptions {
+            clip: Some((1, 0, 2, 1)),
+            ..LoadOptions::default()
+        };
+        let pixels = process_image(&image, &clipped);
+        assert_eq!(row(&pixels, 0), [1, 2]);
+        assert_eq!(pixels.len(), 2);
+    }
+
+    #[test]
+    fn invalid_options_fail_before_decoding() {
+        let options = LoadOptions {
+            width: Some(0),
+            ..LoadOptions::default()
+        };
+        assert!(matches!(
+            process_bytes(b"not an image", &options),
+            Err(RaelImgError::InvalidParameters(_))
+        ));
+        assert!(process_bytes(b"not an image", &LoadOptions::default()).is_err());
+    }
+
+    #[test]
+    fn raw_data_must_match_the_size() {
+        assert!(from_raw_rgb(2, 1, &[0; 6]).is_ok());
+        assert!(matches!(
+            from_raw_rgba(2, 1, &[0; 6]),
+            Err(RaelImgError::InvalidParameters(_))
+        ));
+    }
+}
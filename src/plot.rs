@@ -0,0 +1,326 @@
+//! Line, bar and scatter plots and sparklines rasterized at pixel resolution.
+//!
+//! Each function draws into a fixed `width` x `height` plot area and returns the pixels
+//! that were set, ready to be drawn like a loaded image. Values are mapped so that larger
+//! values appear higher up.
+
+use crate::grid::PixelGrid;
+use rael::Color;
+
+/// Layout and scaling shared by all plot kinds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlotOptions {
+    /// The width of the plot in pixels, including the axes.
+    pub width: u32,
+    /// The height of the plot in pixels, including the axes.
+    pub height: u32,
+    /// The `(x, y)` offset applied to each pixel.
+    pub position: (u32, u32),
+    /// The value range of the vertical axis. Defaults to the range of the data.
+    pub y_range: Option<(f32, f32)>,
+    /// The value range of the horizontal axis for scatter plots. Defaults to the range of
+    /// the data. Line and bar plots spread their values evenly instead.
+    pub x_range: Option<(f32, f32)>,
+    /// If set, a left and bottom axis line is drawn in this color and the data is drawn
+    /// inside them.
+    pub axes: Option<Color>,
+    /// If set, the whole plot area is filled with this color first.
+    pub background: Option<Color>,
+}
+
+impl PlotOptions {
+    /// Creates options for a plot of the given size with automatic ranges and no axes.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            position: (0, 0),
+            y_range: None,
+            x_range: None,
+            axes: None,
+            background: None,
+        }
+    }
+}
+
+/// The plot area after reserving space for the axes.
+struct Frame {
+    grid: PixelGrid,
+    left: i64,
+    width: i64,
+    height: i64,
+}
+
+impl Frame {
+    fn new(options: &PlotOptions) -> Self {
+        let mut grid = PixelGrid::new(options.width, options.height);
+        let (w, h) = (i64::from(options.width), i64::from(options.height));
+        if let Some(background) = options.background {
+            for y in 0..h {
+                for x in 0..w {
+                    grid.set(x, y, background);
+                }
+            }
+        }
+
+        let inset = i64::from(options.axes.is_some());
+        if let Some(axes) = options.axes {
+            for y in 0..h {
+                grid.set(0, y, axes);
+            }
+            for x in 0..w {
+                grid.set(x, h - 1, axes);
+            }
+        }
+
+        Self {
+            grid,
+            left: inset,
+            width: (w - inset).max(0),
+            height: (h - inset).max(0),
+        }
+    }
+
+    /// Maps a normalised `(0..=1, 0..=1)` position to grid coordinates, y pointing up.
+    fn map(&self, tx: f32, ty: f32) -> (i64, i64) {
+        let x = self.left + (tx * (self.width - 1).max(0) as f32).round() as i64;
+        let y = (self.height - 1) - (ty * (self.height - 1).max(0) as f32).round() as i64;
+        (x, y)
+    }
+
+    fn line(&mut self, from: (i64, i64), to: (i64, i64), color: Color) {
+        let (mut x, mut y) = from;
+        let dx = (to.0 - x).abs();
+        let dy = -(to.1 - y).abs();
+        let step_x = if x < to.0 { 1 } else { -1 };
+        let step_y = if y < to.1 { 1 } else { -1 };
+        let mut error = dx + dy;
+        loop {
+            self.grid.set(x, y, color);
+            if (x, y) == to {
+                break;
+            }
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    fn finish(self, position: (u32, u32)) -> Vec<(u32, u32, Color)> {
+        self.grid.to_pixels(position)
+    }
+}
+
+/// Returns the range to map from, falling back to the finite range of `values`.
+fn value_range(values: impl Iterator<Item = f32>, explicit: Option<(f32, f32)>) -> (f32, f32) {
+    if let Some(range) = explicit {
+        return range;
+    }
+    let (min, max) = values
+        .filter(|value| value.is_finite())
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), value| {
+            (min.min(value), max.max(value))
+        });
+    if min > max { (0.0, 1.0) } else { (min, max) }
+}
+
+fn normalise(value: f32, (min, max): (f32, f32)) -> f32 {
+    if max > min {
+        ((value - min) / (max - min)).clamp(0.0, 1.0)
+    } else {
+        0.5
+    }
+}
+
+/// Draws `values` as a connected line, spread evenly across the plot's width.
+///
+/// Non-finite values break the line into separate segments.
+///
+/// # Examples
+///
+/// ```
+/// use rael::Color;
+/// use rael_img::plot::{line_plot, PlotOptions};
+///
+/// let cpu = [12.0, 18.0, 35.0, 30.0, 64.0, 52.0, 40.0];
+/// let mut options = PlotOptions::new(40, 16);
+/// options.y_range = Some((0.0, 100.0));
+/// options.axes = Some(Color { r: 90, g: 90, b: 90 });
+///
+/// let pixels = line_plot(&cpu, Color { r: 80, g: 200, b: 120 }, &options);
+/// assert!(!pixels.is_empty());
+/// ```
+pub fn line_plot(values: &[f32], color: Color, options: &PlotOptions) -> Vec<(u32, u32, Color)> {
+    let mut frame = Frame::new(options);
+    let range = value_range(values.iter().copied(), options.y_range);
+    let last = values.len().saturating_sub(1).max(1) as f32;
+
+    let mut previous = None;
+    for (index, &value) in values.iter().enumerate() {
+        if !value.is_finite() {
+            previous = None;
+            continue;
+        }
+        let point = frame.map(index as f32 / last, normalise(value, range));
+        match previous {
+            Some(from) => frame.line(from, point, color),
+            None => frame.grid.set(point.0, point.1, color),
+        }
+        previous = Some(point);
+    }
+
+    frame.finish(options.position)
+}
+
+/// Draws `values` as vertical bars of equal width.
+///
+/// Bars grow from zero when zero lies inside the value range, and from the bottom of the
+/// range otherwise. With more values than columns, several bars share a column.
+pub fn bar_chart(values: &[f32], color: Color, options: &PlotOptions) -> Vec<(u32, u32, Color)> {
+    let mut frame = Frame::new(options);
+    if values.is_empty() || frame.width == 0 || frame.height == 0 {
+        return frame.finish(options.position);
+    }
+
+    let range = value_range(values.iter().copied(), options.y_range);
+    let baseline = if range.0 <= 0.0 && range.1 >= 0.0 {
+        0.0
+    } else {
+        range.0
+    };
+    let (_, base_y) = frame.map(0.0, normalise(baseline, range));
+
+    let count = values.len() as i64;
+    for (index, &value) in values.iter().enumerate() {
+        if !value.is_finite() {
+            continue;
+        }
+        let index = index as i64;
+        let start = frame.left + index * frame.width / count;
+        let end = (frame.left + (index + 1) * frame.width / count).max(start + 1);
+        let (_, top) = frame.map(0.0, normalise(value, range));
+        let (low, high) = (top.min(base_y), top.max(base_y));
+        for x in start..end {
+            for y in low..=high {
+                frame.grid.set(x, y, color);
+            }
+        }
+    }
+
+    frame.finish(options.position)
+}
+
+/// Draws `(x, y)` points as single pixels.
+pub fn scatter_plot(
+    points: &[(f32, f32)],
+    color: Color,
+    options: &PlotOptions,
+) -> Vec<(u32, u32, Color)> {
+    let mut frame = Frame::new(options);
+    let x_range = value_range(points.iter().map(|point| point.0), options.x_range);
+    let y_range = value_range(points.iter().map(|point| point.1), options.y_range);
+
+    for &(x, y) in points {
+        if !x.is_finite() || !y.is_finite() {
+            continue;
+        }
+        let (px, py) = frame.map(normalise(x, x_range), normalise(y, y_range));
+        frame.grid.set(px, py, color);
+    }
+
+    frame.finish(options.position)
+}
+
+/// Options for [`sparkline`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparklineOptions {
+    /// The width of the strip in pixels. Each column shows one value.
+    pub width: u32,
+    /// The height of the strip in pixels.
+    pub height: u32,
+    /// The `(x, y)` offset applied to each pixel.
+    pub position: (u32, u32),
+    /// The value range mapped onto the strip's height. Defaults to the min/max of the
+    /// visible values.
+    pub range: Option<(f32, f32)>,
+    /// The color of values below every threshold.
+    pub color: Color,
+    /// `(threshold, color)` pairs: a value takes the color of the highest threshold it
+    /// reaches. Order does not matter.
+    pub thresholds: Vec<(f32, Color)>,
+    /// If `true`, columns are filled down to the bottom; otherwise only the top pixel of
+    /// each column is drawn.
+    pub fill: bool,
+}
+
+impl SparklineOptions {
+    /// Creates filled sparkline options with a single color and automatic scaling.
+    pub fn new(width: u32, height: u32, color: Color) -> Self {
+        Self {
+            width,
+            height,
+            position: (0, 0),
+            range: None,
+            color,
+            thresholds: Vec::new(),
+            fill: true,
+        }
+    }
+}
+
+/// Renders a value series as a compact `width` x `height` strip.
+///
+/// When there are more values than columns, only the most recent `width` values are shown,
+/// right-aligned, so a growing series scrolls like a status-bar meter.
+///
+/// # Examples
+///
+/// ```
+/// use rael::Color;
+/// use rael_img::plot::{sparkline, SparklineOptions};
+///
+/// let latency = [12.0, 14.0, 11.0, 40.0, 95.0, 30.0, 15.0];
+/// let mut options = SparklineOptions::new(20, 4, Color { r: 90, g: 200, b: 90 });
+/// options.thresholds = vec![
+///     (50.0, Color { r: 230, g: 200, b: 60 }),
+///     (90.0, Color { r: 230, g: 60, b: 60 }),
+/// ];
+///
+/// let pixels = sparkline(&latency, &options);
+/// assert!(!pixels.is_empty());
+/// ```
+pub fn sparkline(values: &[f32], options: &SparklineOptions) -> Vec<(u32, u32, Color)> {
+    let mut grid = PixelGrid::new(options.width, options.height);
+    let visible = &values[values.len().saturating_sub(options.width as usize)..];
+    let range = value_range(visible.iter().copied(), options.range);
+    let first_column = i64::from(options.width) - visible.len() as i64;
+    let rows = i64::from(options.height);
+
+    for (index, &value) in visible.iter().enumerate() {
+        if !value.is_finite() {
+            continue;
+        }
+        let color = options
+            .thresholds
+            .iter()
+            .filter(|&&(threshold, _)| value >= threshold)
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map_or(options.color, |&(_, color)| color);
+
+        let x = first_column + index as i64;
+        let level = (normalise(value, range) * (rows - 1).max(0) as f32).round() as i64;
+        let top = rows - 1 - level;
+        let bottom = if options.fill { rows - 1 } else { top };
+        for y in top..=bottom {
+            grid.set(x, y, color);
+        }
+    }
+
+    grid.to_pixels(options.position)
+}
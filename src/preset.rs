@@ -0,0 +1,146 @@
+//! Named [`LoadOptions`] presets stored as TOML or JSON.
+//!
+//! A preset file lets several tools share the same processing settings. In TOML it looks
+//! like this, with every field optional:
+//!
+//! ```toml
+//! [presets.sprite]
+//! width = 32
+//! height = 32
+//! stretch = true
+//!
+//! [presets.portrait]
+//! height = 48
+//! scale = 0.5
+//! ```
+//!
+//! The JSON form has the same structure: `{ "presets": { "sprite": { "width": 32 } } }`.
+
+use crate::LoadOptions;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+/// The file formats a [`Presets`] collection can be stored in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PresetFormat {
+    /// TOML, for `.toml` files.
+    Toml,
+    /// JSON, for `.json` files.
+    Json,
+}
+
+impl PresetFormat {
+    /// Picks the format from a file extension, ignoring case.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "toml" => Some(PresetFormat::Toml),
+            "json" => Some(PresetFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// A collection of named [`LoadOptions`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::load_image_with;
+/// use rael_img::preset::Presets;
+///
+/// let presets = Presets::load("./assets/presets.toml").unwrap();
+/// let sprite = presets.get("sprite").copied().unwrap_or_default();
+/// let pixels = load_image_with("./assets/hero.png", &sprite).unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Presets {
+    /// The presets by name, sorted so saved files are stable.
+    #[serde(default)]
+    pub presets: BTreeMap<String, LoadOptions>,
+}
+
+impl Presets {
+    /// Creates an empty collection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the preset called `name`.
+    pub fn get(&self, name: &str) -> Option<&LoadOptions> {
+        self.presets.get(name)
+    }
+
+    /// Adds or replaces the preset called `name`, returning the previous one.
+    pub fn insert(&mut self, name: impl Into<String>, options: LoadOptions) -> Option<LoadOptions> {
+        self.presets.insert(name.into(), options)
+    }
+
+    /// Removes the preset called `name`.
+    pub fn remove(&mut self, name: &str) -> Option<LoadOptions> {
+        self.presets.remove(name)
+    }
+
+    /// Returns the preset names in sorted order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.presets.keys().map(String::as_str)
+    }
+
+    /// Parses presets from a string in the given format.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is:
+    /// - `Ok(Presets)`: The parsed presets.
+    /// - `Err(io::Error)`: Of kind `InvalidData` if the text is not a valid preset file.
+    pub fn parse(text: &str, format: PresetFormat) -> io::Result<Self> {
+        match format {
+            PresetFormat::Toml => toml::from_str(text).map_err(invalid_data),
+            PresetFormat::Json => serde_json::from_str(text).map_err(invalid_data),
+        }
+    }
+
+    /// Serializes the presets in the given format.
+    pub fn to_string(&self, format: PresetFormat) -> io::Result<String> {
+        match format {
+            PresetFormat::Toml => toml::to_string_pretty(self).map_err(invalid_data),
+            PresetFormat::Json => serde_json::to_string_pretty(self).map_err(invalid_data),
+        }
+    }
+
+    /// Reads a preset file, choosing the format from its extension.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is:
+    /// - `Ok(Presets)`: The presets in the file.
+    /// - `Err(io::Error)`: If the file could not be read or parsed, or its extension is
+    ///   neither `.toml` nor `.json` (kind `Unsupported`).
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let format = format_of(path)?;
+        Self::parse(&std::fs::read_to_string(path)?, format)
+    }
+
+    /// Writes the presets to a file, choosing the format from its extension.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let text = self.to_string(format_of(path)?)?;
+        std::fs::write(path, text)
+    }
+}
+
+fn format_of(path: &Path) -> io::Result<PresetFormat> {
+    PresetFormat::from_path(path).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("unknown preset file extension: {}", path.display()),
+        )
+    })
+}
+
+fn invalid_data(error: impl std::error::Error + Send + Sync + 'static) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
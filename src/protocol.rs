@@ -0,0 +1,133 @@
+//! High-resolution output through terminal graphics protocols.
+//!
+//! The cell-based `rael` canvas shows one pixel per half cell. Terminals that implement a
+//! graphics protocol can show an image at its real pixel resolution instead. The encoders
+//! in this module turn a processed pixel list into the escape sequence for one protocol and
+//! report which cells the image covers, so an application can print the sequence on
+//! capable terminals and fall back to drawing the pixels on the canvas elsewhere.
+//!
+//! Every encoder also has a `_loaded` variant that takes a [`LoadedImage`] and keeps its
+//! whole box, so the same loaded image can go to the canvas or to any of the protocols.
+
+pub mod iterm2;
+pub mod kitty;
+#[cfg(feature = "sixel")]
+pub mod sixel;
+
+use crate::grid::{PixelGrid, bounds};
+use crate::loaded::LoadedImage;
+use crate::terminal::CellSize;
+use rael::Color;
+
+/// Where an encoded image lands on the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    /// The 0-indexed terminal column of the image's top-left corner.
+    pub column: u32,
+    /// The 0-indexed terminal row of the image's top-left corner.
+    pub row: u32,
+    /// The number of terminal columns the image covers.
+    pub columns: u32,
+    /// The number of terminal rows the image covers.
+    pub rows: u32,
+    /// The width of the encoded image in pixels.
+    pub pixel_width: u32,
+    /// The height of the encoded image in pixels.
+    pub pixel_height: u32,
+}
+
+/// An image encoded for a terminal graphics protocol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedImage {
+    /// The escape sequence to write to the terminal.
+    pub escape: String,
+    /// The cells covered by the image.
+    pub placement: Placement,
+}
+
+/// The pixel list reassembled into an RGBA buffer, plus its canvas origin.
+pub(crate) struct RgbaBuffer {
+    pub(crate) origin: (u32, u32),
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) data: Vec<u8>,
+}
+
+impl RgbaBuffer {
+    /// Cells without a pixel become fully transparent.
+    pub(crate) fn from_pixels(pixels: &[(u32, u32, Color)]) -> Self {
+        let origin = bounds(pixels).map_or((0, 0), |(x, y, _, _)| (x, y));
+        let grid = PixelGrid::from_pixels(pixels);
+        let mut data = Vec::with_capacity(grid.cells.len() * 4);
+        for cell in &grid.cells {
+            match cell {
+                Some(color) => data.extend_from_slice(&[color.r, color.g, color.b, 255]),
+                None => data.extend_from_slice(&[0, 0, 0, 0]),
+            }
+        }
+        Self {
+            origin,
+            width: grid.width,
+            height: grid.height,
+            data,
+        }
+    }
+
+    /// Keeps the whole box of the image, including transparent margins, at its position.
+    pub(crate) fn from_loaded(image: &LoadedImage) -> Self {
+        Self {
+            origin: image.position(),
+            width: image.width(),
+            height: image.height(),
+            data: image.to_rgba_image().into_raw(),
+        }
+    }
+
+    /// Computes the placement of the buffer. Pixel coordinates follow the `rael` canvas,
+    /// where every terminal row holds two pixel rows.
+    pub(crate) fn placement(
+        &self,
+        columns: Option<u32>,
+        rows: Option<u32>,
+        cell: CellSize,
+    ) -> Placement {
+        let cover = |pixels: u32, cell: u32| pixels.div_ceil(cell.max(1)).max(1);
+        Placement {
+            column: self.origin.0,
+            row: self.origin.1 / 2,
+            columns: columns.unwrap_or_else(|| cover(self.width, cell.width)),
+            rows: rows.unwrap_or_else(|| cover(self.height, cell.height)),
+            pixel_width: self.width,
+            pixel_height: self.height,
+        }
+    }
+}
+
+/// Returns the sequence that moves the cursor to a placement's top-left cell.
+pub(crate) fn cursor_to(placement: &Placement) -> String {
+    format!("\u{1b}[{};{}H", placement.row + 1, placement.column + 1)
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes bytes as standard, padded base64.
+pub(crate) fn base64_encode(bytes: &[u8]) -> String {
+    let mut output = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                output.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
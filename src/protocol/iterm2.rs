@@ -0,0 +1,132 @@
+//! The iTerm2 inline image protocol (`OSC 1337`).
+//!
+//! Supported by iTerm2, WezTerm, mintty and Konsole. The image is sent as a base64 PNG
+//! file and the terminal decodes and scales it itself.
+
+use super::{EncodedImage, RgbaBuffer, base64_encode, cursor_to};
+use crate::error::RaelImgError;
+use crate::loaded::LoadedImage;
+use crate::terminal::CellSize;
+use image::{ImageFormat, RgbaImage};
+use rael::Color;
+use std::io::Cursor;
+
+/// Options for [`to_iterm2`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Iterm2Options {
+    /// Scales the image to this many terminal columns. Defaults to the native size.
+    pub columns: Option<u32>,
+    /// Scales the image to this many terminal rows. Defaults to the native size.
+    pub rows: Option<u32>,
+    /// If `true` and both `columns` and `rows` are set, the terminal letterboxes the image
+    /// instead of stretching it.
+    pub preserve_aspect_ratio: bool,
+    /// If `true`, the escape starts by moving the cursor to the image's position.
+    pub move_cursor: bool,
+    /// The cell size used to compute the covered cells when no size is given.
+    pub cell_size: CellSize,
+}
+
+impl Default for Iterm2Options {
+    fn default() -> Self {
+        Self {
+            columns: None,
+            rows: None,
+            preserve_aspect_ratio: true,
+            move_cursor: true,
+            cell_size: CellSize::TYPICAL,
+        }
+    }
+}
+
+/// Encodes a pixel list as an iTerm2 inline image.
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(EncodedImage)`: The `OSC 1337` sequence and the cells the image covers.
+/// - `Err(RaelImgError)`: If the PNG encoding failed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::load_image;
+/// use rael_img::protocol::iterm2::{to_iterm2, Iterm2Options};
+///
+/// let pixels = load_image("./assets/cover.png", Some(320), None, (0, 0), false, 1.0).unwrap();
+/// let image = to_iterm2(&pixels, &Iterm2Options::default()).unwrap();
+/// print!("{}", image.escape);
+/// ```
+pub fn to_iterm2(
+    pixels: &[(u32, u32, Color)],
+    options: &Iterm2Options,
+) -> Result<EncodedImage, RaelImgError> {
+    encode(RgbaBuffer::from_pixels(pixels), options)
+}
+
+/// Encodes a loaded image as an iTerm2 inline image.
+///
+/// Unlike [`to_iterm2`], which crops to the pixels, the PNG keeps the size and position of
+/// the loaded image, so transparent margins still take up their space.
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(EncodedImage)`: The `OSC 1337` sequence and the cells the image covers.
+/// - `Err(RaelImgError)`: If the PNG encoding failed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::loaded::LoadedImage;
+/// use rael_img::protocol::iterm2::{to_iterm2_loaded, Iterm2Options};
+/// use rael_img::LoadOptions;
+///
+/// let options = LoadOptions {
+///     width: Some(320),
+///     alpha_threshold: 1,
+///     ..LoadOptions::default()
+/// };
+/// let cover = LoadedImage::load("./assets/cover.png", &options).unwrap();
+/// print!("{}", to_iterm2_loaded(&cover, &Iterm2Options::default()).unwrap().escape);
+/// ```
+pub fn to_iterm2_loaded(
+    image: &LoadedImage,
+    options: &Iterm2Options,
+) -> Result<EncodedImage, RaelImgError> {
+    encode(RgbaBuffer::from_loaded(image), options)
+}
+
+fn encode(buffer: RgbaBuffer, options: &Iterm2Options) -> Result<EncodedImage, RaelImgError> {
+    let placement = buffer.placement(options.columns, options.rows, options.cell_size);
+
+    let mut png = Vec::new();
+    if let Some(image) = RgbaImage::from_raw(buffer.width, buffer.height, buffer.data) {
+        image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+    }
+
+    let mut arguments = format!("inline=1;size={}", png.len());
+    if let Some(columns) = options.columns {
+        arguments.push_str(&format!(";width={columns}"));
+    }
+    if let Some(rows) = options.rows {
+        arguments.push_str(&format!(";height={rows}"));
+    }
+    arguments.push_str(&format!(
+        ";preserveAspectRatio={}",
+        u8::from(options.preserve_aspect_ratio)
+    ));
+
+    let mut escape = String::new();
+    if options.move_cursor {
+        escape.push_str(&cursor_to(&placement));
+    }
+    escape.push_str(&format!(
+        "\u{1b}]1337;File={arguments}:{}\u{7}",
+        base64_encode(&png)
+    ));
+
+    Ok(EncodedImage { escape, placement })
+}
@@ -0,0 +1,133 @@
+//! The kitty terminal graphics protocol.
+//!
+//! Supported by kitty, WezTerm, Konsole and ghostty. Images are sent as RGBA data in
+//! base64 chunks of at most 4096 bytes and displayed at the cursor position.
+
+use super::{EncodedImage, RgbaBuffer, base64_encode, cursor_to};
+use crate::loaded::LoadedImage;
+use crate::terminal::CellSize;
+use rael::Color;
+
+const CHUNK_SIZE: usize = 4096;
+
+/// Options for [`to_kitty`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct KittyOptions {
+    /// Scales the image to this many terminal columns. Defaults to the native size.
+    pub columns: Option<u32>,
+    /// Scales the image to this many terminal rows. Defaults to the native size.
+    pub rows: Option<u32>,
+    /// An image id, letting the application replace or delete the image later.
+    pub image_id: Option<u32>,
+    /// If `true`, the escape starts by moving the cursor to the image's position.
+    pub move_cursor: bool,
+    /// The cell size used to compute the covered cells when no size is given.
+    pub cell_size: CellSize,
+    /// The z-index of the placement relative to text. Negative values draw under text.
+    pub z_index: i32,
+}
+
+impl Default for KittyOptions {
+    fn default() -> Self {
+        Self {
+            columns: None,
+            rows: None,
+            image_id: None,
+            move_cursor: true,
+            cell_size: CellSize::TYPICAL,
+            z_index: 0,
+        }
+    }
+}
+
+/// Encodes a pixel list for the kitty graphics protocol.
+///
+/// The image is transmitted and displayed in one command (`a=T`) with terminal responses
+/// suppressed, so nothing is written back to the application's input. Coordinates inside
+/// the pixel list's bounding box that have no pixel are sent as transparent.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::load_image;
+/// use rael_img::protocol::kitty::{to_kitty, KittyOptions};
+///
+/// let pixels = load_image("./assets/cover.png", Some(320), None, (2, 2), false, 1.0).unwrap();
+/// let image = to_kitty(&pixels, &KittyOptions::default());
+/// print!("{}", image.escape);
+/// ```
+pub fn to_kitty(pixels: &[(u32, u32, Color)], options: &KittyOptions) -> EncodedImage {
+    encode(RgbaBuffer::from_pixels(pixels), options)
+}
+
+/// Encodes a loaded image for the kitty graphics protocol.
+///
+/// Unlike [`to_kitty`], which crops to the pixels, the transmitted image keeps the size
+/// and position of the loaded image, so transparent margins still take up their space.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::loaded::LoadedImage;
+/// use rael_img::protocol::kitty::{to_kitty_loaded, KittyOptions};
+/// use rael_img::LoadOptions;
+///
+/// let options = LoadOptions {
+///     width: Some(320),
+///     alpha_threshold: 1,
+///     ..LoadOptions::default()
+/// };
+/// let cover = LoadedImage::load("./assets/cover.png", &options).unwrap();
+/// print!("{}", to_kitty_loaded(&cover, &KittyOptions::default()).escape);
+/// ```
+pub fn to_kitty_loaded(image: &LoadedImage, options: &KittyOptions) -> EncodedImage {
+    encode(RgbaBuffer::from_loaded(image), options)
+}
+
+fn encode(buffer: RgbaBuffer, options: &KittyOptions) -> EncodedImage {
+    let placement = buffer.placement(options.columns, options.rows, options.cell_size);
+
+    let mut control = format!("a=T,f=32,q=2,s={},v={}", buffer.width, buffer.height);
+    if let Some(id) = options.image_id {
+        control.push_str(&format!(",i={id}"));
+    }
+    if let Some(columns) = options.columns {
+        control.push_str(&format!(",c={columns}"));
+    }
+    if let Some(rows) = options.rows {
+        control.push_str(&format!(",r={rows}"));
+    }
+    if options.z_index != 0 {
+        control.push_str(&format!(",z={}", options.z_index));
+    }
+
+    let mut escape = String::new();
+    if options.move_cursor {
+        escape.push_str(&cursor_to(&placement));
+    }
+
+    let payload = base64_encode(&buffer.data);
+    let chunks: Vec<&[u8]> = payload.as_bytes().chunks(CHUNK_SIZE).collect();
+    if chunks.is_empty() {
+        escape.push_str(&format!("\u{1b}_G{control};\u{1b}\\"));
+    }
+    for (index, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(index + 1 < chunks.len());
+        // Base64 output is ASCII, so every chunk boundary is a valid str boundary.
+        let chunk = std::str::from_utf8(chunk).unwrap_or_default();
+        if index == 0 {
+            escape.push_str(&format!("\u{1b}_G{control},m={more};{chunk}\u{1b}\\"));
+        } else {
+            escape.push_str(&format!("\u{1b}_Gm={more};{chunk}\u{1b}\\"));
+        }
+    }
+
+    EncodedImage { escape, placement }
+}
+
+/// Returns the sequence that deletes a previously displayed image by id.
+pub fn delete_kitty_image(image_id: u32) -> String {
+    format!("\u{1b}_Ga=d,d=I,i={image_id},q=2\u{1b}\\")
+}
@@ -0,0 +1,185 @@
+//! The DEC sixel graphics format.
+//!
+//! Supported by xterm (with `-ti vt340`), mlterm, foot, WezTerm and others. Sixel images
+//! are palette based, so the pixels are first quantized to at most 256 colors.
+
+use super::{EncodedImage, RgbaBuffer, cursor_to};
+use crate::loaded::LoadedImage;
+use crate::quantize::{median_cut, nearest_index};
+use crate::terminal::CellSize;
+use rael::Color;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Options for [`to_sixel`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SixelOptions {
+    /// The maximum palette size, clamped to `1..=256`. Many terminals only honour 16 or
+    /// 256 color registers.
+    pub max_colors: usize,
+    /// If `true`, the escape starts by moving the cursor to the image's position.
+    pub move_cursor: bool,
+    /// The cell size used to compute the cells covered by the image.
+    pub cell_size: CellSize,
+}
+
+impl Default for SixelOptions {
+    fn default() -> Self {
+        Self {
+            max_colors: 256,
+            move_cursor: true,
+            cell_size: CellSize::TYPICAL,
+        }
+    }
+}
+
+/// Encodes a pixel list as a sixel image.
+///
+/// The palette is chosen with median-cut quantization. Coordinates inside the pixel list's
+/// bounding box that have no pixel are left transparent.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::load_image;
+/// use rael_img::protocol::sixel::{to_sixel, SixelOptions};
+///
+/// let pixels = load_image("./assets/cover.png", Some(320), None, (0, 0), false, 1.0).unwrap();
+/// let image = to_sixel(&pixels, &SixelOptions::default());
+/// print!("{}", image.escape);
+/// ```
+pub fn to_sixel(pixels: &[(u32, u32, Color)], options: &SixelOptions) -> EncodedImage {
+    encode(RgbaBuffer::from_pixels(pixels), pixels, options)
+}
+
+/// Encodes a loaded image as a sixel image.
+///
+/// Unlike [`to_sixel`], which crops to the pixels, the sixel image keeps the size and
+/// position of the loaded image, so transparent margins still take up their space.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::loaded::LoadedImage;
+/// use rael_img::protocol::sixel::{to_sixel_loaded, SixelOptions};
+/// use rael_img::LoadOptions;
+///
+/// let options = LoadOptions {
+///     width: Some(320),
+///     alpha_threshold: 1,
+///     ..LoadOptions::default()
+/// };
+/// let cover = LoadedImage::load("./assets/cover.png", &options).unwrap();
+/// print!("{}", to_sixel_loaded(&cover, &SixelOptions::default()).escape);
+/// ```
+pub fn to_sixel_loaded(image: &LoadedImage, options: &SixelOptions) -> EncodedImage {
+    encode(RgbaBuffer::from_loaded(image), image.pixels(), options)
+}
+
+fn encode(
+    buffer: RgbaBuffer,
+    pixels: &[(u32, u32, Color)],
+    options: &SixelOptions,
+) -> EncodedImage {
+    let placement = buffer.placement(None, None, options.cell_size);
+    let palette = median_cut(pixels, options.max_colors.clamp(1, 256));
+
+    let mut lookup: HashMap<(u8, u8, u8), usize> = HashMap::new();
+    let indices: Vec<Option<usize>> = buffer
+        .data
+        .chunks_exact(4)
+        .map(|rgba| {
+            if rgba[3] == 0 || palette.is_empty() {
+                return None;
+            }
+            let key = (rgba[0], rgba[1], rgba[2]);
+            Some(*lookup.entry(key).or_insert_with(|| {
+                nearest_index(
+                    &palette,
+                    Color {
+                        r: key.0,
+                        g: key.1,
+                        b: key.2,
+                    },
+                )
+            }))
+        })
+        .collect();
+
+    let mut escape = String::new();
+    if options.move_cursor {
+        escape.push_str(&cursor_to(&placement));
+    }
+    // P2 = 1 keeps pixels that are never painted transparent.
+    escape.push_str("\u{1b}P0;1;0q");
+    let _ = write!(escape, "\"1;1;{};{}", buffer.width, buffer.height);
+    for (index, color) in palette.iter().enumerate() {
+        let percent = |v: u8| (u32::from(v) * 100 + 127) / 255;
+        let _ = write!(
+            escape,
+            "#{index};2;{};{};{}",
+            percent(color.r),
+            percent(color.g),
+            percent(color.b)
+        );
+    }
+
+    let width = buffer.width as usize;
+    let height = buffer.height as usize;
+    for band in (0..height).step_by(6) {
+        let rows = band..(band + 6).min(height);
+
+        let mut used: Vec<usize> = Vec::new();
+        for y in rows.clone() {
+            for index in indices[y * width..(y + 1) * width].iter().flatten() {
+                if !used.contains(index) {
+                    used.push(*index);
+                }
+            }
+        }
+
+        for (position, &color) in used.iter().enumerate() {
+            let _ = write!(escape, "#{color}");
+            let mut line = Vec::with_capacity(width);
+            for x in 0..width {
+                let mut bits = 0u8;
+                for (bit, y) in rows.clone().enumerate() {
+                    if indices[y * width + x] == Some(color) {
+                        bits |= 1 << bit;
+                    }
+                }
+                line.push(char::from(63 + bits));
+            }
+            while line.last() == Some(&'?') {
+                line.pop();
+            }
+            push_run_length(&mut escape, &line);
+            if position + 1 < used.len() {
+                escape.push('$');
+            }
+        }
+        escape.push('-');
+    }
+    escape.push_str("\u{1b}\\");
+
+    EncodedImage { escape, placement }
+}
+
+/// Appends sixel characters, collapsing runs with the `!count char` repeat introducer.
+fn push_run_length(output: &mut String, line: &[char]) {
+    let mut index = 0;
+    while index < line.len() {
+        let ch = line[index];
+        let run = line[index..].iter().take_while(|&&c| c == ch).count();
+        if run > 3 {
+            let _ = write!(output, "!{run}{ch}");
+        } else {
+            for _ in 0..run {
+                output.push(ch);
+            }
+        }
+        index += run;
+    }
+}
@@ -0,0 +1,266 @@
+//! Color quantization: reducing pixel colors to a limited palette.
+
+use rael::Color;
+use std::collections::HashMap;
+
+const fn rgb(r: u8, g: u8, b: u8) -> Color {
+    Color { r, g, b }
+}
+
+/// The 16 ANSI colors as rendered by xterm, in SGR order (index `i` is `30 + i` for
+/// `i < 8` and `90 + i - 8` otherwise).
+pub const ANSI_16: [Color; 16] = [
+    rgb(0, 0, 0),
+    rgb(205, 0, 0),
+    rgb(0, 205, 0),
+    rgb(205, 205, 0),
+    rgb(0, 0, 238),
+    rgb(205, 0, 205),
+    rgb(0, 205, 205),
+    rgb(229, 229, 229),
+    rgb(127, 127, 127),
+    rgb(255, 0, 0),
+    rgb(0, 255, 0),
+    rgb(255, 255, 0),
+    rgb(92, 92, 255),
+    rgb(255, 0, 255),
+    rgb(0, 255, 255),
+    rgb(255, 255, 255),
+];
+
+/// The 16 colors of the classic Windows console (`conhost` before the 2017 "Campbell"
+/// scheme), in the same SGR order as [`ANSI_16`].
+pub const WINDOWS_CONSOLE_16: [Color; 16] = [
+    rgb(0, 0, 0),
+    rgb(128, 0, 0),
+    rgb(0, 128, 0),
+    rgb(128, 128, 0),
+    rgb(0, 0, 128),
+    rgb(128, 0, 128),
+    rgb(0, 128, 128),
+    rgb(192, 192, 192),
+    rgb(128, 128, 128),
+    rgb(255, 0, 0),
+    rgb(0, 255, 0),
+    rgb(255, 255, 0),
+    rgb(0, 0, 255),
+    rgb(255, 0, 255),
+    rgb(0, 255, 255),
+    rgb(255, 255, 255),
+];
+
+/// The 256-color xterm palette: the 16 colors of [`ANSI_16`], a 6×6×6 color cube and a
+/// 24-step gray ramp, indexed like `38;5;n`.
+pub const XTERM_256: [Color; 256] = xterm_256();
+
+const fn xterm_256() -> [Color; 256] {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let mut palette = [rgb(0, 0, 0); 256];
+    let mut index = 0;
+    while index < 256 {
+        palette[index] = if index < 16 {
+            ANSI_16[index]
+        } else if index < 232 {
+            let cube = index - 16;
+            rgb(LEVELS[cube / 36], LEVELS[cube / 6 % 6], LEVELS[cube % 6])
+        } else {
+            let level = 8 + 10 * (index - 232) as u8;
+            rgb(level, level, level)
+        };
+        index += 1;
+    }
+    palette
+}
+
+/// Builds a palette of at most `max_colors` colors that represents `pixels` well, using the
+/// median-cut algorithm.
+///
+/// Colors are weighted by how often they occur. If the image has no more than `max_colors`
+/// distinct colors, exactly those colors are returned.
+pub fn median_cut(pixels: &[(u32, u32, Color)], max_colors: usize) -> Vec<Color> {
+    let mut counts: HashMap<(u8, u8, u8), u32> = HashMap::new();
+    for &(_, _, color) in pixels {
+        *counts.entry((color.r, color.g, color.b)).or_insert(0) += 1;
+    }
+    let mut colors: Vec<([u8; 3], u32)> = counts
+        .into_iter()
+        .map(|((r, g, b), count)| ([r, g, b], count))
+        .collect();
+    // HashMap iteration order is random; sort so the palette is deterministic.
+    colors.sort_unstable();
+
+    if max_colors == 0 {
+        return Vec::new();
+    }
+    if colors.len() <= max_colors {
+        return colors
+            .into_iter()
+            .map(|([r, g, b], _)| Color { r, g, b })
+            .collect();
+    }
+
+    let mut boxes = vec![colors];
+    while boxes.len() < max_colors {
+        let Some((index, channel)) = widest_box(&boxes) else {
+            break;
+        };
+        let mut entries = boxes.swap_remove(index);
+        entries.sort_by_key(|(color, _)| color[channel]);
+
+        let total: u64 = entries.iter().map(|&(_, count)| u64::from(count)).sum();
+        let mut running = 0u64;
+        let mut split = 1;
+        for (i, &(_, count)) in entries.iter().enumerate() {
+            running += u64::from(count);
+            if running * 2 >= total {
+                split = (i + 1).clamp(1, entries.len() - 1);
+                break;
+            }
+        }
+        let upper = entries.split_off(split);
+        boxes.push(entries);
+        boxes.push(upper);
+    }
+
+    boxes.iter().map(|entries| average(entries)).collect()
+}
+
+/// Returns the box with the largest channel range and that channel, if any box can be split.
+fn widest_box(boxes: &[Vec<([u8; 3], u32)>]) -> Option<(usize, usize)> {
+    let mut best = None;
+    let mut best_range = 0;
+    for (index, entries) in boxes.iter().enumerate() {
+        if entries.len() < 2 {
+            continue;
+        }
+        for channel in 0..3 {
+            let (min, max) = entries
+                .iter()
+                .fold((u8::MAX, u8::MIN), |(min, max), (c, _)| {
+                    (min.min(c[channel]), max.max(c[channel]))
+                });
+            let range = max - min;
+            if best.is_none() || range > best_range {
+                best = Some((index, channel));
+                best_range = range;
+            }
+        }
+    }
+    best
+}
+
+fn average(entries: &[([u8; 3], u32)]) -> Color {
+    let mut sums = [0u64; 3];
+    let mut total = 0u64;
+    for &(color, count) in entries {
+        for channel in 0..3 {
+            sums[channel] += u64::from(color[channel]) * u64::from(count);
+        }
+        total += u64::from(count);
+    }
+    let total = total.max(1);
+    Color {
+        r: ((sums[0] + total / 2) / total) as u8,
+        g: ((sums[1] + total / 2) / total) as u8,
+        b: ((sums[2] + total / 2) / total) as u8,
+    }
+}
+
+/// Returns the index of the palette entry closest to `color` (squared RGB distance).
+///
+/// # Panics
+///
+/// Panics if `palette` is empty.
+pub fn nearest_index(palette: &[Color], color: Color) -> usize {
+    let distance = |entry: &Color| {
+        let dr = i32::from(entry.r) - i32::from(color.r);
+        let dg = i32::from(entry.g) - i32::from(color.g);
+        let db = i32::from(entry.b) - i32::from(color.b);
+        dr * dr + dg * dg + db * db
+    };
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, entry)| distance(entry))
+        .map(|(index, _)| index)
+        .expect("palette must not be empty")
+}
+
+/// Maps every pixel to its nearest palette color.
+pub fn remap(pixels: &[(u32, u32, Color)], palette: &[Color]) -> Vec<(u32, u32, Color)> {
+    if palette.is_empty() {
+        return pixels.to_vec();
+    }
+    let mut cache: HashMap<(u8, u8, u8), Color> = HashMap::new();
+    pixels
+        .iter()
+        .map(|&(x, y, color)| {
+            let mapped = *cache
+                .entry((color.r, color.g, color.b))
+                .or_insert_with(|| palette[nearest_index(palette, color)]);
+            (x, y, mapped)
+        })
+        .collect()
+}
+
+/// Maps every pixel to a palette color with Floyd–Steinberg error diffusion.
+///
+/// The quantization error of each pixel is spread over its right and lower neighbours, which
+/// trades banding for fine noise. Missing pixels neither receive nor pass on any error.
+pub fn remap_dithered(pixels: &[(u32, u32, Color)], palette: &[Color]) -> Vec<(u32, u32, Color)> {
+    if palette.is_empty() {
+        return pixels.to_vec();
+    }
+    let Some((min_x, min_y, max_x, max_y)) = crate::grid::bounds(pixels) else {
+        return Vec::new();
+    };
+    let width = (max_x - min_x + 1) as usize;
+    let height = (max_y - min_y + 1) as usize;
+
+    let mut values: Vec<Option<[f32; 3]>> = vec![None; width * height];
+    for &(x, y, color) in pixels {
+        let index = (y - min_y) as usize * width + (x - min_x) as usize;
+        values[index] = Some([f32::from(color.r), f32::from(color.g), f32::from(color.b)]);
+    }
+
+    let mut result = Vec::with_capacity(pixels.len());
+    for y in 0..height {
+        for x in 0..width {
+            let Some(value) = values[y * width + x] else {
+                continue;
+            };
+            let clamped = value.map(|channel| channel.round().clamp(0.0, 255.0) as u8);
+            let chosen = palette[nearest_index(
+                palette,
+                Color {
+                    r: clamped[0],
+                    g: clamped[1],
+                    b: clamped[2],
+                },
+            )];
+            result.push((x as u32 + min_x, y as u32 + min_y, chosen));
+
+            let error = [
+                value[0] - f32::from(chosen.r),
+                value[1] - f32::from(chosen.g),
+                value[2] - f32::from(chosen.b),
+            ];
+            let mut spread = |dx: isize, dy: usize, weight: f32| {
+                let (nx, ny) = (x as isize + dx, y + dy);
+                if nx < 0 || nx as usize >= width || ny >= height {
+                    return;
+                }
+                if let Some(neighbour) = &mut values[ny * width + nx as usize] {
+                    for channel in 0..3 {
+                        neighbour[channel] += error[channel] * weight;
+                    }
+                }
+            };
+            spread(1, 0, 7.0 / 16.0);
+            spread(-1, 1, 3.0 / 16.0);
+            spread(0, 1, 5.0 / 16.0);
+            spread(1, 1, 1.0 / 16.0);
+        }
+    }
+    result
+}
@@ -0,0 +1,364 @@
+//! Recording canvas frames to video files (feature `record`).
+//!
+//! A [`Recorder`] takes one frame per call at a fixed frame rate, the same pixel lists or
+//! color grids the game draws onto its canvas, and encodes each one as it arrives:
+//!
+//! - `.apng` and `.png` files are written directly as animated PNG.
+//! - `.mp4` (H.264) and `.webm` (VP9) files are encoded by piping raw frames to an `ffmpeg`
+//!   executable, which has to be installed.
+
+use crate::error::RaelImgError;
+use crate::stderr::StderrDrain;
+use image::ImageError;
+use image::error::{EncodingError, ImageFormatHint};
+use rael::Color;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+/// A video container and codec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoFormat {
+    /// Animated PNG; lossless and needs no external tools.
+    Apng,
+    /// H.264 in MP4, through `ffmpeg`.
+    Mp4,
+    /// VP9 in WebM, through `ffmpeg`.
+    WebM,
+}
+
+impl VideoFormat {
+    /// Guesses the format from a file extension: `apng` or `png`, `mp4`, `webm`.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "apng" | "png" => Some(VideoFormat::Apng),
+            "mp4" => Some(VideoFormat::Mp4),
+            "webm" => Some(VideoFormat::WebM),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            VideoFormat::Apng => "apng",
+            VideoFormat::Mp4 => "mp4",
+            VideoFormat::WebM => "webm",
+        }
+    }
+}
+
+/// Options for [`Recorder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecorderOptions {
+    /// Frames per second.
+    pub fps: u16,
+    /// The size in video pixels of every canvas pixel, at least 1.
+    pub scale: u32,
+    /// The color of areas that a pixel list leaves empty, usually the canvas clear color.
+    pub background: Color,
+    /// The `ffmpeg` executable used for MP4 and WebM.
+    pub ffmpeg: PathBuf,
+}
+
+impl Default for RecorderOptions {
+    fn default() -> Self {
+        Self {
+            fps: 30,
+            scale: 4,
+            background: Color { r: 0, g: 0, b: 0 },
+            ffmpeg: PathBuf::from("ffmpeg"),
+        }
+    }
+}
+
+enum Sink {
+    Apng {
+        path: PathBuf,
+        writer: png::Writer<BufWriter<File>>,
+        frames: u32,
+    },
+    Ffmpeg {
+        child: Child,
+        stdin: BufWriter<ChildStdin>,
+        stderr: StderrDrain,
+    },
+}
+
+/// Captures successive canvas states and encodes them to a video file.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::record::{Recorder, RecorderOptions};
+///
+/// // A canvas of 80 × 24 cells has 80 × 48 pixels.
+/// let mut recorder = Recorder::create("./promo.mp4", 80, 48, RecorderOptions::default()).unwrap();
+/// # let frames: Vec<Vec<(u32, u32, rael::Color)>> = Vec::new();
+/// for pixels in &frames {
+///     recorder.add_frame(pixels).unwrap();
+/// }
+/// recorder.finish().unwrap();
+/// ```
+pub struct Recorder {
+    format: VideoFormat,
+    width: u32,
+    height: u32,
+    options: RecorderOptions,
+    sink: Sink,
+}
+
+impl Recorder {
+    /// Starts recording a `width` by `height` pixel canvas to `path`, in the format given by
+    /// its extension.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is:
+    /// - `Ok(Recorder)`: The recorder, ready for frames.
+    /// - `Err(RaelImgError)`: `RaelImgError::Unsupported` for unknown extensions, or an
+    ///   I/O error if `ffmpeg` could not be started.
+    pub fn create(
+        path: impl AsRef<Path>,
+        width: u32,
+        height: u32,
+        options: RecorderOptions,
+    ) -> Result<Self, RaelImgError> {
+        let path = path.as_ref();
+        let format = VideoFormat::from_path(path).ok_or_else(|| {
+            RaelImgError::Unsupported(format!("no video format for `{}`", path.display()))
+        })?;
+        Self::with_format(path, format, width, height, options)
+    }
+
+    /// Starts recording to `path` in the given format, whatever its extension.
+    pub fn with_format(
+        path: impl AsRef<Path>,
+        format: VideoFormat,
+        width: u32,
+        height: u32,
+        options: RecorderOptions,
+    ) -> Result<Self, RaelImgError> {
+        let options = RecorderOptions {
+            fps: options.fps.max(1),
+            scale: options.scale.max(1),
+            ..options
+        };
+        let path = path.as_ref();
+        let sink = match format {
+            VideoFormat::Apng => {
+                let (width, height) = (width * options.scale, height * options.scale);
+                let mut encoder =
+                    png::Encoder::new(BufWriter::new(File::create(path)?), width, height);
+                encoder.set_color(png::ColorType::Rgb);
+                encoder.set_depth(png::BitDepth::Eight);
+                let apng = |error: png::EncodingError| encoding(format, error.to_string());
+                // The frame count is not known yet; `finish` writes it over this one.
+                encoder.set_animated(u32::MAX, 0).map_err(apng)?;
+                encoder.set_frame_delay(1, options.fps).map_err(apng)?;
+                Sink::Apng {
+                    path: path.to_path_buf(),
+                    writer: encoder.write_header().map_err(apng)?,
+                    frames: 0,
+                }
+            }
+            VideoFormat::Mp4 | VideoFormat::WebM => {
+                let size = format!("{}x{}", width * options.scale, height * options.scale);
+                let mut command = Command::new(&options.ffmpeg);
+                command
+                    .args([
+                        "-y",
+                        "-loglevel",
+                        "error",
+                        "-f",
+                        "rawvideo",
+                        "-pix_fmt",
+                        "rgb24",
+                    ])
+                    .args(["-s", &size, "-r", &options.fps.to_string(), "-i", "-"]);
+                match format {
+                    // yuv420p, which players expect, needs even dimensions.
+                    VideoFormat::Mp4 => command.args([
+                        "-c:v",
+                        "libx264",
+                        "-vf",
+                        "pad=ceil(iw/2)*2:ceil(ih/2)*2",
+                        "-pix_fmt",
+                        "yuv420p",
+                    ]),
+                    _ => command.args(["-c:v", "libvpx-vp9", "-b:v", "0", "-crf", "32"]),
+                };
+                let mut child = command
+                    .arg(path)
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::piped())
+                    .spawn()
+                    .map_err(|error| {
+                        io::Error::new(
+                            error.kind(),
+                            format!("could not start {}: {error}", options.ffmpeg.display()),
+                        )
+                    })?;
+                let stdin = child.stdin.take().expect("stdin is piped");
+                let stderr = StderrDrain::spawn(child.stderr.take());
+                Sink::Ffmpeg {
+                    child,
+                    stdin: BufWriter::new(stdin),
+                    stderr,
+                }
+            }
+        };
+        Ok(Self {
+            format,
+            width,
+            height,
+            options,
+            sink,
+        })
+    }
+
+    /// Adds a frame from a pixel list.
+    ///
+    /// Pixels outside the canvas are ignored and missing ones take the background color.
+    pub fn add_frame(&mut self, pixels: &[(u32, u32, Color)]) -> Result<(), RaelImgError> {
+        let mut grid = vec![self.options.background; self.width as usize * self.height as usize];
+        for &(x, y, color) in pixels {
+            if x < self.width && y < self.height {
+                grid[y as usize * self.width as usize + x as usize] = color;
+            }
+        }
+        self.add_grid(&grid)
+    }
+
+    /// Adds a frame from a row-major grid of exactly `width` × `height` colors.
+    pub fn add_grid(&mut self, colors: &[Color]) -> Result<(), RaelImgError> {
+        if colors.len() != self.width as usize * self.height as usize {
+            return Err(RaelImgError::InvalidParameters(format!(
+                "expected {} colors for a {}x{} frame, got {}",
+                self.width as usize * self.height as usize,
+                self.width,
+                self.height,
+                colors.len()
+            )));
+        }
+        let scale = self.options.scale as usize;
+        let width = self.width as usize;
+        let mut frame = Vec::with_capacity(colors.len() * scale * scale * 3);
+        for row in colors.chunks_exact(width) {
+            let mut line = Vec::with_capacity(width * scale * 3);
+            for color in row {
+                for _ in 0..scale {
+                    line.extend_from_slice(&[color.r, color.g, color.b]);
+                }
+            }
+            for _ in 0..scale {
+                frame.extend_from_slice(&line);
+            }
+        }
+
+        match &mut self.sink {
+            Sink::Apng { writer, frames, .. } => {
+                writer
+                    .write_image_data(&frame)
+                    .map_err(|error| encoding(self.format, error.to_string()))?;
+                *frames += 1;
+            }
+            Sink::Ffmpeg { stdin, .. } => stdin.write_all(&frame)?,
+        }
+        Ok(())
+    }
+
+    /// Finishes encoding and closes the file.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is:
+    /// - `Ok(())`: If the video was written.
+    /// - `Err(RaelImgError)`: An encoding error if nothing was recorded, `ffmpeg` failed
+    ///   (with its message), or the APNG could not be written.
+    pub fn finish(self) -> Result<(), RaelImgError> {
+        let format = self.format;
+        match self.sink {
+            Sink::Apng {
+                path,
+                writer,
+                frames,
+            } => {
+                if frames == 0 {
+                    drop(writer);
+                    let _ = std::fs::remove_file(&path);
+                    return Err(encoding(format, "no frames were recorded".to_string()));
+                }
+                writer
+                    .finish()
+                    .map_err(|error| encoding(format, error.to_string()))?;
+                write_frame_count(&path, frames)
+                    .map_err(|error| encoding(format, error.to_string()))
+            }
+            Sink::Ffmpeg {
+                mut child,
+                mut stdin,
+                mut stderr,
+            } => {
+                stdin.flush()?;
+                drop(stdin);
+                let status = child.wait()?;
+                let message = stderr.finish();
+                if status.success() {
+                    Ok(())
+                } else {
+                    Err(encoding(
+                        format,
+                        format!("ffmpeg failed ({status}): {message}"),
+                    ))
+                }
+            }
+        }
+    }
+}
+
+/// Writes the frame count into the `acTL` chunk of a finished APNG, which was started with
+/// a placeholder because the count is only known at the end.
+fn write_frame_count(path: &Path, frames: u32) -> io::Result<()> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    let mut head = Vec::new();
+    Read::take(&file, 256).read_to_end(&mut head)?;
+    let start = head
+        .windows(4)
+        .position(|window| window == b"acTL")
+        .filter(|&start| start + 16 <= head.len())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "the APNG has no acTL chunk"))?;
+    // The chunk type and data, which the CRC covers: the frame count, then the play count.
+    let mut chunk = [0u8; 12];
+    chunk.copy_from_slice(&head[start..start + 12]);
+    chunk[4..8].copy_from_slice(&frames.to_be_bytes());
+    file.seek(SeekFrom::Start(start as u64 + 4))?;
+    file.write_all(&chunk[4..8])?;
+    file.seek(SeekFrom::Start(start as u64 + 12))?;
+    file.write_all(&crc32(&chunk).to_be_bytes())
+}
+
+/// The CRC-32 used by PNG chunks.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = u32::MAX;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn encoding(format: VideoFormat, message: String) -> RaelImgError {
+    RaelImgError::Image(ImageError::Encoding(EncodingError::new(
+        ImageFormatHint::Name(format.name().to_string()),
+        message,
+    )))
+}
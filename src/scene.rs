@@ -0,0 +1,305 @@
+//! A minimal scene tree for positioning images relative to each other.
+//!
+//! A [`Scene`] holds image and group nodes. Every node has a [`Transform`] relative to
+//! its parent and a visibility flag; hiding a node hides its whole subtree. A single
+//! [`Scene::render`] call flattens everything inside a [`Viewport`] into one pixel list.
+
+use crate::grid::PixelGrid;
+use rael::Color;
+
+/// Identifies a node inside a [`Scene`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+/// A 2D transform relative to the parent node.
+///
+/// Transforms compose from the root down: a child's offset is scaled by its parent's
+/// scale and then added to the parent's position, and scales multiply.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    /// The `(x, y)` offset from the parent's origin, in pixels.
+    pub offset: (i32, i32),
+    /// A uniform scaling factor. Images are sampled with nearest-neighbour filtering.
+    pub scale: f32,
+}
+
+impl Transform {
+    /// A transform that leaves its node exactly where its parent is.
+    pub const IDENTITY: Transform = Transform {
+        offset: (0, 0),
+        scale: 1.0,
+    };
+
+    /// Creates a transform that only translates.
+    pub fn offset(x: i32, y: i32) -> Self {
+        Self {
+            offset: (x, y),
+            scale: 1.0,
+        }
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// The region of the scene that is rendered.
+///
+/// Scene coordinates inside the viewport are mapped so that `(x, y)` lands on
+/// `(0, 0)` of the output. Pixels outside the viewport are dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    /// The scene x coordinate shown at the left edge.
+    pub x: i32,
+    /// The scene y coordinate shown at the top edge.
+    pub y: i32,
+    /// The number of visible columns.
+    pub width: u32,
+    /// The number of visible rows.
+    pub height: u32,
+}
+
+impl Viewport {
+    /// Creates a viewport anchored at the scene origin.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }
+    }
+}
+
+/// A single node in a [`Scene`].
+#[derive(Debug, Clone)]
+pub struct Node {
+    /// The node's transform relative to its parent.
+    pub transform: Transform,
+    /// Whether the node and its children are drawn.
+    pub visible: bool,
+    /// The draw priority of the node. Higher values are drawn on top; nodes with the
+    /// same z-index are drawn in the order they were added.
+    pub z_index: i32,
+    parent: Option<NodeId>,
+    image: Option<PixelGrid>,
+}
+
+impl Node {
+    /// Returns the parent of this node, or `None` for root nodes.
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    /// Returns `true` if this node carries an image, `false` for pure group nodes.
+    pub fn has_image(&self) -> bool {
+        self.image.is_some()
+    }
+}
+
+/// A tree of image and group nodes.
+///
+/// Nodes are drawn by ascending [`Node::z_index`]. Ties are broken by insertion order, so
+/// with the default z-index of `0` later nodes cover earlier ones.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::load_image;
+/// use rael_img::scene::{Scene, Transform, Viewport};
+///
+/// let panel = load_image("./assets/panel.png", None, None, (0, 0), false, 1.0).unwrap();
+/// let icon = load_image("./assets/icon.png", None, None, (0, 0), false, 1.0).unwrap();
+///
+/// let mut scene = Scene::new();
+/// let menu = scene.add_group(None, Transform::offset(10, 4));
+/// scene.add_image(Some(menu), &panel, Transform::IDENTITY);
+/// scene.add_image(Some(menu), &icon, Transform::offset(2, 2));
+///
+/// let pixels = scene.render(Viewport::new(80, 48));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Scene {
+    nodes: Vec<Node>,
+}
+
+impl Scene {
+    /// Creates an empty scene.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a node without an image, used to move or hide several children at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `parent` does not belong to this scene.
+    pub fn add_group(&mut self, parent: Option<NodeId>, transform: Transform) -> NodeId {
+        self.push(parent, transform, None)
+    }
+
+    /// Adds an image node built from a pixel list.
+    ///
+    /// The pixel list is normalised so that its top-left corner becomes the node's origin.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `parent` does not belong to this scene.
+    pub fn add_image(
+        &mut self,
+        parent: Option<NodeId>,
+        pixels: &[(u32, u32, Color)],
+        transform: Transform,
+    ) -> NodeId {
+        self.push(parent, transform, Some(PixelGrid::from_pixels(pixels)))
+    }
+
+    fn push(
+        &mut self,
+        parent: Option<NodeId>,
+        transform: Transform,
+        image: Option<PixelGrid>,
+    ) -> NodeId {
+        if let Some(NodeId(index)) = parent {
+            assert!(index < self.nodes.len(), "parent node does not exist");
+        }
+        self.nodes.push(Node {
+            transform,
+            visible: true,
+            z_index: 0,
+            parent,
+            image,
+        });
+        NodeId(self.nodes.len() - 1)
+    }
+
+    /// Returns a node by id.
+    pub fn node(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.get(id.0)
+    }
+
+    /// Returns a mutable node by id, for changing its transform or visibility.
+    pub fn node_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        self.nodes.get_mut(id.0)
+    }
+
+    /// Shows or hides a node and its subtree.
+    pub fn set_visible(&mut self, id: NodeId, visible: bool) {
+        if let Some(node) = self.nodes.get_mut(id.0) {
+            node.visible = visible;
+        }
+    }
+
+    /// Replaces a node's transform.
+    pub fn set_transform(&mut self, id: NodeId, transform: Transform) {
+        if let Some(node) = self.nodes.get_mut(id.0) {
+            node.transform = transform;
+        }
+    }
+
+    /// Sets a node's draw priority.
+    pub fn set_z_index(&mut self, id: NodeId, z_index: i32) {
+        if let Some(node) = self.nodes.get_mut(id.0) {
+            node.z_index = z_index;
+        }
+    }
+
+    /// Returns the number of nodes in the scene.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the scene has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Computes the absolute position, scale and effective visibility of every node.
+    ///
+    /// Parents are always added before their children, so one forward pass suffices.
+    fn resolve(&self) -> Vec<((f32, f32), f32, bool)> {
+        let mut resolved: Vec<((f32, f32), f32, bool)> = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            let local = node.transform;
+            let world = match node.parent {
+                Some(NodeId(parent)) => {
+                    let (position, scale, visible) = resolved[parent];
+                    (
+                        (
+                            position.0 + local.offset.0 as f32 * scale,
+                            position.1 + local.offset.1 as f32 * scale,
+                        ),
+                        scale * local.scale,
+                        visible && node.visible,
+                    )
+                }
+                None => (
+                    (local.offset.0 as f32, local.offset.1 as f32),
+                    local.scale,
+                    node.visible,
+                ),
+            };
+            resolved.push(world);
+        }
+        resolved
+    }
+
+    /// Renders every visible image node inside the viewport.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `(x, y, color)` tuples relative to the viewport's top-left corner, with
+    /// at most one entry per coordinate, in row-major order.
+    pub fn render(&self, viewport: Viewport) -> Vec<(u32, u32, Color)> {
+        let mut target = PixelGrid::new(viewport.width, viewport.height);
+        let resolved = self.resolve();
+
+        let mut order: Vec<usize> = (0..self.nodes.len()).collect();
+        order.sort_by_key(|&index| self.nodes[index].z_index);
+
+        for index in order {
+            let node = &self.nodes[index];
+            let (position, scale, visible) = resolved[index];
+            let Some(image) = &node.image else {
+                continue;
+            };
+            if !visible || image.is_empty() || scale <= 0.0 {
+                continue;
+            }
+            draw_grid(&mut target, viewport, image, position, scale);
+        }
+
+        target.to_pixels((0, 0))
+    }
+}
+
+/// Draws a grid scaled by `scale` at `position` (scene coordinates) into the target grid.
+fn draw_grid(
+    target: &mut PixelGrid,
+    viewport: Viewport,
+    image: &PixelGrid,
+    position: (f32, f32),
+    scale: f32,
+) {
+    let left = position.0.round() as i64 - i64::from(viewport.x);
+    let top = position.1.round() as i64 - i64::from(viewport.y);
+    let width = (image.width as f32 * scale).round() as i64;
+    let height = (image.height as f32 * scale).round() as i64;
+
+    let x_start = left.max(0);
+    let y_start = top.max(0);
+    let x_end = (left + width).min(i64::from(viewport.width));
+    let y_end = (top + height).min(i64::from(viewport.height));
+
+    for y in y_start..y_end {
+        let source_y = (((y - top) as f32 + 0.5) / scale) as u32;
+        for x in x_start..x_end {
+            let source_x = (((x - left) as f32 + 0.5) / scale) as u32;
+            if let Some(color) = image.get(source_x, source_y) {
+                target.set(x, y, color);
+            }
+        }
+    }
+}
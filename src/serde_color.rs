@@ -0,0 +1,131 @@
+//! Serialization of `rael::Color` as a `"#rrggbb"` string, for `#[serde(with)]`.
+//!
+//! `rael::Color` does not implement the serde traits itself. Pixel lists are written as
+//! sequences of `[x, y, "#rrggbb"]` triples.
+
+use rael::Color;
+use serde::{Deserialize, Deserializer, Serializer};
+
+pub(crate) fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b))
+}
+
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    parse(&text).ok_or_else(|| {
+        serde::de::Error::invalid_value(
+            serde::de::Unexpected::Str(&text),
+            &"a color like \"#ff8800\"",
+        )
+    })
+}
+
+/// The same for `Option<Color>`, with `None` as a missing value.
+pub(crate) mod option {
+    use rael::Color;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(
+        color: &Option<Color>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match color {
+            Some(color) => super::serialize(color, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Color>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|text| {
+                super::parse(&text).ok_or_else(|| {
+                    serde::de::Error::invalid_value(
+                        serde::de::Unexpected::Str(&text),
+                        &"a color like \"#ff8800\"",
+                    )
+                })
+            })
+            .transpose()
+    }
+}
+
+/// The same for `Vec<Color>`.
+pub(crate) mod list {
+    use super::Hex;
+    use rael::Color;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(
+        colors: &[Color],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(colors.iter().map(|&color| Hex(color)))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Color>, D::Error> {
+        let colors = Vec::<Hex>::deserialize(deserializer)?;
+        Ok(colors.into_iter().map(|Hex(color)| color).collect())
+    }
+}
+
+/// The same for pixel lists, `Vec<(u32, u32, Color)>`.
+pub(crate) mod pixels {
+    use super::Hex;
+    use rael::Color;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(
+        pixels: &[(u32, u32, Color)],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(pixels.iter().map(|&(x, y, color)| (x, y, Hex(color))))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<(u32, u32, Color)>, D::Error> {
+        let pixels = Vec::<(u32, u32, Hex)>::deserialize(deserializer)?;
+        Ok(pixels
+            .into_iter()
+            .map(|(x, y, Hex(color))| (x, y, color))
+            .collect())
+    }
+}
+
+/// A pixel list that serializes like [`pixels`], for pixel lists inside other types.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub(crate) struct Pixels(#[serde(with = "pixels")] pub(crate) Vec<(u32, u32, Color)>);
+
+/// A color that serializes as a `"#rrggbb"` string, for colors inside other types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Hex(pub(crate) Color);
+
+impl serde::Serialize for Hex {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Hex {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize(deserializer).map(Hex)
+    }
+}
+
+fn parse(text: &str) -> Option<Color> {
+    let hex = text.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |range: std::ops::Range<usize>| u8::from_str_radix(&hex[range], 16).ok();
+    Some(Color {
+        r: channel(0..2)?,
+        g: channel(2..4)?,
+        b: channel(4..6)?,
+    })
+}
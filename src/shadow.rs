@@ -0,0 +1,99 @@
+//! Drop shadows under transparent sprites.
+//!
+//! [`LoadOptions::shadow`] places a copy of the silhouette of a transparent image, filled
+//! with one color, at an offset beneath the image, so cards and sprites stand out from the
+//! canvas behind them. The shadow is as transparent as the image is, scaled by its opacity;
+//! like every semi-transparent pixel, it is blended over
+//! [`LoadOptions::background`] or the checkerboard, or keeps its alpha with
+//! [`load_image_rgba`](crate::load_image_rgba). Without any of them, the shadow is drawn in
+//! its color at full strength.
+//!
+//! The image grows by the offset on the side the shadow falls; when it falls up or left, the
+//! result starts that far above or left of the position. Images without an alpha channel
+//! are left as they are.
+//!
+//! [`LoadOptions::shadow`]: crate::LoadOptions::shadow
+//! [`LoadOptions::background`]: crate::LoadOptions::background
+
+use image::{DynamicImage, Rgba, RgbaImage};
+use rael::Color;
+
+/// A shadow cast by the opaque part of an image.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael::Color;
+/// use rael_img::shadow::Shadow;
+/// use rael_img::{load_image_with, LoadOptions};
+///
+/// let options = LoadOptions {
+///     shadow: Some(Shadow::new((1, 1), Color { r: 0, g: 0, b: 0 }, 0.6)),
+///     background: Some(Color { r: 40, g: 44, b: 52 }),
+///     ..LoadOptions::default()
+/// };
+/// let card = load_image_with("./assets/card.png", &options).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Shadow {
+    /// How far the shadow falls from the image, in pixels, as `(x, y)`. Positive values
+    /// move it right and down.
+    pub offset: (i32, i32),
+    /// The color of the shadow.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_color"))]
+    pub color: Color,
+    /// How opaque the shadow is, from `0.0` (invisible) to `1.0` (as opaque as the image).
+    pub opacity: f32,
+}
+
+impl Shadow {
+    /// Creates a shadow at `offset` in `color` with the given opacity.
+    pub const fn new(offset: (i32, i32), color: Color, opacity: f32) -> Self {
+        Self {
+            offset,
+            color,
+            opacity,
+        }
+    }
+
+    /// Returns how far the top-left pixel of the image moves into the result of
+    /// [`apply`](Shadow::apply), as `(x, y)`: the part of the offset that points up or left.
+    pub fn origin(&self) -> (u32, u32) {
+        (
+            self.offset.0.min(0).unsigned_abs(),
+            self.offset.1.min(0).unsigned_abs(),
+        )
+    }
+
+    /// Returns the image with the shadow beneath it, cast by every pixel with an alpha of at
+    /// least `threshold`, or at least 1 if `threshold` is 0.
+    ///
+    /// The result is wider and taller than `image` by the offset; the top-left pixel of
+    /// `image` lands at [`origin`](Shadow::origin). The image is composited over the shadow,
+    /// so its semi-transparent edges take on the shadow color.
+    pub fn apply(&self, image: &DynamicImage, threshold: u8) -> DynamicImage {
+        let threshold = threshold.max(1);
+        let source = image.to_rgba8();
+        let (dx, dy) = (self.offset.0.unsigned_abs(), self.offset.1.unsigned_abs());
+        let mut result = RgbaImage::new(source.width() + dx, source.height() + dy);
+
+        let (left, top) = self.origin();
+        let (shadow_left, shadow_top) = (
+            i64::from(left) + i64::from(self.offset.0),
+            i64::from(top) + i64::from(self.offset.1),
+        );
+        let opacity = self.opacity.clamp(0.0, 1.0);
+        let [r, g, b] = [self.color.r, self.color.g, self.color.b];
+        for (x, y, pixel) in source.enumerate_pixels() {
+            if pixel[3] < threshold {
+                continue;
+            }
+            let alpha = (f32::from(pixel[3]) * opacity).round() as u8;
+            let (sx, sy) = (shadow_left + i64::from(x), shadow_top + i64::from(y));
+            result.put_pixel(sx as u32, sy as u32, Rgba([r, g, b, alpha]));
+        }
+        image::imageops::overlay(&mut result, &source, i64::from(left), i64::from(top));
+        DynamicImage::ImageRgba8(result)
+    }
+}
@@ -0,0 +1,125 @@
+//! Target sizes relative to the terminal.
+//!
+//! Fixed pixel sizes look wrong as soon as the terminal is resized. A [`Size`] can instead
+//! be a share of the terminal or the whole of it, resolved against the terminal's current
+//! size every time options are built with [`LoadOptions::with_size`].
+//!
+//! Sizes are measured on the canvas, where one terminal cell holds two pixels stacked
+//! vertically. Character cells are about twice as tall as they are wide, so canvas pixels
+//! come out square and images keep their aspect ratio: a terminal of 80 × 24 cells is a
+//! canvas of 80 × 48 pixels.
+
+use crate::LoadOptions;
+use std::fmt;
+use std::str::FromStr;
+
+/// The terminal size assumed when it cannot be queried, such as when output is piped.
+pub const FALLBACK_TERMINAL: (u16, u16) = (80, 24);
+
+/// A target width or height.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Size {
+    /// No size; it follows from the other dimension and the aspect ratio.
+    #[default]
+    Auto,
+    /// An exact number of canvas pixels.
+    Pixels(u32),
+    /// A percentage of the terminal's extent in this dimension.
+    Percent(f32),
+    /// The terminal's whole extent in this dimension.
+    FitTerminal,
+}
+
+impl Size {
+    /// Returns the number of pixels this size stands for on a canvas that is `available`
+    /// pixels long in its dimension, or `None` for [`Size::Auto`]. Relative sizes are at
+    /// least 1.
+    pub fn resolve(self, available: u32) -> Option<u32> {
+        match self {
+            Size::Auto => None,
+            Size::Pixels(pixels) => Some(pixels),
+            Size::Percent(percent) => {
+                let pixels = f64::from(available) * f64::from(percent.max(0.0)) / 100.0;
+                Some((pixels.round() as u32).max(1))
+            }
+            Size::FitTerminal => Some(available.max(1)),
+        }
+    }
+}
+
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Size::Auto => f.write_str("auto"),
+            Size::Pixels(pixels) => write!(f, "{pixels}"),
+            Size::Percent(percent) => write!(f, "{percent}%"),
+            Size::FitTerminal => f.write_str("fit"),
+        }
+    }
+}
+
+/// Parses `auto`, `fit`, a number of pixels such as `120`, or a percentage such as `50%`.
+impl FromStr for Size {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        match text {
+            "auto" => return Ok(Size::Auto),
+            "fit" => return Ok(Size::FitTerminal),
+            _ => {}
+        }
+        if let Some(percent) = text.strip_suffix('%') {
+            return match percent.trim().parse::<f32>() {
+                Ok(percent) if percent.is_finite() && percent >= 0.0 => Ok(Size::Percent(percent)),
+                _ => Err(format!("invalid percentage `{text}`")),
+            };
+        }
+        text.parse()
+            .map(Size::Pixels)
+            .map_err(|_| format!("expected pixels, a percentage, `fit` or `auto`, got `{text}`"))
+    }
+}
+
+/// Returns the canvas size of the terminal in pixels: its columns, and twice its rows.
+///
+/// Falls back to [`FALLBACK_TERMINAL`] if the terminal cannot be queried.
+pub fn terminal_canvas_size() -> (u32, u32) {
+    let (columns, rows) = crossterm::terminal::size().unwrap_or(FALLBACK_TERMINAL);
+    (u32::from(columns), u32::from(rows) * 2)
+}
+
+impl LoadOptions {
+    /// Returns these options with `width` and `height` resolved against the current size
+    /// of the terminal.
+    ///
+    /// The sizes are resolved once; build the options again after the terminal is resized.
+    /// Unless [`stretch`](LoadOptions::stretch) is set, the image keeps its aspect ratio
+    /// and fits inside both sizes.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rael_img::size::Size;
+    /// use rael_img::{load_image_with, LoadOptions};
+    ///
+    /// // Half as wide as the terminal, and never taller than it.
+    /// let options = LoadOptions::default().with_size(Size::Percent(50.0), Size::FitTerminal);
+    /// let pixels = load_image_with("./assets/photo.jpg", &options).unwrap();
+    /// ```
+    pub fn with_size(self, width: Size, height: Size) -> Self {
+        self.with_size_in(width, height, terminal_canvas_size())
+    }
+
+    /// Returns these options with `width` and `height` resolved against a canvas of
+    /// `canvas` pixels, such as a part of the screen.
+    pub fn with_size_in(self, width: Size, height: Size, canvas: (u32, u32)) -> Self {
+        Self {
+            width: width.resolve(canvas.0),
+            height: height.resolve(canvas.1),
+            ..self
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! Sprite sheets: many frames in one image file.
+//!
+//! [`SpriteSheet`] decodes a sheet once and cuts it into tiles of a fixed size, read left
+//! to right and top to bottom. Every tile is then processed like a separate image: resized
+//! to the size in the [`LoadOptions`] and offset by its position, so each frame can be drawn
+//! in place of the others.
+
+use crate::LoadOptions;
+use crate::animation::Animation;
+use crate::decode::file_decoder;
+use crate::error::RaelImgError;
+use crate::loaded::LoadedImage;
+use crate::pipeline;
+use image::DynamicImage;
+use std::time::Duration;
+
+/// How a sheet is divided into tiles, in pixels of the source image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SheetGrid {
+    /// The width of each tile.
+    pub tile_width: u32,
+    /// The height of each tile.
+    pub tile_height: u32,
+    /// The border around all tiles.
+    pub margin: u32,
+    /// The gap between neighbouring tiles.
+    pub spacing: u32,
+}
+
+impl SheetGrid {
+    /// Creates a grid of tightly packed tiles.
+    pub const fn new(tile_width: u32, tile_height: u32) -> Self {
+        Self {
+            tile_width,
+            tile_height,
+            margin: 0,
+            spacing: 0,
+        }
+    }
+
+    /// Returns how many whole tiles fit into a sheet of the given size, as `(columns, rows)`.
+    pub fn tiles(&self, width: u32, height: u32) -> (u32, u32) {
+        let count = |length: u32, tile: u32| {
+            if tile == 0 {
+                return 0;
+            }
+            let usable = length.saturating_sub(2 * self.margin);
+            (usable + self.spacing) / (tile + self.spacing)
+        };
+        (
+            count(width, self.tile_width),
+            count(height, self.tile_height),
+        )
+    }
+}
+
+/// The processed frames of a sprite sheet.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::sprite::{SheetGrid, SpriteSheet};
+/// use rael_img::LoadOptions;
+///
+/// let options = LoadOptions {
+///     position: (10, 4),
+///     ..LoadOptions::default()
+/// };
+/// let hero = SpriteSheet::load("./assets/hero.png", SheetGrid::new(16, 16), &options).unwrap();
+/// let standing = hero.frame(0).unwrap();
+/// let walking = hero.frame_at(1, 2).unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpriteSheet {
+    columns: u32,
+    rows: u32,
+    frames: Vec<LoadedImage>,
+}
+
+impl SpriteSheet {
+    /// Loads a sheet and processes every tile.
+    ///
+    /// Partial tiles at the right and bottom edges are left out.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is:
+    /// - `Ok(SpriteSheet)`: The frames.
+    /// - `Err(RaelImgError)`: If the image could not be read or decoded.
+    pub fn load(path: &str, grid: SheetGrid, options: &LoadOptions) -> Result<Self, RaelImgError> {
+        let decoded = pipeline::decode_using(&std::fs::read(path)?, options, &file_decoder(path))?;
+        Ok(Self::from_image(&decoded.image, grid, options))
+    }
+
+    /// Cuts a decoded sheet into processed frames.
+    pub fn from_image(sheet: &DynamicImage, grid: SheetGrid, options: &LoadOptions) -> Self {
+        let (columns, rows) = grid.tiles(sheet.width(), sheet.height());
+        let mut frames = Vec::with_capacity((columns * rows) as usize);
+        for row in 0..rows {
+            for column in 0..columns {
+                let x = grid.margin + column * (grid.tile_width + grid.spacing);
+                let y = grid.margin + row * (grid.tile_height + grid.spacing);
+                let tile = sheet.crop_imm(x, y, grid.tile_width, grid.tile_height);
+                frames.push(LoadedImage::from_image(&tile, options));
+            }
+        }
+        Self {
+            columns,
+            rows,
+            frames,
+        }
+    }
+
+    /// Returns the number of tile columns.
+    pub fn columns(&self) -> u32 {
+        self.columns
+    }
+
+    /// Returns the number of tile rows.
+    pub fn rows(&self) -> u32 {
+        self.rows
+    }
+
+    /// Returns the number of frames.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns `true` if the sheet is smaller than one tile.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Returns the frame at `index`, counting left to right and top to bottom.
+    pub fn frame(&self, index: usize) -> Option<&LoadedImage> {
+        self.frames.get(index)
+    }
+
+    /// Returns the frame in the given tile column and row.
+    pub fn frame_at(&self, column: u32, row: u32) -> Option<&LoadedImage> {
+        if column >= self.columns {
+            return None;
+        }
+        self.frames.get((row * self.columns + column) as usize)
+    }
+
+    /// Returns every frame in order.
+    pub fn frames(&self) -> &[LoadedImage] {
+        &self.frames
+    }
+
+    /// Turns frames into a looping animation with the same delay for each.
+    ///
+    /// Returns `None` if an index is out of range.
+    pub fn animation(&self, indices: &[usize], delay: Duration) -> Option<Animation> {
+        let frames = indices
+            .iter()
+            .map(|&index| self.frame(index).map(|frame| frame.pixels().to_vec()))
+            .collect::<Option<Vec<_>>>()?;
+        Animation::new(frames, vec![delay; indices.len()])
+    }
+}
+
+/// Loads a sheet of `tile_width` by `tile_height` tiles; see [`SpriteSheet::load`].
+pub fn load_sprite_sheet(
+    path: &str,
+    tile_width: u32,
+    tile_height: u32,
+    options: &LoadOptions,
+) -> Result<SpriteSheet, RaelImgError> {
+    SpriteSheet::load(path, SheetGrid::new(tile_width, tile_height), options)
+}
@@ -0,0 +1,40 @@
+//! Collecting the error output of the command-line tools run by the `video` and `record`
+//! features.
+
+use std::io::Read;
+use std::process::ChildStderr;
+use std::thread::JoinHandle;
+
+/// How much error output is kept. The rest is read and thrown away.
+const MAX_KEPT: usize = 64 * 1024;
+
+/// Reads the error output of a child process on its own thread.
+///
+/// A child that writes more than the pipe buffer holds would otherwise block until
+/// someone reads it, and with it the parent waiting for its regular output.
+#[derive(Debug)]
+pub(crate) struct StderrDrain(Option<JoinHandle<String>>);
+
+impl StderrDrain {
+    pub(crate) fn spawn(stderr: Option<ChildStderr>) -> Self {
+        Self(stderr.map(|mut stderr| {
+            std::thread::spawn(move || {
+                let mut kept = Vec::new();
+                let mut chunk = [0u8; 4096];
+                while let Ok(read @ 1..) = stderr.read(&mut chunk) {
+                    let room = MAX_KEPT.saturating_sub(kept.len());
+                    kept.extend_from_slice(&chunk[..read.min(room)]);
+                }
+                String::from_utf8_lossy(&kept).trim().to_string()
+            })
+        }))
+    }
+
+    /// Waits until the child closes its error output and returns what it wrote.
+    pub(crate) fn finish(&mut self) -> String {
+        self.0
+            .take()
+            .and_then(|thread| thread.join().ok())
+            .unwrap_or_default()
+    }
+}
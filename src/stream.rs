@@ -0,0 +1,200 @@
+//! Pixels produced one at a time.
+//!
+//! The loading functions collect the result into a `Vec<(u32, u32, Color)>`, 16 bytes per
+//! pixel on top of the decoded image. For large images drawn straight onto a canvas, a
+//! [`PixelStream`] produces the same pixels in the same order from the resized image instead,
+//! converting each only when it is asked for.
+//!
+//! The finishing steps that need the whole image at once (the checkerboard, the outline and
+//! shadow, readability tuning, dithering, tiling and the debug overlay) still build the list
+//! first; the stream then hands out its pixels.
+
+use crate::LoadOptions;
+use crate::checkerboard;
+use crate::decode::{PartialImage, file_decoder};
+use crate::effects::ColorMode;
+use crate::error::RaelImgError;
+use crate::pipeline::{self, Placement};
+use image::{DynamicImage, GenericImageView};
+use rael::Color;
+use std::borrow::Cow;
+
+/// An iterator over the processed pixels of an image, in row-major order.
+///
+/// Returned by [`load_image_iter`] and [`process_image_iter`].
+#[derive(Debug)]
+pub struct PixelStream<'a> {
+    inner: Inner<'a>,
+}
+
+#[derive(Debug)]
+enum Inner<'a> {
+    Image(Box<ImagePixels<'a>>),
+    Buffered(std::vec::IntoIter<(u32, u32, Color)>),
+}
+
+#[derive(Debug)]
+struct ImagePixels<'a> {
+    image: Cow<'a, DynamicImage>,
+    options: LoadOptions,
+    placement: Placement,
+    /// The alpha below which pixels are skipped, or `None` if all are kept.
+    cutoff: Option<u8>,
+    x: u32,
+    y: u32,
+}
+
+impl<'a> PixelStream<'a> {
+    fn new(image: Cow<'a, DynamicImage>, options: &LoadOptions) -> Self {
+        let alpha = image.color().has_alpha();
+        let decorated = options.outline.is_some() || options.shadow.is_some();
+        let whole = ((options.checkerboard.is_some() || decorated) && alpha)
+            || options.optimize
+            || options.dither.is_some()
+            || options.tile.is_some()
+            || options.debug;
+        if whole {
+            let pixels = pipeline::finish_pixels(&image, options);
+            return Self {
+                inner: Inner::Buffered(pixels.into_iter()),
+            };
+        }
+        let image = match image {
+            Cow::Borrowed(image) => options.detail.apply(image),
+            Cow::Owned(image) if !options.detail.is_none() => {
+                Cow::Owned(options.detail.apply(&image).into_owned())
+            }
+            image => image,
+        };
+        let cutoff = (alpha && (options.background.is_some() || options.alpha_cutoff() > 0))
+            .then(|| options.alpha_cutoff());
+        Self {
+            inner: Inner::Image(Box::new(ImagePixels {
+                image,
+                options: *options,
+                placement: Placement::new(options),
+                cutoff,
+                x: 0,
+                y: 0,
+            })),
+        }
+    }
+}
+
+impl ImagePixels<'_> {
+    /// Finishes the pixel at `(x, y)` of the image, or returns `None` if it is left out.
+    fn finish(&self, x: u32, y: u32) -> Option<(u32, u32, Color)> {
+        let pixel = self.image.get_pixel(x, y);
+        let mut color = Color {
+            r: pixel[0],
+            g: pixel[1],
+            b: pixel[2],
+        };
+        if let Some(cutoff) = self.cutoff {
+            if pixel[3] < cutoff {
+                return None;
+            }
+            if let Some(background) = self.options.background {
+                color = checkerboard::blend(color, pixel[3], background);
+            }
+        }
+        if !self.options.adjustments.is_none() {
+            color = self.options.adjustments.apply(color);
+        }
+        if self.options.color_mode != ColorMode::TrueColor {
+            color = self.options.color_mode.apply(color);
+        }
+        let (mut x, mut y) = (x + self.placement.offset.0, y + self.placement.offset.1);
+        self.placement
+            .place(&mut x, &mut y)
+            .then_some((x, y, color))
+    }
+}
+
+impl Iterator for PixelStream<'_> {
+    type Item = (u32, u32, Color);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pixels = match &mut self.inner {
+            Inner::Buffered(pixels) => return pixels.next(),
+            Inner::Image(pixels) => pixels,
+        };
+        let (width, height) = pixels.image.dimensions();
+        while pixels.y < height && width > 0 {
+            let (x, y) = (pixels.x, pixels.y);
+            pixels.x += 1;
+            if pixels.x == width {
+                pixels.x = 0;
+                pixels.y += 1;
+            }
+            if let Some(pixel) = pixels.finish(x, y) {
+                return Some(pixel);
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.inner {
+            Inner::Buffered(pixels) => pixels.size_hint(),
+            Inner::Image(pixels) => {
+                let (width, height) = pixels.image.dimensions();
+                let done = u64::from(pixels.y) * u64::from(width) + u64::from(pixels.x);
+                let left = (u64::from(width) * u64::from(height)).saturating_sub(done);
+                (0, usize::try_from(left).ok())
+            }
+        }
+    }
+}
+
+/// Loads an image like [`load_image_with`](crate::load_image_with), but returns its
+/// pixels as an iterator instead of a vector.
+///
+/// The image is decoded and resized up front; the pixels are converted as the iterator
+/// advances. They are the same, in the same order, as those `load_image_with` returns.
+///
+/// # Returns
+///
+/// A `Result` which is:
+/// - `Ok(PixelStream)`: An iterator over the `(x, y, color)` tuples.
+/// - `Err(RaelImgError)`: If the file could not be read or decoded.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael::{Canvas, Color};
+/// use rael_img::stream::load_image_iter;
+/// use rael_img::LoadOptions;
+///
+/// let mut canvas = Canvas::new(200, 60, Color { r: 0, g: 0, b: 0 });
+/// let pixels = load_image_iter("./assets/wallpaper.jpg", &LoadOptions::default()).unwrap();
+/// for (x, y, color) in pixels {
+///     canvas.set_pixel(x as usize, y as usize, 1, color);
+/// }
+/// ```
+pub fn load_image_iter(
+    path: &str,
+    options: &LoadOptions,
+) -> Result<PixelStream<'static>, RaelImgError> {
+    let decoded = pipeline::decode_using(&std::fs::read(path)?, options, &file_decoder(path))?;
+    Ok(PixelStream::new(
+        Cow::Owned(resized_owned(decoded, options)),
+        options,
+    ))
+}
+
+/// Processes a decoded image like [`process_image`](pipeline::process_image), but returns
+/// its pixels as an iterator instead of a vector.
+///
+/// Without resizing, cropping or turning, the stream reads from `image` directly.
+pub fn process_image_iter<'a>(image: &'a DynamicImage, options: &LoadOptions) -> PixelStream<'a> {
+    PixelStream::new(pipeline::resized_image(image, options), options)
+}
+
+/// Resizes a decoded image, reusing its buffer when there is nothing to do.
+fn resized_owned(decoded: PartialImage, options: &LoadOptions) -> DynamicImage {
+    if let Cow::Owned(image) = pipeline::resized(&decoded, options) {
+        return image;
+    }
+    decoded.image
+}
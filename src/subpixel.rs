@@ -0,0 +1,235 @@
+//! Smooth sub-pixel motion through precomputed phase variants.
+//!
+//! A sprite that scrolls slower than one pixel per frame moves in visible steps, because the
+//! canvas only has whole pixels. Resampling the sprite at its fractional position every frame
+//! hides the steps but is expensive. [`PhaseVariants`] resamples it once per sub-pixel phase
+//! instead, for example at offsets of 0, ¼, ½ and ¾ pixel, and [`at`](PhaseVariants::at)
+//! picks the variant closest to a fractional position.
+//!
+//! Each variant is rendered by area-sampling a high-resolution copy of the sprite, so its
+//! edges are partially covered. Pixel lists have no alpha, which is why
+//! [`SubpixelOptions::background`] matters: edge pixels are blended with it, and without it
+//! they are either kept or dropped at half coverage, which only smooths the interior.
+
+use crate::LoadOptions;
+use crate::decode::file_decoder;
+use crate::error::RaelImgError;
+use crate::pipeline::{self, prepared, target_size};
+use crate::resample::resample;
+use image::DynamicImage;
+use rael::Color;
+
+/// The most phases per axis; more are not visible on a terminal.
+const MAX_PHASES: u32 = 16;
+
+/// The axes a sprite moves along.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SubpixelAxis {
+    /// Horizontal motion only.
+    #[default]
+    Horizontal,
+    /// Vertical motion only.
+    Vertical,
+    /// Both axes, with `phases × phases` variants.
+    Both,
+}
+
+/// Options for [`PhaseVariants`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubpixelOptions {
+    /// The number of phases per axis, from 1 to 16.
+    pub phases: u32,
+    /// The axes to precompute phases for.
+    pub axis: SubpixelAxis,
+    /// The color the sprite is drawn over, used to blend partially covered pixels. `None`
+    /// keeps pixels that are at least half covered.
+    pub background: Option<Color>,
+}
+
+impl Default for SubpixelOptions {
+    fn default() -> Self {
+        Self {
+            phases: 4,
+            axis: SubpixelAxis::default(),
+            background: None,
+        }
+    }
+}
+
+/// A sprite resampled at every sub-pixel phase.
+///
+/// Variants are positioned at the canvas origin and are one pixel larger than the sprite
+/// along each phased axis, because a shifted sprite overlaps one more pixel.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael::Color;
+/// use rael_img::subpixel::{PhaseVariants, SubpixelOptions};
+/// use rael_img::LoadOptions;
+///
+/// let options = LoadOptions {
+///     width: Some(16),
+///     ..LoadOptions::default()
+/// };
+/// let subpixel = SubpixelOptions {
+///     background: Some(Color { r: 10, g: 10, b: 30 }),
+///     ..SubpixelOptions::default()
+/// };
+/// let cloud = PhaseVariants::load("./assets/cloud.png", &options, &subpixel).unwrap();
+///
+/// let mut x = 0.0;
+/// loop {
+///     let pixels = cloud.at(x, 4.0);
+///     // Draw `pixels` onto the canvas.
+///     x += 0.3;
+/// #   break;
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhaseVariants {
+    phases_x: u32,
+    phases_y: u32,
+    variants: Vec<Vec<(u32, u32, Color)>>,
+}
+
+impl PhaseVariants {
+    /// Loads an image and precomputes its phase variants.
+    ///
+    /// The image is sized like [`load_image_with`](crate::load_image_with) does; the
+    /// position and the finishing steps of `options` are not applied.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is:
+    /// - `Ok(PhaseVariants)`: The variants.
+    /// - `Err(RaelImgError)`: If the image could not be read or decoded.
+    pub fn load(
+        path: &str,
+        options: &LoadOptions,
+        subpixel: &SubpixelOptions,
+    ) -> Result<Self, RaelImgError> {
+        let decoded = pipeline::decode_using(&std::fs::read(path)?, options, &file_decoder(path))?;
+        Ok(Self::from_image(&decoded.image, options, subpixel))
+    }
+
+    /// Precomputes the phase variants of a decoded image.
+    pub fn from_image(
+        image: &DynamicImage,
+        options: &LoadOptions,
+        subpixel: &SubpixelOptions,
+    ) -> Self {
+        let phases = subpixel.phases.clamp(1, MAX_PHASES);
+        let (phases_x, phases_y) = match subpixel.axis {
+            SubpixelAxis::Horizontal => (phases, 1),
+            SubpixelAxis::Vertical => (1, phases),
+            SubpixelAxis::Both => (phases, phases),
+        };
+        let (width, height) = target_size(image.width(), image.height(), options);
+        let detail = resample(
+            &options.orientation.apply(&prepared(image, options)),
+            width * phases_x,
+            height * phases_y,
+            &options.resampling(),
+        )
+        .to_rgba8();
+
+        let mut variants = Vec::with_capacity((phases_x * phases_y) as usize);
+        for phase_y in 0..phases_y {
+            for phase_x in 0..phases_x {
+                let mut pixels = Vec::new();
+                for y in 0..height + u32::from(phases_y > 1) {
+                    for x in 0..width + u32::from(phases_x > 1) {
+                        // The detail pixels covered by this output pixel, shifted by the phase.
+                        let left = i64::from(x * phases_x) - i64::from(phase_x);
+                        let top = i64::from(y * phases_y) - i64::from(phase_y);
+                        let mut sum = [0.0f32; 4];
+                        for dy in
+                            top.max(0)..(top + i64::from(phases_y)).min(detail.height().into())
+                        {
+                            for dx in
+                                left.max(0)..(left + i64::from(phases_x)).min(detail.width().into())
+                            {
+                                let [r, g, b, a] = detail.get_pixel(dx as u32, dy as u32).0;
+                                let alpha = f32::from(a) / 255.0;
+                                sum[0] += f32::from(r) * alpha;
+                                sum[1] += f32::from(g) * alpha;
+                                sum[2] += f32::from(b) * alpha;
+                                sum[3] += alpha;
+                            }
+                        }
+                        let area = (phases_x * phases_y) as f32;
+                        if let Some(color) =
+                            cover(sum.map(|value| value / area), subpixel.background)
+                        {
+                            pixels.push((x, y, color));
+                        }
+                    }
+                }
+                variants.push(pixels);
+            }
+        }
+
+        Self {
+            phases_x,
+            phases_y,
+            variants,
+        }
+    }
+
+    /// Returns the number of horizontal and vertical phases.
+    pub fn phases(&self) -> (u32, u32) {
+        (self.phases_x, self.phases_y)
+    }
+
+    /// Returns the variant shifted right by `phase_x / phases` and down by
+    /// `phase_y / phases` pixels, positioned at the origin.
+    ///
+    /// Phases wrap around the number of phases of their axis.
+    pub fn variant(&self, phase_x: u32, phase_y: u32) -> &[(u32, u32, Color)] {
+        let index = (phase_y % self.phases_y) * self.phases_x + phase_x % self.phases_x;
+        &self.variants[index as usize]
+    }
+
+    /// Returns the pixels of the sprite with its top-left corner at a fractional canvas
+    /// position, using the nearest phase.
+    ///
+    /// Negative coordinates are treated as 0. Along an axis without phases the position is
+    /// rounded to the nearest pixel.
+    pub fn at(&self, x: f32, y: f32) -> Vec<(u32, u32, Color)> {
+        let (offset_x, phase_x) = split(x, self.phases_x);
+        let (offset_y, phase_y) = split(y, self.phases_y);
+        self.variant(phase_x, phase_y)
+            .iter()
+            .map(|&(px, py, color)| (px + offset_x, py + offset_y, color))
+            .collect()
+    }
+}
+
+/// Splits a coordinate into a whole pixel offset and the nearest of `phases` phases.
+fn split(position: f32, phases: u32) -> (u32, u32) {
+    let steps = (position.max(0.0) * phases as f32).round() as u64;
+    (
+        (steps / u64::from(phases)) as u32,
+        (steps % u64::from(phases)) as u32,
+    )
+}
+
+/// Turns a premultiplied area average into a pixel color, or `None` if it is not drawn.
+fn cover([r, g, b, alpha]: [f32; 4], background: Option<Color>) -> Option<Color> {
+    let channel = |value: f32| value.round().clamp(0.0, 255.0) as u8;
+    match background {
+        _ if alpha <= 0.0 => None,
+        Some(under) => Some(Color {
+            r: channel(r + f32::from(under.r) * (1.0 - alpha)),
+            g: channel(g + f32::from(under.g) * (1.0 - alpha)),
+            b: channel(b + f32::from(under.b) * (1.0 - alpha)),
+        }),
+        None if alpha < 0.5 => None,
+        None => Some(Color {
+            r: channel(r / alpha),
+            g: channel(g / alpha),
+            b: channel(b / alpha),
+        }),
+    }
+}
@@ -0,0 +1,428 @@
+//! Queries about the terminal the image will be shown on.
+
+use crate::width::AmbiguousWidth;
+#[cfg(feature = "terminal-query")]
+use std::time::Duration;
+
+/// The size of one terminal character cell in screen pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CellSize {
+    /// The cell width in pixels.
+    pub width: u32,
+    /// The cell height in pixels.
+    pub height: u32,
+}
+
+impl CellSize {
+    /// The cell shape most terminals approximate. Used when the real size is unknown.
+    pub const TYPICAL: CellSize = CellSize {
+        width: 8,
+        height: 16,
+    };
+
+    /// Returns the cell's height divided by its width, `2.0` for a typical terminal font.
+    pub fn aspect_ratio(&self) -> f32 {
+        if self.width == 0 {
+            return 2.0;
+        }
+        self.height as f32 / self.width as f32
+    }
+}
+
+/// Detects the pixel size of a terminal cell.
+///
+/// The window size reported by the `TIOCGWINSZ` ioctl is tried first. Many terminals leave
+/// its pixel fields at zero, in which case the terminal is asked directly with the
+/// `CSI 14 t` text-area query, waiting at most `timeout` for the reply. The query briefly
+/// enables raw mode if it is not already enabled.
+///
+/// Returns `None` if neither method works, e.g. when stdout is not a terminal or on
+/// platforms other than Unix.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::terminal::{cell_size, CellSize};
+/// use std::time::Duration;
+///
+/// let cell = cell_size(Duration::from_millis(100)).unwrap_or(CellSize::TYPICAL);
+/// println!("cells are {:.2}x taller than wide", cell.aspect_ratio());
+/// ```
+#[cfg(feature = "cell-size")]
+pub fn cell_size(timeout: Duration) -> Option<CellSize> {
+    cell_size_from_ioctl().or_else(|| cell_size_from_query(timeout))
+}
+
+/// Reads the cell size from the `TIOCGWINSZ` window size, if the terminal fills it in.
+#[cfg(feature = "cell-size")]
+pub fn cell_size_from_ioctl() -> Option<CellSize> {
+    let size = crossterm::terminal::window_size().ok()?;
+    cell_from_area(
+        u32::from(size.width),
+        u32::from(size.height),
+        size.columns,
+        size.rows,
+    )
+}
+
+/// Asks the terminal for its text-area size in pixels with `CSI 14 t` and divides it by the
+/// number of cells.
+#[cfg(feature = "cell-size")]
+pub fn cell_size_from_query(timeout: Duration) -> Option<CellSize> {
+    let (columns, rows) = crossterm::terminal::size().ok()?;
+    let (width, height) = query::text_area_pixels(timeout)?;
+    cell_from_area(width, height, columns, rows)
+}
+
+#[cfg(feature = "cell-size")]
+fn cell_from_area(width: u32, height: u32, columns: u16, rows: u16) -> Option<CellSize> {
+    if width == 0 || height == 0 || columns == 0 || rows == 0 {
+        return None;
+    }
+    Some(CellSize {
+        width: width / u32::from(columns),
+        height: height / u32::from(rows),
+    })
+}
+
+#[cfg(all(feature = "terminal-query", unix))]
+mod query {
+    use crossterm::terminal::{disable_raw_mode, enable_raw_mode, is_raw_mode_enabled};
+    use std::io::Write;
+    use std::time::{Duration, Instant};
+
+    /// Sends `CSI 14 t` and parses the `CSI 4 ; height ; width t` reply.
+    #[cfg(feature = "cell-size")]
+    pub(super) fn text_area_pixels(timeout: Duration) -> Option<(u32, u32)> {
+        let reply = exchange(b"\x1b[14t", |reply| reply.ends_with(b"t"), timeout)?;
+        parse_reply(&reply)
+    }
+
+    /// Sends a kitty graphics query followed by the primary device attributes request
+    /// (`CSI c`) and returns whether kitty graphics and sixel are supported.
+    ///
+    /// Every terminal answers the device attributes request, so the reply to it ends the
+    /// wait; a kitty reply, if any, arrives before it.
+    pub(super) fn graphics_support(timeout: Duration) -> Option<(bool, bool)> {
+        let request = b"\x1b_Gi=31,s=1,v=1,a=q,t=d,f=24;AAAA\x1b\\\x1b[c";
+        let reply = exchange(request, |reply| device_attributes(reply).is_some(), timeout)?;
+        let text = String::from_utf8_lossy(&reply);
+        let kitty = text.contains("\x1b_Gi=31;OK");
+        let sixel = device_attributes(&reply)?
+            .split(';')
+            .any(|code| code == "4");
+        Some((kitty, sixel))
+    }
+
+    /// Returns the parameters of a complete `CSI ? … c` reply.
+    fn device_attributes(reply: &[u8]) -> Option<&str> {
+        let text = std::str::from_utf8(reply).ok()?;
+        text.rsplit_once("\x1b[?")?.1.strip_suffix('c')
+    }
+
+    /// Writes `request` in raw mode and reads the reply until `done` accepts it.
+    fn exchange(
+        request: &[u8],
+        done: impl Fn(&[u8]) -> bool,
+        timeout: Duration,
+    ) -> Option<Vec<u8>> {
+        // SAFETY: isatty only inspects the given file descriptors.
+        let is_tty = unsafe {
+            libc::isatty(libc::STDIN_FILENO) == 1 && libc::isatty(libc::STDOUT_FILENO) == 1
+        };
+        if !is_tty {
+            return None;
+        }
+
+        let was_raw = is_raw_mode_enabled().ok()?;
+        if !was_raw {
+            enable_raw_mode().ok()?;
+        }
+        let reply = send_and_read(request, done, timeout);
+        if !was_raw {
+            let _ = disable_raw_mode();
+        }
+        reply
+    }
+
+    fn send_and_read(
+        request: &[u8],
+        done: impl Fn(&[u8]) -> bool,
+        timeout: Duration,
+    ) -> Option<Vec<u8>> {
+        let mut stdout = std::io::stdout();
+        stdout.write_all(request).ok()?;
+        stdout.flush().ok()?;
+
+        let deadline = Instant::now() + timeout;
+        let mut reply = Vec::new();
+        while !done(&reply) {
+            let remaining = deadline.checked_duration_since(Instant::now())?;
+            let mut poll_fd = libc::pollfd {
+                fd: libc::STDIN_FILENO,
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            // SAFETY: `poll_fd` is a valid pollfd for the duration of the call.
+            let ready = unsafe { libc::poll(&mut poll_fd, 1, remaining.as_millis() as i32) };
+            if ready <= 0 {
+                return None;
+            }
+            let mut byte = 0u8;
+            // SAFETY: reads at most one byte into a valid one-byte buffer.
+            let read = unsafe { libc::read(libc::STDIN_FILENO, (&mut byte as *mut u8).cast(), 1) };
+            if read != 1 {
+                return None;
+            }
+            reply.push(byte);
+        }
+        Some(reply)
+    }
+
+    #[cfg(feature = "cell-size")]
+    fn parse_reply(reply: &[u8]) -> Option<(u32, u32)> {
+        let text = std::str::from_utf8(reply).ok()?;
+        let body = text.rsplit("\x1b[4;").next()?.strip_suffix('t')?;
+        let (height, width) = body.split_once(';')?;
+        Some((width.parse().ok()?, height.parse().ok()?))
+    }
+}
+
+#[cfg(all(feature = "terminal-query", not(unix)))]
+mod query {
+    use std::time::Duration;
+
+    #[cfg(feature = "cell-size")]
+    pub(super) fn text_area_pixels(_timeout: Duration) -> Option<(u32, u32)> {
+        None
+    }
+
+    pub(super) fn graphics_support(_timeout: Duration) -> Option<(bool, bool)> {
+        None
+    }
+}
+
+/// The number of colors a terminal can display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColorDepth {
+    /// No color support at all.
+    Monochrome,
+    /// The 16 standard ANSI colors.
+    Ansi16,
+    /// The xterm 256-color palette.
+    Ansi256,
+    /// 24-bit RGB colors.
+    TrueColor,
+}
+
+/// How much of Unicode a terminal can be expected to render.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnicodeLevel {
+    /// Only printable ASCII is safe.
+    Ascii,
+    /// The locale is UTF-8, so block and quadrant glyphs are available.
+    Unicode,
+}
+
+/// The capabilities of the terminal the program runs in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TermCaps {
+    /// The supported color depth.
+    pub color: ColorDepth,
+    /// The supported character repertoire.
+    pub unicode: UnicodeLevel,
+    /// Whether the kitty graphics protocol is available.
+    pub kitty: bool,
+    /// Whether sixel graphics are available. Images are only encoded as sixel with the
+    /// `sixel` feature.
+    pub sixel: bool,
+    /// Whether the iTerm2 inline image protocol is available.
+    pub iterm2: bool,
+    /// How wide ambiguous-width glyphs such as `▀` are drawn.
+    pub ambiguous_width: AmbiguousWidth,
+}
+
+impl TermCaps {
+    /// Detects the capabilities of the current terminal from environment variables.
+    ///
+    /// This looks at `TERM`, `TERM_PROGRAM`, `COLORTERM`, `NO_COLOR`, the locale variables
+    /// and terminal-specific markers like `KITTY_WINDOW_ID`. It never writes to the
+    /// terminal, so it is safe to call before raw mode is enabled. Terminals that hide
+    /// behind a generic `TERM` (for example inside tmux) are reported conservatively.
+    ///
+    /// Ambiguous-width glyphs are assumed to be wide when the locale is Chinese, Japanese
+    /// or Korean, which is how most terminals configure themselves for those locales.
+    pub fn detect() -> Self {
+        Self::from_env(|name| std::env::var(name).ok())
+    }
+
+    /// Detects the capabilities like [`detect`](TermCaps::detect), then asks the terminal
+    /// which graphics protocols it supports, waiting at most `timeout` for the answer.
+    ///
+    /// The terminal is sent a kitty graphics query and the primary device attributes
+    /// request, whose reply lists sixel support. This finds protocols that the environment
+    /// does not reveal, for example xterm started with sixel enabled. The query briefly
+    /// enables raw mode if it is not already enabled. When stdout is not a terminal or the
+    /// terminal does not answer in time, the result is the same as [`detect`].
+    ///
+    /// Needs the `terminal-query` feature, which `cell-size` also enables.
+    ///
+    /// [`detect`]: TermCaps::detect
+    #[cfg(feature = "terminal-query")]
+    pub fn probe(timeout: Duration) -> Self {
+        let mut caps = Self::detect();
+        if let Some((kitty, sixel)) = query::graphics_support(timeout) {
+            caps.kitty |= kitty;
+            caps.sixel |= sixel;
+        }
+        caps
+    }
+
+    /// Detects capabilities from an arbitrary variable lookup, e.g. a captured environment.
+    pub fn from_env(var: impl Fn(&str) -> Option<String>) -> Self {
+        let term = var("TERM").unwrap_or_default().to_ascii_lowercase();
+        let program = var("TERM_PROGRAM").unwrap_or_default().to_ascii_lowercase();
+        let colorterm = var("COLORTERM").unwrap_or_default().to_ascii_lowercase();
+        let has = |name: &str| var(name).is_some_and(|value| !value.is_empty());
+
+        let kitty = term.contains("kitty") || has("KITTY_WINDOW_ID") || program == "ghostty";
+        let wezterm = program == "wezterm" || has("WEZTERM_EXECUTABLE");
+        let iterm2 = program == "iterm.app" || has("ITERM_SESSION_ID");
+        let konsole = has("KONSOLE_VERSION");
+        let windows_terminal = has("WT_SESSION");
+        // conhost without a TERM variable: the classic 16-color Windows console.
+        let legacy_console = term.is_empty()
+            && !windows_terminal
+            && var("OS").is_some_and(|os| os.eq_ignore_ascii_case("windows_nt"));
+
+        let color = if has("NO_COLOR") || term == "dumb" {
+            ColorDepth::Monochrome
+        } else if legacy_console {
+            ColorDepth::Ansi16
+        } else if colorterm == "truecolor"
+            || colorterm == "24bit"
+            || term.contains("direct")
+            || kitty
+            || wezterm
+            || iterm2
+            || konsole
+            || windows_terminal
+        {
+            ColorDepth::TrueColor
+        } else if term.contains("256color") {
+            ColorDepth::Ansi256
+        } else if term.is_empty() {
+            ColorDepth::Monochrome
+        } else {
+            ColorDepth::Ansi16
+        };
+
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .find_map(|name| var(name).filter(|value| !value.is_empty()))
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let unicode = if locale.contains("utf-8")
+            || locale.contains("utf8")
+            || kitty
+            || wezterm
+            || windows_terminal
+        {
+            UnicodeLevel::Unicode
+        } else {
+            UnicodeLevel::Ascii
+        };
+
+        let ambiguous_width = if ["ja", "ko", "zh"]
+            .iter()
+            .any(|lang| locale.starts_with(lang))
+        {
+            AmbiguousWidth::Wide
+        } else {
+            AmbiguousWidth::Narrow
+        };
+
+        let sixel = wezterm
+            || konsole
+            || term.contains("sixel")
+            || term.starts_with("foot")
+            || term.starts_with("mlterm")
+            || program == "mintty";
+
+        Self {
+            color,
+            unicode,
+            kitty: kitty || wezterm || konsole,
+            sixel,
+            iterm2: iterm2 || wezterm || program == "mintty",
+            ambiguous_width,
+        }
+    }
+}
+
+/// The best way a terminal can show images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GraphicsSupport {
+    /// The kitty graphics protocol.
+    Kitty,
+    /// The iTerm2 inline image protocol.
+    Iterm2,
+    /// Sixel graphics. Needs the `sixel` feature.
+    #[cfg(feature = "sixel")]
+    Sixel,
+    /// No graphics protocol, so images are drawn with character cells in this color depth.
+    Cells(ColorDepth),
+}
+
+impl GraphicsSupport {
+    /// Returns the best way to show images with the given capabilities, preferring the
+    /// protocols in the same order as [`OutputMode::for_caps`](crate::adaptive::OutputMode::for_caps).
+    pub fn for_caps(caps: &TermCaps) -> Self {
+        if caps.kitty {
+            return GraphicsSupport::Kitty;
+        }
+        if caps.iterm2 {
+            return GraphicsSupport::Iterm2;
+        }
+        #[cfg(feature = "sixel")]
+        if caps.sixel {
+            return GraphicsSupport::Sixel;
+        }
+        GraphicsSupport::Cells(caps.color)
+    }
+}
+
+/// Reports the best way the current terminal can show images, from environment variables.
+///
+/// This is [`GraphicsSupport::for_caps`] of [`TermCaps::detect`], so it never writes to
+/// the terminal. With the `terminal-query` feature, [`detect_protocol_with_query`] also
+/// asks the terminal itself.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::terminal::{detect_protocol, ColorDepth, GraphicsSupport};
+///
+/// match detect_protocol() {
+///     GraphicsSupport::Cells(ColorDepth::TrueColor) => println!("truecolor cells"),
+///     GraphicsSupport::Cells(depth) => println!("cells with {depth:?}"),
+///     protocol => println!("{protocol:?}"),
+/// }
+/// ```
+pub fn detect_protocol() -> GraphicsSupport {
+    GraphicsSupport::for_caps(&TermCaps::detect())
+}
+
+/// Reports the best way the current terminal can show images, asking the terminal with
+/// device attribute queries as [`TermCaps::probe`] does and waiting at most `timeout`.
+///
+/// Needs the `terminal-query` feature, which `cell-size` also enables.
+#[cfg(feature = "terminal-query")]
+pub fn detect_protocol_with_query(timeout: Duration) -> GraphicsSupport {
+    GraphicsSupport::for_caps(&TermCaps::probe(timeout))
+}
@@ -0,0 +1,407 @@
+//! Golden-image snapshot tests for scenes built on this crate.
+//!
+//! [`assert_snapshot`] compares the pixels a test renders with a snapshot file stored next to
+//! the tests and fails with a description of the differences. Snapshots are plain text, one
+//! line per row with a `rrggbb` hex code per pixel, so they review well in diffs:
+//!
+//! ```text
+//! rael-img snapshot 1
+//! origin 10 4
+//! size 3 2
+//! ff0000 00ff00 ......
+//! 0000ff ffffff ffffff
+//! ```
+//!
+//! `......` marks a position without a pixel. A missing snapshot is written on the first run;
+//! setting the environment variable `RAEL_IMG_UPDATE_SNAPSHOTS=1` rewrites all of them after
+//! an intended change. When a comparison fails, the new rendering is written next to the
+//! snapshot with the extension `.new`, ready to be inspected or copied over.
+//!
+//! Decoding, resizing and every other step of this crate are deterministic for the same
+//! input, options and crate version, so snapshots only change when the rendering does.
+//! [`Tolerance`] absorbs the small rounding differences a filter or dependency update causes.
+
+use crate::grid::PixelGrid;
+use rael::Color;
+use std::fmt::{self, Write};
+use std::path::Path;
+
+/// The environment variable that makes [`assert_snapshot`] rewrite snapshots.
+pub const UPDATE_VARIABLE: &str = "RAEL_IMG_UPDATE_SNAPSHOTS";
+
+const HEADER: &str = "rael-img snapshot 1";
+
+/// How far a rendering may differ from its snapshot and still match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Tolerance {
+    /// The largest difference of a single color channel that still counts as equal.
+    pub channel: u8,
+    /// The number of pixels that may differ by more than `channel`, or be missing on one side.
+    pub pixels: usize,
+}
+
+impl Tolerance {
+    /// Requires an exact match.
+    pub const EXACT: Tolerance = Tolerance {
+        channel: 0,
+        pixels: 0,
+    };
+}
+
+/// A pixel that differs between a rendering and its snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelDifference {
+    /// The canvas coordinate of the pixel.
+    pub position: (u32, u32),
+    /// The pixel in the snapshot, `None` if the snapshot has none there.
+    pub expected: Option<Color>,
+    /// The rendered pixel, `None` if the rendering has none there.
+    pub actual: Option<Color>,
+}
+
+/// The result of a failed comparison.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotDiff {
+    /// Every pixel outside the channel tolerance, in row-major order.
+    pub differences: Vec<PixelDifference>,
+    /// The largest channel difference between pixels present on both sides.
+    pub max_channel_difference: u8,
+}
+
+impl fmt::Display for SnapshotDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} pixels differ (largest channel difference {})",
+            self.differences.len(),
+            self.max_channel_difference
+        )?;
+        for difference in self.differences.iter().take(10) {
+            let (x, y) = difference.position;
+            writeln!(
+                f,
+                "  ({x}, {y}): expected {}, got {}",
+                hex(difference.expected),
+                hex(difference.actual)
+            )?;
+        }
+        if self.differences.len() > 10 {
+            writeln!(f, "  and {} more", self.differences.len() - 10)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the pixels sorted in row-major order, keeping the last of duplicate coordinates.
+///
+/// Two pixel lists that draw the same picture are equal after normalizing.
+pub fn normalize(pixels: &[(u32, u32, Color)]) -> Vec<(u32, u32, Color)> {
+    let Some((min_x, min_y, _, _)) = crate::grid::bounds(pixels) else {
+        return Vec::new();
+    };
+    PixelGrid::from_pixels(pixels).to_pixels((min_x, min_y))
+}
+
+/// Serializes pixels into the snapshot text format.
+///
+/// # Examples
+///
+/// ```
+/// use rael::Color;
+/// use rael_img::testing::render_snapshot;
+///
+/// let red = Color { r: 255, g: 0, b: 0 };
+/// let text = render_snapshot(&[(1, 0, red), (2, 1, red)]);
+/// assert!(text.ends_with("ff0000 ......\n...... ff0000\n"));
+/// ```
+pub fn render_snapshot(pixels: &[(u32, u32, Color)]) -> String {
+    let mut text = format!("{HEADER}\n");
+    let Some((min_x, min_y, _, _)) = crate::grid::bounds(pixels) else {
+        text.push_str("origin 0 0\nsize 0 0\n");
+        return text;
+    };
+    let grid = PixelGrid::from_pixels(pixels);
+    let _ = writeln!(text, "origin {min_x} {min_y}");
+    let _ = writeln!(text, "size {} {}", grid.width, grid.height);
+    for row in grid.cells.chunks(grid.width as usize) {
+        let cells: Vec<String> = row.iter().map(|&cell| hex(cell)).collect();
+        text.push_str(&cells.join(" "));
+        text.push('\n');
+    }
+    text
+}
+
+/// Parses the snapshot text format back into pixels, in row-major order.
+///
+/// Returns `None` if the text is not a snapshot.
+pub fn parse_snapshot(text: &str) -> Option<Vec<(u32, u32, Color)>> {
+    let mut lines = text.lines();
+    if lines.next()?.trim() != HEADER {
+        return None;
+    }
+    let numbers = |line: Option<&str>, key: &str| -> Option<(u32, u32)> {
+        let mut words = line?.strip_prefix(key)?.split_whitespace();
+        let pair = (words.next()?.parse().ok()?, words.next()?.parse().ok()?);
+        words.next().is_none().then_some(pair)
+    };
+    let (origin_x, origin_y) = numbers(lines.next(), "origin ")?;
+    let (width, height) = numbers(lines.next(), "size ")?;
+
+    let mut pixels = Vec::new();
+    for y in 0..height {
+        let cells: Vec<&str> = lines.next()?.split_whitespace().collect();
+        if cells.len() != width as usize {
+            return None;
+        }
+        for (x, cell) in cells.into_iter().enumerate() {
+            if cell == "......" {
+                continue;
+            }
+            let color = parse_hex(cell)?;
+            pixels.push((origin_x + x as u32, origin_y + y, color));
+        }
+    }
+    Some(pixels)
+}
+
+/// Compares a rendering with the expected pixels.
+///
+/// # Returns
+///
+/// `None` if they match within `tolerance`, otherwise the differences.
+pub fn compare(
+    actual: &[(u32, u32, Color)],
+    expected: &[(u32, u32, Color)],
+    tolerance: &Tolerance,
+) -> Option<SnapshotDiff> {
+    let actual = normalize(actual);
+    let expected = normalize(expected);
+    let mut positions: Vec<(u32, u32)> = actual
+        .iter()
+        .chain(&expected)
+        .map(|&(x, y, _)| (y, x))
+        .collect();
+    positions.sort_unstable();
+    positions.dedup();
+
+    let lookup = |pixels: &[(u32, u32, Color)], (y, x): (u32, u32)| {
+        pixels
+            .binary_search_by_key(&(y, x), |&(px, py, _)| (py, px))
+            .ok()
+            .map(|index| pixels[index].2)
+    };
+    let mut differences = Vec::new();
+    let mut max_channel_difference = 0;
+    for position in positions {
+        let (expected, actual) = (lookup(&expected, position), lookup(&actual, position));
+        let differs = match (expected, actual) {
+            (Some(a), Some(b)) => {
+                let difference = [a.r.abs_diff(b.r), a.g.abs_diff(b.g), a.b.abs_diff(b.b)]
+                    .into_iter()
+                    .max()
+                    .unwrap_or(0);
+                max_channel_difference = max_channel_difference.max(difference);
+                difference > tolerance.channel
+            }
+            _ => true,
+        };
+        if differs {
+            differences.push(PixelDifference {
+                position: (position.1, position.0),
+                expected,
+                actual,
+            });
+        }
+    }
+
+    (differences.len() > tolerance.pixels).then_some(SnapshotDiff {
+        differences,
+        max_channel_difference,
+    })
+}
+
+/// Asserts that pixels match the snapshot stored at `path`.
+///
+/// A missing snapshot is created, and every snapshot is rewritten when
+/// [`UPDATE_VARIABLE`] is set to `1`.
+///
+/// # Panics
+///
+/// If the rendering does not match within `tolerance`, after writing it to `path` with the
+/// extension `.new`; or if the snapshot cannot be read, parsed or written.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::testing::{assert_snapshot, Tolerance};
+/// use rael_img::{load_image_with, LoadOptions};
+///
+/// let options = LoadOptions {
+///     width: Some(24),
+///     ..LoadOptions::default()
+/// };
+/// let pixels = load_image_with("./assets/title.png", &options).unwrap();
+/// assert_snapshot("./tests/snapshots/title.snap", &pixels, &Tolerance::EXACT);
+/// ```
+#[track_caller]
+pub fn assert_snapshot(
+    path: impl AsRef<Path>,
+    pixels: &[(u32, u32, Color)],
+    tolerance: &Tolerance,
+) {
+    let path = path.as_ref();
+    let rendered = render_snapshot(pixels);
+    let update = std::env::var(UPDATE_VARIABLE).is_ok_and(|value| value == "1");
+    if update || !path.exists() {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .unwrap_or_else(|error| panic!("cannot create {}: {error}", parent.display()));
+        }
+        std::fs::write(path, rendered)
+            .unwrap_or_else(|error| panic!("cannot write {}: {error}", path.display()));
+        return;
+    }
+
+    let text = std::fs::read_to_string(path)
+        .unwrap_or_else(|error| panic!("cannot read {}: {error}", path.display()));
+    let expected = parse_snapshot(&text)
+        .unwrap_or_else(|| panic!("{} is not a rael-img snapshot", path.display()));
+    if let Some(diff) = compare(pixels, &expected, tolerance) {
+        let new = path.with_extension("new");
+        std::fs::write(&new, rendered)
+            .unwrap_or_else(|error| panic!("cannot write {}: {error}", new.display()));
+        panic!(
+            "rendering does not match {}\n{diff}the new rendering is in {}; set {UPDATE_VARIABLE}=1 to accept it",
+            path.display(),
+            new.display()
+        );
+    }
+}
+
+fn hex(color: Option<Color>) -> String {
+    match color {
+        Some(color) => format!("{:02x}{:02x}{:02x}", color.r, color.g, color.b),
+        None => "......".to_string(),
+    }
+}
+
+fn parse_hex(text: &str) -> Option<Color> {
+    if text.len() != 6 || !text.is_ascii() {
+        return None;
+    }
+    let channel = |range: std::ops::Range<usize>| u8::from_str_radix(&text[range], 16).ok();
+    Some(Color {
+        r: channel(0..2)?,
+        g: channel(2..4)?,
+        b: channel(4..6)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Color = Color { r: 255, g: 0, b: 0 };
+    const GREEN: Color = Color { r: 0, g: 255, b: 0 };
+
+    fn snapshot_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir()
+            .join(format!("rael_img-{}-snapshots", std::process::id()))
+            .join(name)
+    }
+
+    #[test]
+    fn snapshots_round_trip() {
+        let pixels = vec![(10, 4, RED), (11, 4, GREEN), (11, 5, RED)];
+        let text = render_snapshot(&pixels);
+        assert_eq!(
+            text,
+            "rael-img snapshot 1\norigin 10 4\nsize 2 2\nff0000 00ff00\n...... ff0000\n"
+        );
+        assert_eq!(parse_snapshot(&text), Some(pixels));
+        assert_eq!(parse_snapshot(&render_snapshot(&[])), Some(Vec::new()));
+    }
+
+    #[test]
+    fn malformed_snapshots_are_rejected() {
+        let valid = render_snapshot(&[(0, 0, RED), (1, 0, GREEN)]);
+        assert_eq!(parse_snapshot("not a snapshot"), None);
+        assert_eq!(parse_snapshot(&valid.replace("00ff00", "00ff0")), None);
+        assert_eq!(parse_snapshot(&valid.replace(" 00ff00", "")), None);
+        assert_eq!(parse_snapshot(&valid.replace("size 2 1", "size 2 2")), None);
+    }
+
+    #[test]
+    fn normalize_sorts_and_keeps_the_last_duplicate() {
+        let pixels = [(1, 1, RED), (0, 0, RED), (1, 1, GREEN)];
+        assert_eq!(normalize(&pixels), [(0, 0, RED), (1, 1, GREEN)]);
+    }
+
+    #[test]
+    fn compare_respects_the_tolerance() {
+        let expected = [(0, 0, RED), (1, 0, RED)];
+        let close = [(0, 0, Color { r: 250, g: 3, b: 0 }), (1, 0, RED)];
+        assert!(compare(&expected, &expected, &Tolerance::EXACT).is_none());
+
+        let diff = compare(&close, &expected, &Tolerance::EXACT).unwrap();
+        assert_eq!(diff.max_channel_difference, 5);
+        assert_eq!(
+            diff.differences,
+            [PixelDifference {
+                position: (0, 0),
+                expected: Some(RED),
+                actual: Some(close[0].2),
+            }]
+        );
+        let loose = Tolerance {
+            channel: 5,
+            pixels: 0,
+        };
+        assert!(compare(&close, &expected, &loose).is_none());
+
+        let missing = compare(&expected[..1], &expected, &Tolerance::EXACT).unwrap();
+        assert_eq!(missing.differences[0].actual, None);
+        let one_allowed = Tolerance {
+            channel: 0,
+            pixels: 1,
+        };
+        assert!(compare(&expected[..1], &expected, &one_allowed).is_none());
+    }
+
+    #[test]
+    fn assert_snapshot_writes_missing_snapshots_then_compares() {
+        let path = snapshot_path("written.snap");
+        let pixels = [(2, 3, RED)];
+        assert_snapshot(&path, &pixels, &Tolerance::EXACT);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            render_snapshot(&pixels)
+        );
+        assert_snapshot(&path, &pixels, &Tolerance::EXACT);
+        std::fs::remove_file(&path).unwrap();
+        // Fails while the other test still has files in it.
+        let _ = std::fs::remove_dir(path.parent().unwrap());
+    }
+
+    #[test]
+    fn assert_snapshot_writes_the_new_rendering_on_mismatch() {
+        let path = snapshot_path("mismatch.snap");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, render_snapshot(&[(0, 0, RED)])).unwrap();
+
+        let changed = [(0, 0, GREEN)];
+        let result = std::panic::catch_unwind(|| {
+            assert_snapshot(&path, &changed, &Tolerance::EXACT);
+        });
+        let new = path.with_extension("new");
+        let written = std::fs::read_to_string(&new);
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&new).unwrap();
+        let _ = std::fs::remove_dir(path.parent().unwrap());
+
+        let message = result.unwrap_err();
+        let message = message.downcast_ref::<String>().unwrap();
+        assert!(message.contains("1 pixels differ"), "{message}");
+        assert_eq!(written.unwrap(), render_snapshot(&changed));
+    }
+}
@@ -0,0 +1,99 @@
+//! Infinite tiling of a texture for scrolling backgrounds.
+
+use crate::grid::PixelGrid;
+use rael::Color;
+
+/// A texture that repeats endlessly in both axes.
+///
+/// Build it once from a pixel list (usually the output of [`crate::load_image`]) and call
+/// [`TiledBackground::view`] every frame with the current scroll offset. Only the pixels
+/// inside the requested viewport are produced, so the cost of a frame depends on the
+/// viewport size rather than on how far the background has scrolled.
+#[derive(Debug, Clone)]
+pub struct TiledBackground {
+    grid: PixelGrid,
+}
+
+impl TiledBackground {
+    /// Creates a tiled background from a pixel list.
+    ///
+    /// The bounding box of the pixels defines a single tile; any position offset that was
+    /// applied when loading the texture is removed.
+    pub fn from_pixels(texture: &[(u32, u32, Color)]) -> Self {
+        Self {
+            grid: PixelGrid::from_pixels(texture),
+        }
+    }
+
+    /// Creates a tiled background whose tile is the `width` × `height` box at `origin`,
+    /// keeping the cells no pixel covers empty so a transparent margin repeats too.
+    pub(crate) fn from_region(
+        texture: &[(u32, u32, Color)],
+        origin: (u32, u32),
+        (width, height): (u32, u32),
+    ) -> Self {
+        Self {
+            grid: PixelGrid::from_region(texture, origin, width, height),
+        }
+    }
+
+    /// Returns the `(width, height)` of a single tile.
+    pub fn tile_size(&self) -> (u32, u32) {
+        (self.grid.width, self.grid.height)
+    }
+
+    /// Returns the visible pixels of the infinitely tiled texture.
+    ///
+    /// # Arguments
+    ///
+    /// * `scroll` - The `(x, y)` offset into the infinite plane. Any value is valid,
+    ///              including negative offsets; the texture wraps in both directions.
+    /// * `size` - The `(width, height)` of the visible window.
+    /// * `position` - The `(x, y)` offset applied to every returned pixel, placing the
+    ///                window on the canvas.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `(x, y, color)` tuples covering the window. It is empty if the
+    /// texture itself is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rael_img::{load_image, tile::TiledBackground};
+    ///
+    /// let texture = load_image("./assets/stars.png", None, None, (0, 0), false, 1.0).unwrap();
+    /// let background = TiledBackground::from_pixels(&texture);
+    ///
+    /// let mut scroll_x = 0i64;
+    /// // In the game loop:
+    /// scroll_x += 1;
+    /// let visible = background.view((scroll_x, 0), (80, 48), (0, 0));
+    /// ```
+    pub fn view(
+        &self,
+        scroll: (i64, i64),
+        size: (u32, u32),
+        position: (u32, u32),
+    ) -> Vec<(u32, u32, Color)> {
+        if self.grid.is_empty() {
+            return Vec::new();
+        }
+
+        let tile_width = i64::from(self.grid.width);
+        let tile_height = i64::from(self.grid.height);
+        let mut pixels = Vec::with_capacity(size.0 as usize * size.1 as usize);
+
+        for y in 0..size.1 {
+            let texture_y = (i64::from(y) + scroll.1).rem_euclid(tile_height) as u32;
+            for x in 0..size.0 {
+                let texture_x = (i64::from(x) + scroll.0).rem_euclid(tile_width) as u32;
+                if let Some(color) = self.grid.get(texture_x, texture_y) {
+                    pixels.push((x + position.0, y + position.1, color));
+                }
+            }
+        }
+
+        pixels
+    }
+}
@@ -0,0 +1,234 @@
+//! Global tint and brightness animation for ambient lighting.
+//!
+//! A [`TintCycle`] interpolates between [`TintKeyframe`]s over a repeating period (a
+//! day/night cycle, a flickering torch, a thunderstorm) and yields the [`Tint`] for any
+//! point in time. Applying a tint goes through per-channel lookup tables, so recoloring an
+//! already loaded image every frame costs one table lookup per channel.
+
+use rael::Color;
+use std::time::Duration;
+
+/// A multiplicative color filter with a brightness factor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tint {
+    /// The light color. White leaves colors unchanged, blue-ish colors give night scenes.
+    pub color: Color,
+    /// How strongly `color` is applied, from `0.0` (no effect) to `1.0` (full multiply).
+    pub strength: f32,
+    /// A factor applied after the color filter; `1.0` keeps the original brightness.
+    pub brightness: f32,
+}
+
+impl Tint {
+    /// A tint that leaves every color unchanged.
+    pub const NONE: Tint = Tint {
+        color: Color {
+            r: 255,
+            g: 255,
+            b: 255,
+        },
+        strength: 0.0,
+        brightness: 1.0,
+    };
+
+    /// Creates a tint from a color, strength and brightness.
+    pub fn new(color: Color, strength: f32, brightness: f32) -> Self {
+        Self {
+            color,
+            strength,
+            brightness,
+        }
+    }
+
+    /// Linearly interpolates between two tints; `t = 0.0` gives `self`, `t = 1.0` gives `other`.
+    pub fn lerp(&self, other: &Tint, t: f32) -> Tint {
+        let t = t.clamp(0.0, 1.0);
+        let channel =
+            |a: u8, b: u8| (f32::from(a) + (f32::from(b) - f32::from(a)) * t).round() as u8;
+        Tint {
+            color: Color {
+                r: channel(self.color.r, other.color.r),
+                g: channel(self.color.g, other.color.g),
+                b: channel(self.color.b, other.color.b),
+            },
+            strength: self.strength + (other.strength - self.strength) * t,
+            brightness: self.brightness + (other.brightness - self.brightness) * t,
+        }
+    }
+
+    /// Applies the tint to a single color.
+    pub fn apply(&self, color: Color) -> Color {
+        let lut = TintLut::new(self);
+        lut.apply(color)
+    }
+
+    /// Applies the tint to every pixel of a list, returning a new list.
+    pub fn apply_to(&self, pixels: &[(u32, u32, Color)]) -> Vec<(u32, u32, Color)> {
+        let mut output = Vec::with_capacity(pixels.len());
+        self.apply_into(pixels, &mut output);
+        output
+    }
+
+    /// Applies the tint to every pixel of `source`, writing into `output`.
+    ///
+    /// `output` is cleared first and its allocation reused, so a game loop can keep one
+    /// buffer per cached image instead of allocating every frame.
+    pub fn apply_into(&self, source: &[(u32, u32, Color)], output: &mut Vec<(u32, u32, Color)>) {
+        let lut = TintLut::new(self);
+        output.clear();
+        output.extend(source.iter().map(|&(x, y, color)| (x, y, lut.apply(color))));
+    }
+}
+
+impl Default for Tint {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// Per-channel lookup tables for one tint.
+struct TintLut {
+    r: [u8; 256],
+    g: [u8; 256],
+    b: [u8; 256],
+}
+
+impl TintLut {
+    fn new(tint: &Tint) -> Self {
+        let strength = tint.strength.clamp(0.0, 1.0);
+        let table = |light: u8| {
+            let factor = (1.0 - strength + strength * f32::from(light) / 255.0) * tint.brightness;
+            let mut table = [0u8; 256];
+            for (value, entry) in table.iter_mut().enumerate() {
+                *entry = (value as f32 * factor).round().clamp(0.0, 255.0) as u8;
+            }
+            table
+        };
+        Self {
+            r: table(tint.color.r),
+            g: table(tint.color.g),
+            b: table(tint.color.b),
+        }
+    }
+
+    fn apply(&self, color: Color) -> Color {
+        Color {
+            r: self.r[usize::from(color.r)],
+            g: self.g[usize::from(color.g)],
+            b: self.b[usize::from(color.b)],
+        }
+    }
+}
+
+/// A tint at a point within a [`TintCycle`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TintKeyframe {
+    /// The offset from the start of the cycle.
+    pub at: Duration,
+    /// The tint at that offset.
+    pub tint: Tint,
+}
+
+/// A repeating sequence of tint keyframes.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael::Color;
+/// use rael_img::load_image;
+/// use rael_img::tint::{Tint, TintCycle, TintKeyframe};
+/// use std::time::{Duration, Instant};
+///
+/// let scene = load_image("./assets/village.png", None, None, (0, 0), false, 1.0).unwrap();
+/// let minute = |m: u64| Duration::from_secs(m * 60);
+///
+/// let day = TintCycle::new(
+///     minute(24),
+///     vec![
+///         TintKeyframe { at: minute(0), tint: Tint::new(Color { r: 80, g: 90, b: 200 }, 0.8, 0.5) },
+///         TintKeyframe { at: minute(6), tint: Tint::new(Color { r: 255, g: 170, b: 120 }, 0.5, 0.9) },
+///         TintKeyframe { at: minute(12), tint: Tint::NONE },
+///         TintKeyframe { at: minute(18), tint: Tint::new(Color { r: 255, g: 120, b: 90 }, 0.6, 0.8) },
+///     ],
+/// );
+///
+/// let start = Instant::now();
+/// let mut frame = Vec::new();
+/// // In the game loop:
+/// day.sample(start.elapsed()).apply_into(&scene, &mut frame);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TintCycle {
+    period: Duration,
+    keyframes: Vec<TintKeyframe>,
+}
+
+impl TintCycle {
+    /// Creates a cycle of length `period` from keyframes.
+    ///
+    /// Keyframes are sorted by offset; offsets beyond `period` wrap around. Between the last
+    /// and the first keyframe the tint interpolates across the period boundary.
+    pub fn new(period: Duration, mut keyframes: Vec<TintKeyframe>) -> Self {
+        if !period.is_zero() {
+            for keyframe in &mut keyframes {
+                keyframe.at =
+                    Duration::from_nanos((keyframe.at.as_nanos() % period.as_nanos()) as u64);
+            }
+        }
+        keyframes.sort_by_key(|keyframe| keyframe.at);
+        Self { period, keyframes }
+    }
+
+    /// Returns the length of one cycle.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Returns the keyframes, sorted by offset.
+    pub fn keyframes(&self) -> &[TintKeyframe] {
+        &self.keyframes
+    }
+
+    /// Returns the interpolated tint at `elapsed` time since the cycle started.
+    ///
+    /// An empty cycle yields [`Tint::NONE`]; a single keyframe yields its tint at all times.
+    pub fn sample(&self, elapsed: Duration) -> Tint {
+        let (first, last) = match (self.keyframes.first(), self.keyframes.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Tint::NONE,
+        };
+        if self.keyframes.len() == 1 || self.period.is_zero() {
+            return first.tint;
+        }
+
+        let period = self.period.as_secs_f64();
+        let t = elapsed.as_secs_f64() % period;
+
+        match self
+            .keyframes
+            .iter()
+            .position(|keyframe| keyframe.at.as_secs_f64() > t)
+        {
+            Some(index) if index > 0 => {
+                let from = &self.keyframes[index - 1];
+                let to = &self.keyframes[index];
+                interpolate(from, to, from.at.as_secs_f64(), to.at.as_secs_f64(), t)
+            }
+            _ => {
+                // Between the last keyframe and the first one of the next cycle.
+                let start = last.at.as_secs_f64();
+                let end = first.at.as_secs_f64() + period;
+                let t = if t < start { t + period } else { t };
+                interpolate(last, first, start, end, t)
+            }
+        }
+    }
+}
+
+fn interpolate(from: &TintKeyframe, to: &TintKeyframe, start: f64, end: f64, t: f64) -> Tint {
+    let span = end - start;
+    if span <= 0.0 {
+        return to.tint;
+    }
+    from.tint.lerp(&to.tint, ((t - start) / span) as f32)
+}
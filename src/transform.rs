@@ -0,0 +1,134 @@
+//! Rotating and mirroring images while loading.
+//!
+//! Games need sprites facing both ways, and photos are often stored on their side. Setting
+//! [`LoadOptions::orientation`](crate::LoadOptions::orientation) turns the image as part of
+//! the load, so a single asset on disk covers every direction:
+//!
+//! ```no_run
+//! use rael_img::transform::Orientation;
+//! use rael_img::{load_image_with, LoadOptions};
+//!
+//! let right = LoadOptions {
+//!     width: Some(16),
+//!     ..LoadOptions::default()
+//! };
+//! let left = LoadOptions {
+//!     orientation: Orientation::MIRRORED,
+//!     ..right
+//! };
+//! let facing_right = load_image_with("./assets/hero.png", &right).unwrap();
+//! let facing_left = load_image_with("./assets/hero.png", &left).unwrap();
+//! ```
+//!
+//! The target size in the options is that of the turned image: a 90° rotation of a 40 by
+//! 20 image with a width of 10 gives 10 by 20 pixels.
+
+use image::DynamicImage;
+use std::borrow::Cow;
+
+/// A clockwise rotation by a multiple of 90°.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Rotation {
+    /// No rotation.
+    #[default]
+    None,
+    /// A quarter turn clockwise.
+    Rotate90,
+    /// A half turn.
+    Rotate180,
+    /// A quarter turn counterclockwise.
+    Rotate270,
+}
+
+impl Rotation {
+    /// Returns `true` if the rotation swaps width and height.
+    pub fn swaps_axes(self) -> bool {
+        matches!(self, Rotation::Rotate90 | Rotation::Rotate270)
+    }
+}
+
+/// A rotation followed by mirroring.
+///
+/// The flips apply to the rotated image, so `flip_horizontal` always mirrors left and right
+/// as the image is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Orientation {
+    /// The clockwise rotation.
+    pub rotation: Rotation,
+    /// Whether to mirror left and right.
+    pub flip_horizontal: bool,
+    /// Whether to mirror top and bottom.
+    pub flip_vertical: bool,
+}
+
+impl Orientation {
+    /// The image as it is stored.
+    pub const IDENTITY: Self = Self {
+        rotation: Rotation::None,
+        flip_horizontal: false,
+        flip_vertical: false,
+    };
+
+    /// The image mirrored left to right, as for a sprite facing the other way.
+    pub const MIRRORED: Self = Self {
+        rotation: Rotation::None,
+        flip_horizontal: true,
+        flip_vertical: false,
+    };
+
+    /// Returns `true` if the orientation leaves images unchanged.
+    pub fn is_identity(&self) -> bool {
+        // A half turn is both flips at once.
+        let rotation = self.rotation == Rotation::Rotate180;
+        self.rotation != Rotation::Rotate90
+            && self.rotation != Rotation::Rotate270
+            && self.flip_horizontal == rotation
+            && self.flip_vertical == rotation
+    }
+
+    /// Returns the size of a `width` by `height` image after turning it.
+    pub fn size(&self, width: u32, height: u32) -> (u32, u32) {
+        if self.rotation.swaps_axes() {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    /// Turns an image, borrowing it if the orientation leaves it unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rael_img::transform::{Orientation, Rotation};
+    ///
+    /// let photo = image::open("./assets/photo.jpg").unwrap();
+    /// let upright = Orientation {
+    ///     rotation: Rotation::Rotate90,
+    ///     ..Orientation::IDENTITY
+    /// }
+    /// .apply(&photo);
+    /// ```
+    pub fn apply<'a>(&self, image: &'a DynamicImage) -> Cow<'a, DynamicImage> {
+        if self.is_identity() {
+            return Cow::Borrowed(image);
+        }
+        let mut turned = match self.rotation {
+            Rotation::None => image.clone(),
+            Rotation::Rotate90 => image.rotate90(),
+            Rotation::Rotate180 => image.rotate180(),
+            Rotation::Rotate270 => image.rotate270(),
+        };
+        if self.flip_horizontal {
+            turned = turned.fliph();
+        }
+        if self.flip_vertical {
+            turned = turned.flipv();
+        }
+        Cow::Owned(turned)
+    }
+}
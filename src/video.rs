@@ -0,0 +1,252 @@
+//! Video playback through ffmpeg (feature `video`).
+//!
+//! [`Video`] runs the `ffmpeg` command-line tool to decode a video file, in any format
+//! ffmpeg reads such as MP4 or WebM, into raw frames, then processes every frame like
+//! [`LoadedImage::from_image`] processes a still image. Frames are decoded on demand as the
+//! iterator advances, so only one is in memory at a time.
+//!
+//! Running the tool instead of linking ffmpeg's libraries keeps the feature free of
+//! dependencies and of a C toolchain at build time. `ffmpeg` and `ffprobe` must be on the
+//! `PATH` when a video is opened.
+
+use crate::LoadOptions;
+use crate::error::RaelImgError;
+use crate::loaded::LoadedImage;
+use crate::stderr::StderrDrain;
+use image::{DynamicImage, RgbaImage};
+use std::io::{self, BufReader, Read};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::time::Duration;
+
+/// The frame rate assumed when ffprobe does not report one.
+const DEFAULT_FRAME_RATE: f64 = 25.0;
+
+/// A decoded and processed video frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VideoFrame {
+    /// The processed frame.
+    pub image: LoadedImage,
+    /// When the frame is shown, from the start of the video.
+    pub timestamp: Duration,
+}
+
+/// A video decoded frame by frame.
+///
+/// Iterating yields the frames in order, each processed with the options the video was
+/// opened with. Dropping the video stops ffmpeg.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::video::Video;
+/// use rael_img::LoadOptions;
+/// use std::time::Instant;
+///
+/// let options = LoadOptions {
+///     width: Some(80),
+///     ..LoadOptions::default()
+/// };
+/// let start = Instant::now();
+/// for frame in Video::open("./assets/intro.mp4", &options).unwrap() {
+///     let frame = frame.unwrap();
+///     if let Some(wait) = frame.timestamp.checked_sub(start.elapsed()) {
+///         std::thread::sleep(wait);
+///     }
+///     // Draw `frame.image`.
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Video {
+    child: Child,
+    stdout: BufReader<ChildStdout>,
+    stderr: StderrDrain,
+    options: LoadOptions,
+    width: u32,
+    height: u32,
+    frame_rate: f64,
+    index: u32,
+    finished: bool,
+}
+
+impl Video {
+    /// Opens a video file and starts decoding it.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is:
+    /// - `Ok(Video)`: The video, positioned before its first frame.
+    /// - `Err(RaelImgError)`: If ffmpeg or ffprobe is not installed (`Unsupported`), or
+    ///   the file has no video stream ffprobe can read.
+    pub fn open(path: &str, options: &LoadOptions) -> Result<Self, RaelImgError> {
+        options.validate()?;
+        // ffmpeg rotates frames as the rotation metadata asks, so the probed size is
+        // already swapped for portrait phone videos.
+        let (width, height, frame_rate) = probe(path)?;
+        let mut child = Command::new("ffmpeg")
+            .args(["-v", "error", "-nostdin", "-i", path, "-an", "-sn"])
+            .args(["-map", "0:v:0", "-f", "rawvideo", "-pix_fmt", "rgba"])
+            .args(["-r", &frame_rate.to_string(), "-"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(missing_tool("ffmpeg"))?;
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let stderr = StderrDrain::spawn(child.stderr.take());
+        Ok(Self {
+            child,
+            stdout: BufReader::new(stdout),
+            stderr,
+            options: *options,
+            width,
+            height,
+            frame_rate,
+            index: 0,
+            finished: false,
+        })
+    }
+
+    /// Returns the `(width, height)` of the frames before processing.
+    pub fn source_size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Returns the number of frames per second.
+    pub fn frame_rate(&self) -> f64 {
+        self.frame_rate
+    }
+
+    /// Returns how long every frame is shown.
+    pub fn frame_duration(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.frame_rate)
+    }
+
+    /// Reads the next raw frame, or `None` at the end of the video.
+    fn read_frame(&mut self) -> Result<Option<RgbaImage>, RaelImgError> {
+        let mut buffer = vec![0; self.width as usize * self.height as usize * 4];
+        let mut filled = 0;
+        while filled < buffer.len() {
+            match self.stdout.read(&mut buffer[filled..]) {
+                Ok(0) => break,
+                Ok(read) => filled += read,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+        if filled == 0 {
+            self.finish()?;
+            return Ok(None);
+        }
+        if filled < buffer.len() {
+            self.finish()?;
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        Ok(RgbaImage::from_raw(self.width, self.height, buffer))
+    }
+
+    /// Waits for ffmpeg to exit and turns a failure into an error with its message.
+    fn finish(&mut self) -> Result<(), RaelImgError> {
+        self.finished = true;
+        let message = self.stderr.finish();
+        let status = self.child.wait()?;
+        if status.success() {
+            return Ok(());
+        }
+        Err(io::Error::other(format!("ffmpeg failed ({status}): {message}")).into())
+    }
+}
+
+impl Iterator for Video {
+    type Item = Result<VideoFrame, RaelImgError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let frame = match self.read_frame() {
+            Ok(frame) => frame?,
+            Err(error) => return Some(Err(error)),
+        };
+        let timestamp = Duration::from_secs_f64(f64::from(self.index) / self.frame_rate);
+        self.index += 1;
+        Some(Ok(VideoFrame {
+            image: LoadedImage::from_image(&DynamicImage::ImageRgba8(frame), &self.options),
+            timestamp,
+        }))
+    }
+}
+
+impl Drop for Video {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+/// Asks ffprobe for the width, height and frame rate of the first video stream, with the
+/// width and height swapped if the stream is rotated by a quarter turn.
+fn probe(path: &str) -> Result<(u32, u32, f64), RaelImgError> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-select_streams", "v:0"])
+        .args([
+            "-show_entries",
+            "stream=width,height,avg_frame_rate:stream_tags=rotate:stream_side_data=rotation",
+        ])
+        .args(["-of", "default=noprint_wrappers=1", path])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(missing_tool("ffprobe"))?;
+    if !output.status.success() {
+        let message = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(format!("ffprobe failed: {}", message.trim())).into());
+    }
+
+    let (mut width, mut height, mut frame_rate, mut rotation) = (None, None, None, 0.0);
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        match line.split_once('=') {
+            Some(("width", value)) => width = value.parse().ok(),
+            Some(("height", value)) => height = value.parse().ok(),
+            Some(("avg_frame_rate", value)) => frame_rate = parse_rate(value),
+            // Newer files store the rotation as display matrix side data, older ones as a
+            // `rotate` tag.
+            Some(("rotation" | "TAG:rotate", value)) => {
+                rotation = value.trim().parse::<f64>().unwrap_or(0.0);
+            }
+            _ => {}
+        }
+    }
+    let quarter_turn = (rotation.round() as i64).rem_euclid(180) == 90;
+    match (width, height) {
+        (Some(width), Some(height)) if width > 0 && height > 0 => {
+            let (width, height) = if quarter_turn {
+                (height, width)
+            } else {
+                (width, height)
+            };
+            Ok((width, height, frame_rate.unwrap_or(DEFAULT_FRAME_RATE)))
+        }
+        _ => Err(RaelImgError::Unsupported(format!(
+            "no video stream in `{path}`"
+        ))),
+    }
+}
+
+/// Parses a frame rate like `30000/1001`. Unknown rates (`0/0`) give `None`.
+fn parse_rate(value: &str) -> Option<f64> {
+    let (numerator, denominator) = value.split_once('/').unwrap_or((value, "1"));
+    let rate = numerator.parse::<f64>().ok()? / denominator.parse::<f64>().ok()?;
+    (rate.is_finite() && rate > 0.0).then_some(rate)
+}
+
+/// Reports a tool that is not installed as unsupported rather than as a missing file.
+fn missing_tool(tool: &'static str) -> impl Fn(io::Error) -> RaelImgError {
+    move |error| match error.kind() {
+        io::ErrorKind::NotFound => {
+            RaelImgError::Unsupported(format!("video decoding needs `{tool}` on the PATH"))
+        }
+        _ => error.into(),
+    }
+}
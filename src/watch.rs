@@ -0,0 +1,150 @@
+//! Reloading an image whenever its file changes (feature `watch`).
+//!
+//! While iterating on artwork it is handy to see every save in the terminal right away.
+//! [`ImageWatcher`] loads an image on a background thread, then loads it again each time
+//! the file changes, and delivers every result through a channel or a callback.
+//!
+//! The watcher polls the modification time and size of the file instead of relying on
+//! operating system notifications. That behaves the same on every platform, on network
+//! drives and in containers, at the cost of noticing a change up to one interval late. A
+//! change is only loaded once the file has stayed the same for a whole interval, so editors
+//! that write in several steps do not produce half-written images.
+
+use crate::LoadOptions;
+use crate::error::RaelImgError;
+use crate::loaded::LoadedImage;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+
+/// How often an [`ImageWatcher::new`] checks the file.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_millis(200);
+
+/// A background thread that reloads an image when its file changes.
+///
+/// The first result is the initial load. Dropping the watcher stops the thread.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rael_img::watch::ImageWatcher;
+/// use rael_img::LoadOptions;
+///
+/// let options = LoadOptions {
+///     width: Some(60),
+///     ..LoadOptions::default()
+/// };
+/// let watcher = ImageWatcher::new("./assets/title.png", &options);
+/// loop {
+///     if let Some(Ok(image)) = watcher.latest() {
+///         // Redraw with the new pixels.
+///         // for &(x, y, color) in image.pixels() {
+///         //     canvas.set_pixel(x as usize, y as usize, 1, color);
+///         // }
+///     }
+/// #   break;
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ImageWatcher {
+    receiver: Option<Receiver<Result<LoadedImage, RaelImgError>>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ImageWatcher {
+    /// Watches `path`, checking it every [`DEFAULT_INTERVAL`] and sending the results to
+    /// a channel read with [`latest`](ImageWatcher::latest) or
+    /// [`receiver`](ImageWatcher::receiver).
+    pub fn new(path: impl Into<String>, options: &LoadOptions) -> Self {
+        Self::with_interval(path, options, DEFAULT_INTERVAL)
+    }
+
+    /// Watches `path` like [`new`](ImageWatcher::new), checking it every `interval`.
+    pub fn with_interval(
+        path: impl Into<String>,
+        options: &LoadOptions,
+        interval: Duration,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = Self::with_callback(path, options, interval, move |result| {
+            // The receiver is gone only while the watcher is being dropped.
+            let _ = sender.send(result);
+        });
+        watcher.receiver = Some(receiver);
+        watcher
+    }
+
+    /// Watches `path`, checking it every `interval` and calling `callback` with every
+    /// result on the watcher's thread.
+    pub fn with_callback(
+        path: impl Into<String>,
+        options: &LoadOptions,
+        interval: Duration,
+        mut callback: impl FnMut(Result<LoadedImage, RaelImgError>) + Send + 'static,
+    ) -> Self {
+        let (path, options) = (path.into(), *options);
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+        let thread = std::thread::Builder::new()
+            .name("rael_img-watch".to_string())
+            .spawn(move || {
+                let mut loaded = stamp(&path);
+                callback(LoadedImage::load(&path, &options));
+                let mut seen = loaded;
+                while !stopped.load(Ordering::Relaxed) {
+                    std::thread::sleep(interval);
+                    let current = stamp(&path);
+                    // Load once the file has stopped changing; a missing file is usually
+                    // an editor replacing it.
+                    if current.is_some() && current == seen && current != loaded {
+                        loaded = current;
+                        callback(LoadedImage::load(&path, &options));
+                    }
+                    seen = current;
+                }
+            })
+            .ok();
+        Self {
+            receiver: None,
+            stop,
+            thread,
+        }
+    }
+
+    /// Returns the newest result that arrived since the last call, discarding older ones.
+    /// Always `None` for watchers created with
+    /// [`with_callback`](ImageWatcher::with_callback).
+    pub fn latest(&self) -> Option<Result<LoadedImage, RaelImgError>> {
+        self.receiver.as_ref()?.try_iter().last()
+    }
+
+    /// Returns the channel the results arrive on, to block on it or read every result.
+    pub fn receiver(&self) -> Option<&Receiver<Result<LoadedImage, RaelImgError>>> {
+        self.receiver.as_ref()
+    }
+
+    /// Returns `true` while the watcher's thread is running.
+    pub fn is_running(&self) -> bool {
+        self.thread
+            .as_ref()
+            .is_some_and(|thread| !thread.is_finished())
+    }
+}
+
+impl Drop for ImageWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// What identifies a version of the file: its modification time and size.
+fn stamp(path: &str) -> Option<(SystemTime, u64)> {
+    let meta = std::fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
@@ -0,0 +1,76 @@
+//! Display width of characters and text in terminal cells.
+//!
+//! Character-based renderers assume that every glyph covers exactly one cell. That is not
+//! true for wide CJK characters, and many block and shade glyphs are "ambiguous width" in
+//! Unicode: terminals configured for a CJK locale draw them two cells wide, which shears an
+//! image made of them. The helpers here let renderers measure glyphs under either convention
+//! and keep only those that really are one cell wide.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// How a terminal displays characters of ambiguous East Asian width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AmbiguousWidth {
+    /// One cell, as in most Western locales.
+    #[default]
+    Narrow,
+    /// Two cells, as in terminals configured for Chinese, Japanese or Korean.
+    Wide,
+}
+
+/// Returns the number of cells `ch` covers, or `None` for control characters.
+///
+/// Combining marks and other zero-width characters return `Some(0)`.
+pub fn char_width(ch: char, ambiguous: AmbiguousWidth) -> Option<usize> {
+    match ambiguous {
+        AmbiguousWidth::Narrow => ch.width(),
+        AmbiguousWidth::Wide => ch.width_cjk(),
+    }
+}
+
+/// Returns `true` if `ch` covers exactly one cell.
+pub fn is_single_cell(ch: char, ambiguous: AmbiguousWidth) -> bool {
+    char_width(ch, ambiguous) == Some(1)
+}
+
+/// Returns the number of cells `text` covers, measured grapheme by grapheme.
+///
+/// A grapheme cluster such as a letter with combining accents or an emoji sequence is
+/// counted once, and never as more than two cells.
+pub fn text_width(text: &str, ambiguous: AmbiguousWidth) -> usize {
+    text.graphemes(true)
+        .map(|grapheme| grapheme_width(grapheme, ambiguous))
+        .sum()
+}
+
+/// Returns the longest prefix of `text` that fits in `columns` cells, without splitting a
+/// grapheme cluster.
+///
+/// # Examples
+///
+/// ```
+/// use rael_img::width::{truncate_to_width, AmbiguousWidth};
+///
+/// assert_eq!(truncate_to_width("日本語", 5, AmbiguousWidth::Narrow), "日本");
+/// assert_eq!(truncate_to_width("e\u{301}te\u{301}", 2, AmbiguousWidth::Narrow), "e\u{301}t");
+/// ```
+pub fn truncate_to_width(text: &str, columns: usize, ambiguous: AmbiguousWidth) -> &str {
+    let mut used = 0;
+    for (index, grapheme) in text.grapheme_indices(true) {
+        used += grapheme_width(grapheme, ambiguous);
+        if used > columns {
+            return &text[..index];
+        }
+    }
+    text
+}
+
+fn grapheme_width(grapheme: &str, ambiguous: AmbiguousWidth) -> usize {
+    let width = match ambiguous {
+        AmbiguousWidth::Narrow => grapheme.width(),
+        AmbiguousWidth::Wide => grapheme.width_cjk(),
+    };
+    width.min(2)
+}